@@ -0,0 +1,126 @@
+//! # Benchmark: FBCU Codecs
+//!
+//! Compara los codecs de `FBCUEngine` (GZIP, zstd, fractal PIFS, wavelet
+//! CDF 5/3, híbrido y Visual DNA) sobre el corpus de templates (`ROADMAP_V2/07_TEMPLATES`,
+//! o el directorio en `FBCU_BENCH_CORPUS`). Antes de medir imprime el ratio
+//! de cada codec y el de la selección automática de `compress`.
+//!
+//! Ejecutar: `cargo bench --bench fbcu_codecs`
+
+use bitacora_core::fbcu::{CompressionType, FBCUConfig, FBCUEngine};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::path::{Path, PathBuf};
+
+const CODECS: [CompressionType; 6] = [
+    CompressionType::Gzip,
+    CompressionType::Zstd,
+    CompressionType::Fractal,
+    CompressionType::Wavelet,
+    CompressionType::Hybrid,
+    CompressionType::QuantumVisual,
+];
+
+/// Config por defecto con Visual DNA habilitado
+fn bench_config() -> FBCUConfig {
+    FBCUConfig {
+        enable_visual_dna: true,
+        ..Default::default()
+    }
+}
+
+/// Archivos del corpus (recursivo, orden estable)
+fn load_corpus() -> Vec<(PathBuf, Vec<u8>)> {
+    let root = std::env::var_os("FBCU_BENCH_CORPUS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("ROADMAP_V2/07_TEMPLATES"));
+
+    let mut pending = vec![root];
+    let mut files = Vec::new();
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).expect("corpus directory").flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(bytes) = std::fs::read(&path) {
+                files.push((path, bytes));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn report_ratios(corpus: &[(PathBuf, Vec<u8>)]) {
+    let original: usize = corpus.iter().map(|(_, bytes)| bytes.len()).sum();
+    let mut engine = FBCUEngine::new(bench_config()).unwrap();
+
+    for codec in CODECS {
+        let compressed: usize = corpus
+            .iter()
+            .map(|(_, bytes)| engine.compress_with(bytes, codec).unwrap().compressed_data.len())
+            .sum();
+        println!(
+            "{:?}: {} files, {} → {} bytes (ratio {:.2})",
+            codec,
+            corpus.len(),
+            original,
+            compressed,
+            original as f64 / compressed as f64
+        );
+    }
+
+    // Selección automática de `compress` (por archivo)
+    let compressed: usize = corpus
+        .iter()
+        .map(|(_, bytes)| engine.compress(bytes).unwrap().compressed_data.len())
+        .sum();
+    println!(
+        "Auto: {} files, {} → {} bytes (ratio {:.2})",
+        corpus.len(),
+        original,
+        compressed,
+        original as f64 / compressed as f64
+    );
+}
+
+fn bench_codecs(c: &mut Criterion) {
+    let corpus = load_corpus();
+    assert!(!corpus.is_empty(), "empty corpus");
+    report_ratios(&corpus);
+
+    let total: u64 = corpus.iter().map(|(_, bytes)| bytes.len() as u64).sum();
+    let mut engine = FBCUEngine::new(bench_config()).unwrap();
+
+    let mut group = c.benchmark_group("fbcu_template_corpus");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(total));
+
+    for codec in CODECS {
+        group.bench_function(BenchmarkId::new("compress", format!("{:?}", codec)), |b| {
+            b.iter(|| {
+                for (_, bytes) in &corpus {
+                    black_box(engine.compress_with(black_box(bytes), codec).unwrap());
+                }
+            })
+        });
+
+        let cores: Vec<_> = corpus
+            .iter()
+            .map(|(_, bytes)| engine.compress_with(bytes, codec).unwrap())
+            .collect();
+        group.bench_function(BenchmarkId::new("decompress", format!("{:?}", codec)), |b| {
+            b.iter(|| {
+                for core in &cores {
+                    // Motor nuevo: sin cache, se mide la decodificación real
+                    let mut engine = FBCUEngine::new(bench_config()).unwrap();
+                    black_box(engine.decompress(black_box(core)).unwrap());
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_codecs);
+criterion_main!(benches);
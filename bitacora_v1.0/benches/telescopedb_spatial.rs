@@ -0,0 +1,86 @@
+//! # Benchmark: TelescopeDB Spatial Index
+//!
+//! Compara `SphericalIndex` (octree sobre `to_cartesian`) contra el scan lineal
+//! que usaba `query_contextual`, con 10K, 100K y 1M cores.
+//!
+//! Ejecutar: `cargo bench --bench telescopedb_spatial`
+
+use bitacora_core::telescopedb::{SphericalCoords, SphericalIndex};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+const TOP_K: usize = 10;
+const RADIUS: f64 = 0.3;
+
+/// Coordenadas deterministas (LCG) con la distribución de tensores normalizados
+fn sample_coords(n: usize, seed: u64) -> Vec<SphericalCoords> {
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    (0..n)
+        .map(|_| SphericalCoords {
+            r: next(),
+            theta: next() * 2.0 * std::f64::consts::PI,
+            phi: next() * std::f64::consts::PI,
+        })
+        .collect()
+}
+
+/// Baseline: scan lineal + sort (implementación previa de `query_contextual`)
+fn linear_top_k(points: &[SphericalCoords], center: SphericalCoords) -> Vec<(usize, f64)> {
+    let mut results: Vec<(usize, f64)> = points
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| {
+            let d = center.distance(p);
+            if d <= RADIUS {
+                Some((i, d))
+            } else {
+                None
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| a.1.total_cmp(&b.1));
+    results.truncate(TOP_K);
+    results
+}
+
+fn bench_contextual_query(c: &mut Criterion) {
+    let queries = sample_coords(64, 7);
+
+    let mut group = c.benchmark_group("telescopedb_query_contextual");
+    group.sample_size(10);
+
+    for &size in SIZES.iter() {
+        let points = sample_coords(size, 42);
+
+        let mut index = SphericalIndex::new();
+        for (i, p) in points.iter().enumerate() {
+            index.insert(format!("core-{}", i), *p);
+        }
+
+        let mut q = 0;
+        group.bench_with_input(BenchmarkId::new("octree_top_k", size), &size, |b, _| {
+            b.iter(|| {
+                q = (q + 1) % queries.len();
+                black_box(index.nearest(queries[q], TOP_K, RADIUS))
+            })
+        });
+
+        let mut q = 0;
+        group.bench_with_input(BenchmarkId::new("linear_scan", size), &size, |b, _| {
+            b.iter(|| {
+                q = (q + 1) % queries.len();
+                black_box(linear_top_k(&points, queries[q]))
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_contextual_query);
+criterion_main!(benches);
@@ -120,7 +120,7 @@ fn test_wavelet_compression() {
 #[test]
 fn test_cache_lru() {
     let mut engine = FBCUEngine::new(FBCUConfig {
-        cache_size: 3,
+        cache_capacity_bytes: 3 * 27, // 3 entradas de 27 bytes
         ..Default::default()
     })
     .unwrap();
@@ -146,13 +146,18 @@ fn test_cache_lru() {
     engine.decompress(&core1).unwrap();
     assert_eq!(engine.metrics().cache_hits, 1);
     
-    // Descomprimir core4 (excede cache size)
+    // Descomprimir core4 (excede el presupuesto): desaloja core2, el menos reciente
     engine.decompress(&core4).unwrap();
     assert_eq!(engine.metrics().cache_misses, 4);
+    assert_eq!(engine.metrics().cache_evictions, 1);
+    engine.decompress(&core2).unwrap();
+    assert_eq!(engine.metrics().cache_misses, 5);
     
     println!("Cache metrics:");
     println!("  Hits: {}", engine.metrics().cache_hits);
     println!("  Misses: {}", engine.metrics().cache_misses);
+    println!("  Evictions: {}", engine.metrics().cache_evictions);
+    println!("  Hit rate: {:.2}", engine.metrics().cache_hit_rate());
 }
 
 /// Test 6: Datos pequeños sin compresión
@@ -647,6 +647,7 @@ fn cmd_document_with_template(file_path: &str, template_name: &str) -> Result<()
 /// Generate multi-document narrative with FlowQuery
 fn cmd_flow_document(files: &[String]) -> Result<()> {
     use bitacora_core::bstradivarius::narrative_builder::NarrativeBuilder;
+    use bitacora_core::bstradivarius::flow_query::FlowQuery;
     use bitacora_core::bstradivarius::document_graph::DocumentCategory;
     use std::path::PathBuf;
     
@@ -655,7 +656,10 @@ fn cmd_flow_document(files: &[String]) -> Result<()> {
     let root = env::current_dir()?;
     let templates_dir = root.join("templates");
     
-    let mut builder = NarrativeBuilder::new(root.clone(), Some(templates_dir))?;
+    // Relaciones persistidas junto al estado del watcher
+    let config = WatcherConfig::default();
+    let flow_query = FlowQuery::open(root.clone(), PathBuf::from(&config.voxel_db_path))?;
+    let mut builder = NarrativeBuilder::with_flow_query(flow_query, Some(templates_dir))?;
     
     // Indexar archivos
     let mut file_paths = Vec::new();
@@ -676,6 +680,8 @@ fn cmd_flow_document(files: &[String]) -> Result<()> {
         return Ok(());
     }
     
+    builder.flow_query().save()?;
+    
     CliFormatter::print_stage("Building", "narrative with Git context");
     
     // Generar narrativa
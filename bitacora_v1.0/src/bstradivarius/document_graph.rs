@@ -37,11 +37,16 @@
 //! graph.add_node(node);
 //! ```
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+use crate::qpx::{
+    EntanglementKind, QPXDecoder, QPXEncoder, QPXEntanglement, QPXEntanglementMap,
+    QPXError, QPXHeader, QPX_FOOTER_SIZE,
+};
+
 /// Categoría de documento en el proyecto
 ///
 /// Clasifica los documentos según su propósito en el proyecto.
@@ -98,6 +103,36 @@ pub enum RelationType {
     DependsOn,
 }
 
+impl From<&RelationType> for EntanglementKind {
+    fn from(relation_type: &RelationType) -> Self {
+        match relation_type {
+            RelationType::Imports => EntanglementKind::Imports,
+            RelationType::References => EntanglementKind::References,
+            RelationType::Implements => EntanglementKind::Implements,
+            RelationType::Decides => EntanglementKind::Decides,
+            RelationType::Tests => EntanglementKind::Tests,
+            RelationType::Supersedes => EntanglementKind::Supersedes,
+            RelationType::DependsOn => EntanglementKind::DependsOn,
+        }
+    }
+}
+
+impl RelationType {
+    /// Tipo de relación para un entanglement QPX (`None` si no es documental)
+    pub fn from_entanglement_kind(kind: EntanglementKind) -> Option<Self> {
+        match kind {
+            EntanglementKind::Imports => Some(Self::Imports),
+            EntanglementKind::References => Some(Self::References),
+            EntanglementKind::Implements => Some(Self::Implements),
+            EntanglementKind::Decides => Some(Self::Decides),
+            EntanglementKind::Tests => Some(Self::Tests),
+            EntanglementKind::Supersedes => Some(Self::Supersedes),
+            EntanglementKind::DependsOn => Some(Self::DependsOn),
+            EntanglementKind::Telescope | EntanglementKind::Other(_) => None,
+        }
+    }
+}
+
 /// Nodo del grafo de documentos
 ///
 /// Representa un documento individual con toda su metadata.
@@ -276,8 +311,18 @@ impl DocumentGraph {
     }
     
     /// Agrega una relación entre documentos
-    pub fn add_relation(&mut self, relation: DocumentRelation) {
-        self.edges.push(relation);
+    ///
+    /// Una relación con el mismo origen, destino y tipo que otra ya presente
+    /// se ignora (re-indexar o cargar dos veces no duplica edges). Retorna
+    /// `true` si se agregó.
+    pub fn add_relation(&mut self, relation: DocumentRelation) -> bool {
+        let exists = self.edges.iter().any(|e| {
+            e.from == relation.from && e.to == relation.to && e.relation_type == relation.relation_type
+        });
+        if !exists {
+            self.edges.push(relation);
+        }
+        !exists
     }
     
     /// Obtiene todos los documentos relacionados desde un nodo
@@ -350,6 +395,97 @@ impl DocumentGraph {
             .filter(|e| &e.to == path)
             .collect()
     }
+    
+    /// Relaciones agrupadas por documento origen como entanglement maps QPX
+    ///
+    /// Un map por origen, ordenados por path para que la salida sea estable.
+    pub fn to_entanglement_maps(&self) -> Vec<QPXEntanglementMap> {
+        let mut by_source: BTreeMap<&PathBuf, Vec<QPXEntanglement>> = BTreeMap::new();
+        
+        for edge in &self.edges {
+            by_source.entry(&edge.from).or_default().push(QPXEntanglement {
+                target_id: edge.to.to_string_lossy().into_owned(),
+                kind: EntanglementKind::from(&edge.relation_type),
+                weight: 1.0,
+                label: edge.description.clone(),
+                created_at: edge.created_at,
+            });
+        }
+        
+        by_source
+            .into_iter()
+            .map(|(source, entanglements)| QPXEntanglementMap {
+                source_id: source.to_string_lossy().into_owned(),
+                entanglements,
+            })
+            .collect()
+    }
+    
+    /// Agrega las relaciones de un entanglement map QPX
+    ///
+    /// Los entanglements que no son relaciones documentales (p.ej. TelescopeDB)
+    /// y las relaciones ya presentes se ignoran. Retorna el número de
+    /// relaciones agregadas.
+    pub fn add_entanglement_map(&mut self, map: &QPXEntanglementMap) -> usize {
+        let from = PathBuf::from(&map.source_id);
+        let mut added = 0;
+        
+        for entanglement in &map.entanglements {
+            if let Some(relation_type) = RelationType::from_entanglement_kind(entanglement.kind) {
+                let relation = DocumentRelation {
+                    from: from.clone(),
+                    to: PathBuf::from(&entanglement.target_id),
+                    relation_type,
+                    description: entanglement.label.clone(),
+                    created_at: entanglement.created_at,
+                };
+                if self.add_relation(relation) {
+                    added += 1;
+                }
+            }
+        }
+        
+        added
+    }
+    
+    /// Persistir las relaciones como registros QPX Entanglement (0x80)
+    ///
+    /// El archivo es la concatenación de un registro por documento origen.
+    pub fn save_relations_qpx(&self, path: &Path) -> crate::qpx::Result<()> {
+        let mut bytes = Vec::new();
+        for map in self.to_entanglement_maps() {
+            bytes.extend_from_slice(&QPXEncoder::encode_entanglement_map(&map)?);
+        }
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+    
+    /// Cargar relaciones guardadas con `save_relations_qpx`
+    ///
+    /// Los nodos no se persisten aquí: se reconstruyen al indexar los archivos.
+    /// Retorna el número de relaciones agregadas.
+    pub fn load_relations_qpx(&mut self, path: &Path) -> crate::qpx::Result<usize> {
+        let bytes = std::fs::read(path)?;
+        let mut offset = 0;
+        let mut added = 0;
+        
+        while offset < bytes.len() {
+            let header = QPXHeader::from_bytes(&bytes[offset..])?;
+            let record_end = offset + header.footer_offset as usize + QPX_FOOTER_SIZE;
+            if record_end > bytes.len() {
+                return Err(QPXError::DecodingFailed(format!(
+                    "Truncated entanglement record at offset {}",
+                    offset
+                )));
+            }
+            
+            let map = QPXDecoder::decode_entanglement_map(&bytes[offset..record_end])?;
+            added += self.add_entanglement_map(&map);
+            offset = record_end;
+        }
+        
+        Ok(added)
+    }
 }
 
 impl Default for DocumentGraph {
@@ -416,6 +552,54 @@ mod tests {
         assert_eq!(related[0].path, PathBuf::from("doc.md"));
     }
     
+    #[test]
+    fn test_relations_persist_through_qpx_entanglements() {
+        use tempfile::tempdir;
+        
+        let mut graph = DocumentGraph::new();
+        graph.add_relation(DocumentRelation::new(
+            PathBuf::from("src/fbcu/mod.rs"),
+            PathBuf::from("docs/compression.md"),
+            RelationType::Implements,
+            "Implementa la arquitectura de compresión".to_string(),
+        ));
+        graph.add_relation(DocumentRelation::new(
+            PathBuf::from("src/fbcu/mod.rs"),
+            PathBuf::from("src/qpx/mod.rs"),
+            RelationType::Imports,
+            String::new(),
+        ));
+        graph.add_relation(DocumentRelation::new(
+            PathBuf::from("tests/fbcu.rs"),
+            PathBuf::from("src/fbcu/mod.rs"),
+            RelationType::Tests,
+            "Valida round-trip".to_string(),
+        ));
+        
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("relations.qpx");
+        graph.save_relations_qpx(&path).unwrap();
+        
+        let mut loaded = DocumentGraph::new();
+        assert_eq!(loaded.load_relations_qpx(&path).unwrap(), 3);
+        assert_eq!(loaded.edge_count(), 3);
+        
+        let from_fbcu = loaded.get_relations_from(&PathBuf::from("src/fbcu/mod.rs"));
+        assert_eq!(from_fbcu.len(), 2);
+        assert_eq!(from_fbcu[0].relation_type, RelationType::Implements);
+        assert_eq!(from_fbcu[0].description, "Implementa la arquitectura de compresión");
+        assert_eq!(from_fbcu[0].created_at, graph.edges[0].created_at);
+        
+        let to_fbcu = loaded.get_relations_to(&PathBuf::from("src/fbcu/mod.rs"));
+        assert_eq!(to_fbcu.len(), 1);
+        assert_eq!(to_fbcu[0].relation_type, RelationType::Tests);
+        
+        // Cargar de nuevo no duplica edges
+        assert_eq!(loaded.load_relations_qpx(&path).unwrap(), 0);
+        assert_eq!(loaded.edge_count(), 3);
+        assert!(!loaded.add_relation(graph.edges[1].clone()));
+    }
+    
     #[test]
     fn test_get_by_category() {
         let mut graph = DocumentGraph::new();
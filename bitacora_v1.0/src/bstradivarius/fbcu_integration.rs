@@ -10,12 +10,13 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use anyhow::{Result, Context};
 use chrono::{DateTime, Utc};
 
-use crate::fbcu::{FBCUCore, FBCUEngine, FBCUConfig, CompressionType, FBCUMetadata};
-use crate::qpx::{QPXEncoder, QPXDecoder, QPXQuantumCore, TemplateMetadata as QPXTemplateMetadata, Pixel};
-use crate::voxeldb::{VoxelDB, TemplateEntry, TemplateCategory, CubicCoords};
+use crate::fbcu::{FBCUCache, FBCUCore, FBCUEngine, FBCUConfig, CompressionType, FBCUMetadata};
+use crate::qpx::{QPXEncoder, QPXDecoder, QPXQuantumCore, TemplateMetadata as QPXTemplateMetadata, Pixel, embedding_to_pixels};
+use crate::voxeldb::{VoxelDB, TemplateEntry, TemplateCategory, CubicCoords, embed_text};
 
 /// FBCU Integration for template compression and storage
 pub struct FBCUIntegration {
@@ -80,8 +81,14 @@ impl IntegrationStats {
 impl FBCUIntegration {
     /// Create new FBCU integration
     pub fn new(voxel_db_path: PathBuf) -> Result<Self> {
+        let cache = FBCUCache::shared(FBCUConfig::default().cache_capacity_bytes);
+        Self::with_cache(voxel_db_path, cache)
+    }
+    
+    /// Create FBCU integration sharing a decompression cache (e.g. with FlowPacks)
+    pub fn with_cache(voxel_db_path: PathBuf, cache: Arc<FBCUCache>) -> Result<Self> {
         let config = FBCUConfig::default();
-        let fbcu_engine = FBCUEngine::new(config)
+        let fbcu_engine = FBCUEngine::with_cache(config, cache)
             .context("Failed to initialize FBCU engine")?;
         let voxel_db = VoxelDB::new(voxel_db_path)
             .context("Failed to initialize VoxelDB")?;
@@ -152,7 +159,7 @@ impl FBCUIntegration {
         template.file_extension = file_extension;
         
         // 4. Generate 384D embedding → 128 pixels
-        let pixels = self.generate_semantic_pixels(markdown_content, &category);
+        let pixels = self.generate_semantic_pixels(markdown_content);
         
         // 5. Write QPX to VoxelDB
         let compressed_size = fbcu_core.compressed_data.len();
@@ -186,11 +193,10 @@ impl FBCUIntegration {
     
     /// Get original filename from template metadata (reads from QPX file)
     pub fn get_original_filename(&mut self, template_id: &str) -> Result<Option<String>> {
-        // Try to find QPX file
-        let qpx_path = self.voxel_db.find_template_qpx(template_id)?;
-        let quantum_core = self.voxel_db.read_template_qpx(&qpx_path)?;
+        // Only the QuantumMeta section is read; the FBCU blob is skipped
+        let summary = self.voxel_db.load_template_qpx_summary(template_id)?;
         
-        Ok(quantum_core.metadata.original_filename)
+        Ok(summary.metadata.original_filename)
     }
     
     /// Regenerate markdown from compressed template
@@ -212,18 +218,15 @@ impl FBCUIntegration {
         
         self.stats.cache_misses += 1;
         
-        // 2. Find QPX file
-        let qpx_path = if let Some(path) = self.qpx_path_cache.get(template_id) {
-            path.clone()
-        } else {
-            self.voxel_db.find_template_qpx(template_id)
-                .context("QPX file not found")?
+        // 2-3. Find and decode QPX (cached .qpxf path, otherwise pack or per-file lookup)
+        let quantum_core = match self.qpx_path_cache.get(template_id) {
+            Some(path) if !self.voxel_db.is_packed_storage() => self.voxel_db
+                .read_template_qpx(path)
+                .context("Failed to read QPX file")?,
+            _ => self.voxel_db.load_template_qpx(template_id)
+                .context("QPX data not found")?,
         };
         
-        // 3. Read and decode QPX
-        let quantum_core = self.voxel_db.read_template_qpx(&qpx_path)
-            .context("Failed to read QPX file")?;
-        
         self.stats.qpx_files_read += 1;
         
         // 4. Decompress FBCU data
@@ -249,64 +252,13 @@ impl FBCUIntegration {
     
     /// Generate semantic pixels from markdown content
     /// 
-    /// Maps text features to 384D embedding (128 pixels × 3 RGB channels)
+    /// Maps the text to a 384D embedding (128 pixels × 3 RGB channels) with
+    /// `voxeldb::embed_text`, the same embedding `VoxelDB::query_semantic` uses
+    /// for text queries, so stored concepts are searchable by meaning.
     /// 
-    /// # Algorithm
-    /// - R channel: Semantic density (keywords, concepts)
-    /// - G channel: Emotional tone (positive/negative words)
-    /// - B channel: Temporal indicators (past/present/future)
-    /// - Alpha: Effectiveness score from template metrics
-    fn generate_semantic_pixels(&self, content: &str, category: &TemplateCategory) -> Vec<Pixel> {
-        let word_count = content.split_whitespace().count();
-        let char_count = content.len();
-        
-        // Base semantic value from content length
-        let semantic_base = ((word_count as f64 / 100.0).min(1.0) * 255.0) as u8;
-        
-        // Emotional tone (simple heuristic: positive vs negative words)
-        let positive_words = ["good", "great", "excellent", "success", "effective"];
-        let negative_words = ["bad", "fail", "error", "problem", "issue"];
-        
-        let positive_count = positive_words.iter()
-            .filter(|w| content.to_lowercase().contains(*w))
-            .count();
-        let negative_count = negative_words.iter()
-            .filter(|w| content.to_lowercase().contains(*w))
-            .count();
-        
-        let emotional_tone = if positive_count > negative_count {
-            ((positive_count as f64 / (positive_count + negative_count) as f64) * 255.0) as u8
-        } else {
-            128 // Neutral
-        };
-        
-        // Temporal indicators
-        let temporal_base = ((char_count as f64 / 1000.0).min(1.0) * 255.0) as u8;
-        
-        // Category influences base RGB
-        let (r_offset, g_offset, b_offset) = match category {
-            TemplateCategory::Technical => (20, 0, 0),
-            TemplateCategory::Creative => (0, 20, 0),
-            TemplateCategory::Emotional => (0, 0, 20),
-            TemplateCategory::Analytical => (10, 10, 0),
-            TemplateCategory::Collaborative => (0, 10, 10),
-            TemplateCategory::Meta => (10, 0, 10),
-        };
-        
-        // Generate 128 pixels
-        let mut pixels = Vec::with_capacity(128);
-        for i in 0..128 {
-            let variation = (i as f64 / 128.0 * 50.0) as u8;
-            
-            let r = semantic_base.saturating_add(r_offset).saturating_add(variation);
-            let g = emotional_tone.saturating_add(g_offset);
-            let b = temporal_base.saturating_add(b_offset);
-            let alpha = 128; // Default effectiveness
-            
-            pixels.push(Pixel::new(r, g, b, alpha));
-        }
-        
-        pixels
+    /// Alpha: default effectiveness (helper template)
+    fn generate_semantic_pixels(&self, content: &str) -> Vec<Pixel> {
+        embedding_to_pixels(&embed_text(content), 128)
     }
     
     /// Get integration statistics
@@ -314,6 +266,11 @@ impl FBCUIntegration {
         &self.stats
     }
     
+    /// FBCU decompression cache (shareable with other engines)
+    pub fn fbcu_cache(&self) -> &Arc<FBCUCache> {
+        self.fbcu_engine.cache()
+    }
+    
     /// Clear markdown cache
     pub fn clear_cache(&mut self) {
         self.markdown_cache.clear();
@@ -424,7 +381,7 @@ mod tests {
         let integration = FBCUIntegration::new(temp_dir.path().to_path_buf()).unwrap();
         
         let content = "This is a great and excellent test with success.";
-        let pixels = integration.generate_semantic_pixels(content, &TemplateCategory::Technical);
+        let pixels = integration.generate_semantic_pixels(content);
         
         assert_eq!(pixels.len(), 128);
         assert!(pixels[0].r > 0); // Semantic content
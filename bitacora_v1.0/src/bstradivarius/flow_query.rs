@@ -27,6 +27,9 @@ use regex::Regex;
 use super::document_graph::{DocumentGraph, DocumentNode, DocumentRelation, DocumentCategory, RelationType};
 use super::git_integration::{GitIntegration, CommitInfo};
 
+/// Archivo (en `state_dir`) con las relaciones del grafo como entanglements QPX
+pub const RELATIONS_FILE_NAME: &str = "document_relations.qpx";
+
 /// Motor de consultas multi-documento
 pub struct FlowQuery {
     graph: DocumentGraph,
@@ -37,6 +40,9 @@ pub struct FlowQuery {
     
     /// Integración con Git (opcional)
     git: Option<GitIntegration>,
+    
+    /// Directorio donde se persisten las relaciones (None = solo en memoria)
+    state_dir: Option<PathBuf>,
 }
 
 impl FlowQuery {
@@ -48,9 +54,41 @@ impl FlowQuery {
             root_path,
             import_cache: HashMap::new(),
             git,
+            state_dir: None,
         }
     }
     
+    /// FlowQuery con relaciones persistidas en `state_dir`
+    ///
+    /// Carga las relaciones guardadas por `save` (si existen); los nodos se
+    /// vuelven a crear al indexar.
+    pub fn open(root_path: PathBuf, state_dir: PathBuf) -> Result<Self> {
+        let mut flow_query = Self::new(root_path);
+        
+        let relations_path = state_dir.join(RELATIONS_FILE_NAME);
+        if relations_path.exists() {
+            flow_query.graph.load_relations_qpx(&relations_path)
+                .context(format!("Failed to load relations: {:?}", relations_path))?;
+        }
+        
+        flow_query.state_dir = Some(state_dir);
+        Ok(flow_query)
+    }
+    
+    /// Guardar las relaciones del grafo en `state_dir` (no-op sin persistencia)
+    pub fn save(&self) -> Result<()> {
+        let Some(ref state_dir) = self.state_dir else {
+            return Ok(());
+        };
+        
+        fs::create_dir_all(state_dir)
+            .context(format!("Failed to create state dir: {:?}", state_dir))?;
+        let relations_path = state_dir.join(RELATIONS_FILE_NAME);
+        self.graph.save_relations_qpx(&relations_path)
+            .context(format!("Failed to save relations: {:?}", relations_path))?;
+        Ok(())
+    }
+    
     /// Indexar un archivo en el grafo
     pub fn index_file(&mut self, file_path: PathBuf, category: DocumentCategory) -> Result<()> {
         let content = fs::read_to_string(&file_path)
@@ -425,4 +463,34 @@ mod tests {
         assert_eq!(refs.len(), 1);
         assert_eq!(refs[0], "docs/architecture.md");
     }
+    
+    #[test]
+    fn test_relations_persist_across_open() {
+        let root = tempfile::tempdir().unwrap();
+        let state = tempfile::tempdir().unwrap();
+        let module = root.path().join("lib.rs");
+        let doc = root.path().join("README.md");
+        fs::write(&module, "pub fn run() {}").unwrap();
+        fs::write(&doc, "See [lib](lib.rs)").unwrap();
+        
+        let mut fq = FlowQuery::open(root.path().to_path_buf(), state.path().to_path_buf()).unwrap();
+        fq.graph.add_relation(DocumentRelation::new(
+            doc.clone(),
+            module.clone(),
+            RelationType::Implements,
+            "API".to_string(),
+        ));
+        fq.save().unwrap();
+        assert!(state.path().join(RELATIONS_FILE_NAME).exists());
+        
+        let mut reopened = FlowQuery::open(root.path().to_path_buf(), state.path().to_path_buf()).unwrap();
+        assert_eq!(reopened.graph.edge_count(), 1);
+        
+        // Re-indexar y guardar/abrir otra vez no duplica relaciones
+        reopened.index_file(doc.clone(), DocumentCategory::Documentation).unwrap();
+        reopened.save().unwrap();
+        let again = FlowQuery::open(root.path().to_path_buf(), state.path().to_path_buf()).unwrap();
+        assert_eq!(again.graph.edge_count(), reopened.graph.edge_count());
+        assert_eq!(again.graph.get_relations_from(&doc).len(), reopened.graph.get_relations_from(&doc).len());
+    }
 }
@@ -269,7 +269,7 @@ mod tests {
     #[test]
     fn test_get_file_commits() {
         let root = env::current_dir().unwrap();
-        let git = GitIntegration::new(root).unwrap();
+        let git = GitIntegration::new(root.clone()).unwrap();
         
         // Probar con este mismo archivo
         let this_file = root.join("src/bstradivarius/git_integration.rs");
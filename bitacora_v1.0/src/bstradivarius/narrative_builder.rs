@@ -44,8 +44,11 @@ pub struct NarrativeBuilder {
 impl NarrativeBuilder {
     /// Crear nuevo narrative builder
     pub fn new(root_path: PathBuf, templates_dir: Option<PathBuf>) -> Result<Self> {
-        let flow_query = FlowQuery::new(root_path);
-        
+        Self::with_flow_query(FlowQuery::new(root_path), templates_dir)
+    }
+    
+    /// Crear narrative builder sobre un FlowQuery existente (p.ej. persistido)
+    pub fn with_flow_query(flow_query: FlowQuery, templates_dir: Option<PathBuf>) -> Result<Self> {
         let template_engine = if let Some(dir) = templates_dir {
             Some(TemplateEngine::new(dir)?)
         } else {
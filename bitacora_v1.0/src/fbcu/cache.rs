@@ -0,0 +1,203 @@
+//! # Cache - LRU de descompresiones con presupuesto en bytes
+//!
+//! Guarda el original de los cores descomprimidos, indexado por
+//! `metadata.original_hash` (SHA-256 del original, ya verificado al
+//! insertar). No se usa `core.id`: dos cores con el mismo id y distinto
+//! contenido se pisarían. El límite es de bytes, no de entradas: un core de
+//! 2 MB pesa lo mismo que mil conceptos de 2 KB.
+//!
+//! El estado va tras un `Mutex`, así que un `Arc<FBCUCache>` se comparte
+//! entre varios `FBCUEngine` (BStradivarius, FlowPacks) y entre threads.
+
+use lru::LruCache;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Cache LRU compartible de descompresiones
+#[derive(Debug)]
+pub struct FBCUCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug)]
+struct CacheState {
+    entries: LruCache<String, Arc<[u8]>>,
+    bytes: usize,
+    capacity_bytes: usize,
+    hits: usize,
+    misses: usize,
+    evictions: usize,
+}
+
+/// Estado de la cache en un instante
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FBCUCacheStats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub capacity_bytes: usize,
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+}
+
+impl FBCUCacheStats {
+    /// Fracción de búsquedas servidas desde cache (0.0 sin búsquedas)
+    pub fn hit_rate(&self) -> f64 {
+        hit_rate(self.hits, self.misses)
+    }
+}
+
+impl FBCUCache {
+    /// Crear una cache de hasta `capacity_bytes` bytes de datos originales
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self {
+            state: Mutex::new(CacheState {
+                entries: LruCache::unbounded(),
+                bytes: 0,
+                capacity_bytes,
+                hits: 0,
+                misses: 0,
+                evictions: 0,
+            }),
+        }
+    }
+
+    /// Crear una cache lista para compartir entre engines
+    pub fn shared(capacity_bytes: usize) -> Arc<Self> {
+        Arc::new(Self::new(capacity_bytes))
+    }
+
+    /// Buscar un core (lo marca como usado recientemente)
+    pub fn get(&self, id: &str) -> Option<Arc<[u8]>> {
+        let mut state = self.lock();
+        match state.entries.get(id).cloned() {
+            Some(data) => {
+                state.hits += 1;
+                Some(data)
+            }
+            None => {
+                state.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Insertar un core, desalojando los menos recientes hasta caber
+    ///
+    /// Un dato mayor que el presupuesto completo no se guarda. Devuelve las
+    /// entradas desalojadas.
+    pub fn insert(&self, id: String, data: Arc<[u8]>) -> usize {
+        let mut state = self.lock();
+        if let Some(previous) = state.entries.pop(&id) {
+            state.bytes -= previous.len();
+        }
+        if data.len() > state.capacity_bytes {
+            return 0;
+        }
+
+        let mut evicted = 0;
+        while state.bytes + data.len() > state.capacity_bytes {
+            let Some((_, oldest)) = state.entries.pop_lru() else {
+                break;
+            };
+            state.bytes -= oldest.len();
+            evicted += 1;
+        }
+        state.evictions += evicted;
+
+        state.bytes += data.len();
+        state.entries.put(id, data);
+        evicted
+    }
+
+    /// Estadísticas actuales
+    pub fn stats(&self) -> FBCUCacheStats {
+        let state = self.lock();
+        FBCUCacheStats {
+            entries: state.entries.len(),
+            bytes: state.bytes,
+            capacity_bytes: state.capacity_bytes,
+            hits: state.hits,
+            misses: state.misses,
+            evictions: state.evictions,
+        }
+    }
+
+    /// Vaciar la cache (las estadísticas se conservan)
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.bytes = 0;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        // Un pánico con el lock tomado no deja la cache inconsistente:
+        // `bytes` se actualiza junto a cada cambio de `entries`
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub(crate) fn hit_rate(hits: usize, misses: usize) -> f64 {
+    let total = hits + misses;
+    if total == 0 {
+        return 0.0;
+    }
+    hits as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(len: usize) -> Arc<[u8]> {
+        vec![0u8; len].into()
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_by_bytes() {
+        let cache = FBCUCache::new(100);
+        cache.insert("a".into(), data(40));
+        cache.insert("b".into(), data(40));
+
+        // "a" pasa a ser el más reciente: se desaloja "b"
+        assert!(cache.get("a").is_some());
+        assert_eq!(cache.insert("c".into(), data(30)), 1);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+
+        // Reemplazar una entrada no cuenta sus bytes dos veces
+        cache.insert("c".into(), data(60));
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.bytes, stats.evictions), (2, 100, 1));
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert!((stats.hit_rate() - 2.0 / 3.0).abs() < 1e-9);
+
+        // Mayor que todo el presupuesto: no se guarda ni desaloja nada
+        assert_eq!(cache.insert("huge".into(), data(101)), 0);
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let cache = FBCUCache::shared(1000);
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for i in 0..50 {
+                        let id = format!("{}-{}", t, i % 10);
+                        if cache.get(&id).is_none() {
+                            cache.insert(id, data(10));
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.bytes), (40, 400));
+        assert_eq!((stats.hits, stats.misses), (160, 40));
+    }
+}
@@ -0,0 +1,88 @@
+//! # Dictionary - Zstd con diccionario compartido
+//!
+//! Los conceptos markdown pequeños (< 1-2 KB) comparten estructura (títulos,
+//! front-matter, secciones de template) pero son demasiado cortos para que
+//! GZIP encuentre repeticiones dentro de cada uno. Un diccionario zstd
+//! entrenado sobre un corpus de cores existentes aporta ese contexto común:
+//! cada core sigue siendo independiente, pero solo se puede descomprimir con
+//! el mismo diccionario.
+//!
+//! El ID del diccionario viaja en el frame zstd (y en `FBCUMetadata`), así
+//! que un core comprimido con otro diccionario se rechaza con un error claro
+//! en lugar de fallar el hash de integridad.
+
+use super::{FBCUError, Result};
+use std::io::Read;
+
+/// Nivel zstd usado por FBCU (cores se escriben una vez y se leen mucho)
+pub(crate) const ZSTD_LEVEL: i32 = 19;
+
+/// Nivel zstd de las pruebas sobre la muestra (solo estiman el ratio)
+pub(crate) const ZSTD_TRIAL_LEVEL: i32 = 3;
+
+/// Diccionario zstd entrenado sobre un corpus de cores
+#[derive(Debug, Clone)]
+pub struct FBCUDictionary {
+    id: u32,
+    bytes: Vec<u8>,
+}
+
+impl FBCUDictionary {
+    /// Entrenar un diccionario de hasta `max_size` bytes
+    ///
+    /// zstd necesita bastantes muestras (decenas, idealmente ~100× el tamaño
+    /// del diccionario en total); con un corpus insuficiente falla.
+    pub fn train<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> Result<Self> {
+        let bytes = zstd::dict::from_samples(samples, max_size)
+            .map_err(|e| FBCUError::CompressionFailed(format!("Dictionary training failed: {}", e)))?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Cargar un diccionario previamente entrenado (p. ej. desde disco)
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        let id = zstd::zstd_safe::get_dict_id(&bytes).ok_or_else(|| {
+            FBCUError::CompressionFailed("Not a trained zstd dictionary (no dictionary ID)".to_string())
+        })?;
+        Ok(Self { id: id.get(), bytes })
+    }
+
+    /// ID del diccionario (registrado en cada frame que lo usa)
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Bytes del diccionario, para persistirlo junto a los cores
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Comprimir con zstd a `level`, opcionalmente con diccionario
+pub(crate) fn zstd_compress(data: &[u8], dictionary: Option<&FBCUDictionary>, level: i32) -> Result<Vec<u8>> {
+    let dictionary_bytes = dictionary.map(|dict| dict.as_bytes()).unwrap_or_default();
+    zstd::bulk::Compressor::with_dictionary(level, dictionary_bytes)
+        .and_then(|mut compressor| compressor.compress(data))
+        .map_err(|e| FBCUError::CompressionFailed(format!("Zstd failed: {}", e)))
+}
+
+/// Descomprimir un frame zstd, verificando que use el diccionario esperado
+pub(crate) fn zstd_decompress(compressed: &[u8], dictionary: Option<&FBCUDictionary>) -> Result<Vec<u8>> {
+    let frame_dictionary = zstd::zstd_safe::get_dict_id_from_frame(compressed).map(|id| id.get());
+    let loaded = dictionary.map(|dict| dict.id());
+    if frame_dictionary.is_some() && frame_dictionary != loaded {
+        return Err(FBCUError::DecompressionFailed(format!(
+            "Zstd frame needs dictionary {}, loaded: {:?}",
+            frame_dictionary.unwrap_or_default(),
+            loaded
+        )));
+    }
+
+    let dictionary_bytes = dictionary.map(|dict| dict.as_bytes()).unwrap_or_default();
+    let mut decoder = zstd::stream::read::Decoder::with_dictionary(compressed, dictionary_bytes)
+        .map_err(|e| FBCUError::DecompressionFailed(format!("Zstd failed: {}", e)))?;
+    let mut decompressed = Vec::new();
+    decoder
+        .read_to_end(&mut decompressed)
+        .map_err(|e| FBCUError::DecompressionFailed(format!("Zstd failed: {}", e)))?;
+    Ok(decompressed)
+}
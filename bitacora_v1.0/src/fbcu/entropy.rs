@@ -0,0 +1,312 @@
+//! # Entropy - Codificador aritmético adaptativo
+//!
+//! Range coder binario (estilo LZMA: probabilidades de 11 bits que se adaptan
+//! tras cada bit) y un modelo de coeficientes enteros encima:
+//!
+//! - `zigzag(v)` se parte en número de bits `n` (árbol binario de 5 bits,
+//!   adaptativo) y mantisa (bit más alto adaptativo, el resto directos)
+//! - Contexto = banda del coeficiente × clase de magnitud del anterior, así
+//!   cada subbanda wavelet aprende su propia distribución
+
+/// Bits de precisión de las probabilidades
+const PROB_BITS: u32 = 11;
+const PROB_ONE: u16 = 1 << PROB_BITS;
+const PROB_INIT: u16 = PROB_ONE / 2;
+
+/// Velocidad de adaptación (mayor = más lenta)
+const ADAPT_SHIFT: u32 = 5;
+
+/// Umbral de renormalización del rango
+const TOP: u32 = 1 << 24;
+
+/// Bits del árbol que codifica la longitud de `zigzag(v)` (hasta 31)
+const LENGTH_TREE_BITS: u32 = 5;
+
+/// Clases de magnitud del coeficiente anterior
+const MAGNITUDE_CLASSES: usize = 3;
+
+/// Encoder de rango binario
+pub(crate) struct RangeEncoder {
+    low: u64,
+    range: u32,
+    cache: u8,
+    cache_size: u64,
+    output: Vec<u8>,
+}
+
+impl RangeEncoder {
+    pub(crate) fn new(output: Vec<u8>) -> Self {
+        Self {
+            low: 0,
+            range: u32::MAX,
+            cache: 0,
+            cache_size: 1,
+            output,
+        }
+    }
+
+    /// Codificar `bit` con la probabilidad adaptativa `prob` (de que sea 0)
+    pub(crate) fn encode_bit(&mut self, prob: &mut u16, bit: bool) {
+        let bound = (self.range >> PROB_BITS) * *prob as u32;
+        if bit {
+            self.low += bound as u64;
+            self.range -= bound;
+            *prob -= *prob >> ADAPT_SHIFT;
+        } else {
+            self.range = bound;
+            *prob += (PROB_ONE - *prob) >> ADAPT_SHIFT;
+        }
+        self.normalize();
+    }
+
+    /// Codificar los `count` bits bajos de `value` con probabilidad fija ½
+    pub(crate) fn encode_direct_bits(&mut self, value: u32, count: u32) {
+        for shift in (0..count).rev() {
+            self.range >>= 1;
+            if (value >> shift) & 1 == 1 {
+                self.low += self.range as u64;
+            }
+            self.normalize();
+        }
+    }
+
+    /// Vaciar el estado y devolver la salida
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        for _ in 0..5 {
+            self.shift_low();
+        }
+        self.output
+    }
+
+    fn normalize(&mut self) {
+        while self.range < TOP {
+            self.range <<= 8;
+            self.shift_low();
+        }
+    }
+
+    fn shift_low(&mut self) {
+        if self.low < 0xFF00_0000 || self.low >= 1 << 32 {
+            let carry = (self.low >> 32) as u8;
+            let mut byte = self.cache;
+            loop {
+                self.output.push(byte.wrapping_add(carry));
+                byte = 0xFF;
+                self.cache_size -= 1;
+                if self.cache_size == 0 {
+                    break;
+                }
+            }
+            self.cache = (self.low >> 24) as u8;
+        }
+        self.cache_size += 1;
+        self.low = (self.low & 0x00FF_FFFF) << 8;
+    }
+}
+
+/// Decoder de rango binario
+///
+/// Leer más allá del final devuelve ceros: un flujo truncado produce datos
+/// incorrectos (detectados por el hash de integridad), nunca un pánico.
+pub(crate) struct RangeDecoder<'a> {
+    input: &'a [u8],
+    range: u32,
+    code: u32,
+}
+
+impl<'a> RangeDecoder<'a> {
+    pub(crate) fn new(input: &'a [u8]) -> Self {
+        let mut decoder = Self {
+            input,
+            range: u32::MAX,
+            code: 0,
+        };
+        for _ in 0..5 {
+            decoder.code = (decoder.code << 8) | decoder.next_byte() as u32;
+        }
+        decoder
+    }
+
+    pub(crate) fn decode_bit(&mut self, prob: &mut u16) -> bool {
+        let bound = (self.range >> PROB_BITS) * *prob as u32;
+        let bit = if self.code < bound {
+            self.range = bound;
+            *prob += (PROB_ONE - *prob) >> ADAPT_SHIFT;
+            false
+        } else {
+            self.code -= bound;
+            self.range -= bound;
+            *prob -= *prob >> ADAPT_SHIFT;
+            true
+        };
+        self.normalize();
+        bit
+    }
+
+    pub(crate) fn decode_direct_bits(&mut self, count: u32) -> u32 {
+        let mut value = 0;
+        for _ in 0..count {
+            self.range >>= 1;
+            let bit = self.code >= self.range;
+            if bit {
+                self.code -= self.range;
+            }
+            value = (value << 1) | bit as u32;
+            self.normalize();
+        }
+        value
+    }
+
+    fn normalize(&mut self) {
+        while self.range < TOP {
+            self.range <<= 8;
+            self.code = (self.code << 8) | self.next_byte() as u32;
+        }
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        match self.input.split_first() {
+            Some((&byte, rest)) => {
+                self.input = rest;
+                byte
+            }
+            None => 0,
+        }
+    }
+}
+
+/// Probabilidades de un contexto (banda × clase de magnitud)
+#[derive(Clone)]
+struct ContextModel {
+    length_tree: [u16; 1 << LENGTH_TREE_BITS],
+    top_mantissa_bit: [u16; 1 << LENGTH_TREE_BITS],
+}
+
+impl Default for ContextModel {
+    fn default() -> Self {
+        Self {
+            length_tree: [PROB_INIT; 1 << LENGTH_TREE_BITS],
+            top_mantissa_bit: [PROB_INIT; 1 << LENGTH_TREE_BITS],
+        }
+    }
+}
+
+/// Modelo adaptativo de coeficientes enteros con contexto por banda
+pub(crate) struct CoefficientModel {
+    contexts: Vec<ContextModel>,
+    previous_length: u32,
+}
+
+impl CoefficientModel {
+    pub(crate) fn new(bands: usize) -> Self {
+        Self {
+            contexts: vec![ContextModel::default(); bands.max(1) * MAGNITUDE_CLASSES],
+            previous_length: 0,
+        }
+    }
+
+    /// Reiniciar el contexto de vecindad (inicio de banda)
+    pub(crate) fn start_band(&mut self) {
+        self.previous_length = 0;
+    }
+
+    /// Codificar `value` (|value| < 2^30) en la banda `band`
+    pub(crate) fn encode(&mut self, encoder: &mut RangeEncoder, band: usize, value: i32) {
+        debug_assert!(value.unsigned_abs() < 1 << 30, "coefficient out of range: {}", value);
+        let zigzag = ((value << 1) ^ (value >> 31)) as u32;
+        let length = 32 - zigzag.leading_zeros();
+        let context = self.context(band);
+
+        // Longitud: árbol binario, MSB primero
+        let mut node = 1usize;
+        for shift in (0..LENGTH_TREE_BITS).rev() {
+            let bit = (length >> shift) & 1 == 1;
+            encoder.encode_bit(&mut context.length_tree[node], bit);
+            node = (node << 1) | bit as usize;
+        }
+
+        // Mantisa bajo el 1 inicial
+        if length >= 2 {
+            let top = (zigzag >> (length - 2)) & 1 == 1;
+            encoder.encode_bit(&mut context.top_mantissa_bit[length as usize], top);
+            encoder.encode_direct_bits(zigzag, length - 2);
+        }
+
+        self.previous_length = length;
+    }
+
+    pub(crate) fn decode(&mut self, decoder: &mut RangeDecoder<'_>, band: usize) -> i32 {
+        let context = self.context(band);
+
+        let mut node = 1usize;
+        for _ in 0..LENGTH_TREE_BITS {
+            let bit = decoder.decode_bit(&mut context.length_tree[node]);
+            node = (node << 1) | bit as usize;
+        }
+        let length = (node - (1 << LENGTH_TREE_BITS)) as u32;
+
+        let zigzag = match length {
+            0 => 0,
+            1 => 1,
+            _ => {
+                let top = decoder.decode_bit(&mut context.top_mantissa_bit[length as usize]) as u32;
+                let rest = decoder.decode_direct_bits(length - 2);
+                (1 << (length - 1)) | (top << (length - 2)) | rest
+            }
+        };
+
+        self.previous_length = length;
+        ((zigzag >> 1) as i32) ^ -((zigzag & 1) as i32)
+    }
+
+    fn context(&mut self, band: usize) -> &mut ContextModel {
+        let class = match self.previous_length {
+            0 => 0,
+            1..=3 => 1,
+            _ => 2,
+        };
+        let bands = self.contexts.len() / MAGNITUDE_CLASSES;
+        &mut self.contexts[band.min(bands - 1) * MAGNITUDE_CLASSES + class]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coefficients_roundtrip_and_adapt() {
+        let values: Vec<i32> = (0..5000)
+            .map(|i: i32| match i % 7 {
+                0 => i.wrapping_mul(7919) % 70_000 - 35_000,
+                1 | 2 => (i % 5) - 2,
+                _ => 0,
+            })
+            .chain([(1 << 30) - 1, -(1 << 30) + 1, 1, -1])
+            .collect();
+
+        let mut model = CoefficientModel::new(2);
+        let mut encoder = RangeEncoder::new(Vec::new());
+        for (i, &value) in values.iter().enumerate() {
+            model.encode(&mut encoder, i % 2, value);
+        }
+        let encoded = encoder.finish();
+
+        let mut model = CoefficientModel::new(2);
+        let mut decoder = RangeDecoder::new(&encoded);
+        let decoded: Vec<i32> = (0..values.len()).map(|i| model.decode(&mut decoder, i % 2)).collect();
+        assert_eq!(decoded, values);
+
+        // Mayoría de ceros: el modelo adaptativo baja de 1 byte por valor
+        assert!(encoded.len() < values.len());
+    }
+
+    #[test]
+    fn test_truncated_stream_does_not_panic() {
+        let mut model = CoefficientModel::new(1);
+        let mut decoder = RangeDecoder::new(&[0xFF, 0x13]);
+        for _ in 0..100 {
+            model.decode(&mut decoder, 0);
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! # Fractal Compressor - PIFS sobre flujos de bytes
+//!
+//! Codec PIFS (Partitioned Iterated Function System) 1D y sin pérdida:
+//!
+//! 1. La señal se parte en bloques rango de `RANGE_SIZE` bytes.
+//! 2. Para cada rango se busca un bloque dominio (el doble de largo, contraído
+//!    promediando pares) y una transformación afín `r ≈ s·D + o` con
+//!    `s ∈ {0, ¼, ½, ¾}` (contractiva) en orientación normal o invertida.
+//!    La búsqueda se limita a los `4 << fractal_level` dominios más cercanos
+//!    al rango.
+//! 3. Los rangos sin una aproximación suficientemente buena se guardan
+//!    literales.
+//! 4. El decoder itera las transformaciones un número fijo de veces desde una
+//!    señal nula hasta aproximar el atractor. El encoder ejecuta el mismo
+//!    decoder y guarda el residuo `original - atractor` (mod 256), así que la
+//!    reconstrucción es exacta.
+//!
+//! Transformaciones y residuo se empaquetan con DEFLATE. En texto GZIP suele
+//! ganar (corpus de templates: ratio 2.66 contra 2.85, ver
+//! `benches/fbcu_codecs.rs`); PIFS aprovecha la autosimilitud de amplitud
+//! (rampas, gradientes, bloques que se repiten con otra ganancia).
+//!
+//! ## Formato
+//!
+//! ```text
+//! versión (u8) | tamaño de rango (u8) | iteraciones (u8) | longitud (u32 LE)
+//! DEFLATE( transformaciones (una por rango) | residuo (longitud bytes) )
+//! ```
+//!
+//! Cada transformación empieza con un byte de flags (`FLAG_*`, escala en los
+//! 2 bits bajos); las afines siguen con el desplazamiento relativo al dominio
+//! (solo si `s > 0`) y el offset, ambos varint zigzag.
+
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use super::{FBCUError, Result};
+
+const FORMAT_VERSION: u8 = 1;
+
+/// Tamaño de los bloques rango (bytes)
+const RANGE_SIZE: usize = 8;
+
+/// Tamaño de los bloques dominio (se contraen a `RANGE_SIZE`)
+const DOMAIN_SIZE: usize = 2 * RANGE_SIZE;
+
+const HEADER_SIZE: usize = 3 + 4;
+
+/// Error cuadrático medio máximo para aceptar una transformación; por encima
+/// el residuo cuesta más que el rango literal
+const MAX_MEAN_SQUARED_ERROR: i64 = 2;
+
+/// Cota de |offset| (el encoder nunca pasa de ±255)
+const MAX_OFFSET: i64 = 1024;
+
+const FLAG_LITERAL: u8 = 0b1000;
+const FLAG_REVERSED: u8 = 0b0100;
+const SCALE_MASK: u8 = 0b0011;
+
+/// Transformación de un bloque rango
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RangeMap {
+    /// Bytes guardados tal cual en el residuo
+    Literal,
+
+    /// `r[k] = clamp(scale/4 · D[k] + offset)` con `D` el dominio contraído
+    Affine {
+        domain: usize,
+        scale: u8,
+        reversed: bool,
+        offset: i32,
+    },
+}
+
+/// Compresor Fractal PIFS
+pub struct FractalCompressor {
+    level: u8,
+    max_domains: usize,
+    iterations: u8,
+}
+
+impl FractalCompressor {
+    /// `level` (1-10) acota la búsqueda de dominios y las iteraciones del decoder
+    pub fn new(level: u8) -> Self {
+        let level = level.clamp(1, 10);
+        Self {
+            level,
+            max_domains: 4 << level,
+            iterations: 4 + level,
+        }
+    }
+
+    /// Nivel efectivo (1-10)
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Comprimir usando PIFS
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let len = u32::try_from(data.len()).map_err(|_| {
+            FBCUError::CompressionFailed(format!("Fractal input too large: {} bytes", data.len()))
+        })?;
+
+        let maps = self.search(data);
+
+        // Atractor tal como lo verá el decoder: literales fijos + iteración
+        let mut attractor = vec![0u8; data.len()];
+        for (index, map) in maps.iter().enumerate() {
+            if *map == RangeMap::Literal {
+                let range = range_bounds(index, data.len());
+                attractor[range.clone()].copy_from_slice(&data[range]);
+            }
+        }
+        iterate(&maps, &mut attractor, self.iterations);
+
+        let mut payload = Vec::with_capacity(data.len() + maps.len() * 2);
+        for (index, map) in maps.iter().enumerate() {
+            write_map(&mut payload, index, map);
+        }
+        for (index, map) in maps.iter().enumerate() {
+            let range = range_bounds(index, data.len());
+            match map {
+                RangeMap::Literal => payload.extend_from_slice(&data[range]),
+                RangeMap::Affine { .. } => payload.extend(
+                    data[range.clone()]
+                        .iter()
+                        .zip(&attractor[range])
+                        .map(|(original, approx)| original.wrapping_sub(*approx)),
+                ),
+            }
+        }
+
+        let mut compressed = Vec::with_capacity(HEADER_SIZE + payload.len() / 2);
+        compressed.push(FORMAT_VERSION);
+        compressed.push(RANGE_SIZE as u8);
+        compressed.push(self.iterations);
+        compressed.extend_from_slice(&len.to_le_bytes());
+
+        let mut encoder = DeflateEncoder::new(compressed, Compression::best());
+        encoder
+            .write_all(&payload)
+            .map_err(|e| FBCUError::CompressionFailed(format!("Fractal DEFLATE failed: {}", e)))?;
+        encoder
+            .finish()
+            .map_err(|e| FBCUError::CompressionFailed(format!("Fractal DEFLATE failed: {}", e)))
+    }
+
+    /// Descomprimir PIFS (las iteraciones se leen del flujo, no del nivel)
+    pub fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>> {
+        if compressed.len() < HEADER_SIZE {
+            return Err(corrupted(format!("stream too short: {} bytes", compressed.len())));
+        }
+        if compressed[0] != FORMAT_VERSION {
+            return Err(corrupted(format!("unsupported version {}", compressed[0])));
+        }
+        if compressed[1] as usize != RANGE_SIZE {
+            return Err(corrupted(format!("unsupported range size {}", compressed[1])));
+        }
+        let iterations = compressed[2];
+        let len = u32::from_le_bytes([compressed[3], compressed[4], compressed[5], compressed[6]]) as usize;
+
+        // Cota del payload: transformación más larga posible por rango + residuo
+        let range_count = len.div_ceil(RANGE_SIZE);
+        let max_payload = range_count * (1 + 10 + 10) + len;
+        let mut payload = Vec::new();
+        DeflateDecoder::new(&compressed[HEADER_SIZE..])
+            .take(max_payload as u64 + 1)
+            .read_to_end(&mut payload)
+            .map_err(|e| corrupted(format!("DEFLATE failed: {}", e)))?;
+
+        let mut cursor = &payload[..];
+        let mut maps = Vec::with_capacity(range_count.min(payload.len()));
+        for index in 0..range_count {
+            maps.push(read_map(&mut cursor, index, len)?);
+        }
+        if cursor.len() != len {
+            return Err(corrupted(format!(
+                "residual has {} bytes, expected {}",
+                cursor.len(),
+                len
+            )));
+        }
+        let residual = cursor;
+
+        let mut signal = vec![0u8; len];
+        for (index, map) in maps.iter().enumerate() {
+            if *map == RangeMap::Literal {
+                let range = range_bounds(index, len);
+                signal[range.clone()].copy_from_slice(&residual[range]);
+            }
+        }
+        iterate(&maps, &mut signal, iterations);
+        for (index, map) in maps.iter().enumerate() {
+            if let RangeMap::Affine { .. } = map {
+                let range = range_bounds(index, len);
+                for (value, delta) in signal[range.clone()].iter_mut().zip(&residual[range]) {
+                    *value = value.wrapping_add(*delta);
+                }
+            }
+        }
+
+        Ok(signal)
+    }
+
+    /// Buscar la mejor transformación de cada rango (dominios del original,
+    /// teorema del collage)
+    fn search(&self, data: &[u8]) -> Vec<RangeMap> {
+        let domain_count = if data.len() >= DOMAIN_SIZE {
+            (data.len() - DOMAIN_SIZE) / RANGE_SIZE + 1
+        } else {
+            0
+        };
+        let domains: Vec<[i32; RANGE_SIZE]> = (0..domain_count)
+            .map(|domain| contract(&data[domain * RANGE_SIZE..domain * RANGE_SIZE + DOMAIN_SIZE]))
+            .collect();
+
+        (0..data.len().div_ceil(RANGE_SIZE))
+            .map(|index| {
+                let range = range_bounds(index, data.len());
+                if range.len() < RANGE_SIZE {
+                    return RangeMap::Literal;
+                }
+                self.best_map(&data[range], index, &domains)
+            })
+            .collect()
+    }
+
+    fn best_map(&self, range: &[u8], index: usize, domains: &[[i32; RANGE_SIZE]]) -> RangeMap {
+        let max_error = MAX_MEAN_SQUARED_ERROR * RANGE_SIZE as i64;
+        let target: Vec<i32> = range.iter().map(|&b| b as i32).collect();
+        let sum_target: i32 = target.iter().sum();
+
+        // s = 0: bloque constante, sin dominio
+        let offset = div_round(sum_target, RANGE_SIZE as i32);
+        let constant = [0i32; RANGE_SIZE];
+        let mut best_error = squared_error(&target, &constant, 0, offset, i64::MAX);
+        let mut best = RangeMap::Affine { domain: 0, scale: 0, reversed: false, offset };
+
+        'search: for domain in nearest_domains(index, domains.len(), self.max_domains) {
+            if best_error == 0 {
+                break;
+            }
+            for reversed in [false, true] {
+                let mut contracted = domains[domain];
+                if reversed {
+                    contracted.reverse();
+                }
+                let sum_domain: i32 = contracted.iter().sum();
+
+                for scale in 1..=SCALE_MASK {
+                    let offset = div_round(
+                        4 * sum_target - scale as i32 * sum_domain,
+                        4 * RANGE_SIZE as i32,
+                    );
+                    // Por encima del umbral el rango va literal: no hace falta el error exacto
+                    let limit = best_error.min(max_error);
+                    let error = squared_error(&target, &contracted, scale, offset, limit);
+                    if error < best_error {
+                        best_error = error;
+                        best = RangeMap::Affine { domain, scale, reversed, offset };
+                        if error == 0 {
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+
+        if best_error > max_error {
+            RangeMap::Literal
+        } else {
+            best
+        }
+    }
+}
+
+/// Aplicar las transformaciones afines `iterations` veces (en orden, in-place)
+fn iterate(maps: &[RangeMap], signal: &mut [u8], iterations: u8) {
+    for _ in 0..iterations {
+        for (index, map) in maps.iter().enumerate() {
+            let RangeMap::Affine { domain, scale, reversed, offset } = *map else {
+                continue;
+            };
+            let mut contracted = if scale == 0 {
+                [0i32; RANGE_SIZE]
+            } else {
+                contract(&signal[domain * RANGE_SIZE..domain * RANGE_SIZE + DOMAIN_SIZE])
+            };
+            if reversed {
+                contracted.reverse();
+            }
+
+            let start = index * RANGE_SIZE;
+            for (value, d) in signal[start..start + RANGE_SIZE].iter_mut().zip(contracted) {
+                *value = apply(scale, d, offset);
+            }
+        }
+    }
+}
+
+/// Contraer un dominio a `RANGE_SIZE` promediando pares
+fn contract(domain: &[u8]) -> [i32; RANGE_SIZE] {
+    let mut contracted = [0i32; RANGE_SIZE];
+    for (k, value) in contracted.iter_mut().enumerate() {
+        *value = (domain[2 * k] as i32 + domain[2 * k + 1] as i32 + 1) >> 1;
+    }
+    contracted
+}
+
+fn apply(scale: u8, d: i32, offset: i32) -> u8 {
+    ((scale as i32 * d + 2) / 4 + offset).clamp(0, 255) as u8
+}
+
+/// Error cuadrático de la transformación; corta en cuanto supera `limit`
+fn squared_error(target: &[i32], contracted: &[i32], scale: u8, offset: i32, limit: i64) -> i64 {
+    let mut error = 0;
+    for (&r, &d) in target.iter().zip(contracted) {
+        let diff = (r - apply(scale, d, offset) as i32) as i64;
+        error += diff * diff;
+        if error > limit {
+            break;
+        }
+    }
+    error
+}
+
+/// Dominios en orden de distancia al rango `index` (alternando derecha e
+/// izquierda), como máximo `limit`
+fn nearest_domains(index: usize, count: usize, limit: usize) -> impl Iterator<Item = usize> {
+    let center = index.min(count.saturating_sub(1)) as isize;
+    (0..count as isize)
+        .flat_map(move |d| match d {
+            0 => [Some(center), None],
+            d => [Some(center + d), Some(center - d)],
+        })
+        .flatten()
+        .filter(move |&j| j >= 0 && (j as usize) < count)
+        .map(|j| j as usize)
+        .take(limit)
+}
+
+fn div_round(numerator: i32, denominator: i32) -> i32 {
+    (numerator as f64 / denominator as f64).round() as i32
+}
+
+fn range_bounds(index: usize, len: usize) -> std::ops::Range<usize> {
+    let start = index * RANGE_SIZE;
+    start..(start + RANGE_SIZE).min(len)
+}
+
+fn write_map(buffer: &mut Vec<u8>, index: usize, map: &RangeMap) {
+    match *map {
+        RangeMap::Literal => buffer.push(FLAG_LITERAL),
+        RangeMap::Affine { domain, scale, reversed, offset } => {
+            buffer.push(scale | if reversed { FLAG_REVERSED } else { 0 });
+            if scale > 0 {
+                write_varint(buffer, zigzag(domain as i64 - index as i64));
+            }
+            write_varint(buffer, zigzag(offset as i64));
+        }
+    }
+}
+
+fn read_map(cursor: &mut &[u8], index: usize, len: usize) -> Result<RangeMap> {
+    let flags = read_byte(cursor)?;
+    if flags & FLAG_LITERAL != 0 {
+        return Ok(RangeMap::Literal);
+    }
+    if range_bounds(index, len).len() < RANGE_SIZE {
+        return Err(corrupted(format!("partial range {} is not literal", index)));
+    }
+
+    let scale = flags & SCALE_MASK;
+    let domain = if scale > 0 {
+        // Delta arbitrario del flujo: aritmética comprobada, sin overflow
+        let delta = unzigzag(read_varint(cursor)?);
+        i64::try_from(index)
+            .ok()
+            .and_then(|index| index.checked_add(delta))
+            .and_then(|domain| usize::try_from(domain).ok())
+            .filter(|domain| {
+                domain
+                    .checked_mul(RANGE_SIZE)
+                    .and_then(|start| start.checked_add(DOMAIN_SIZE))
+                    .is_some_and(|end| end <= len)
+            })
+            .ok_or_else(|| corrupted(format!("domain {} + {} out of bounds", index, delta)))?
+    } else {
+        0
+    };
+    let offset = unzigzag(read_varint(cursor)?);
+    if offset.unsigned_abs() > MAX_OFFSET as u64 {
+        return Err(corrupted(format!("offset {} out of range", offset)));
+    }
+
+    Ok(RangeMap::Affine {
+        domain,
+        scale,
+        reversed: flags & FLAG_REVERSED != 0,
+        offset: offset as i32,
+    })
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn read_varint(cursor: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(cursor)?;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(corrupted("varint too long".to_string()))
+}
+
+fn read_byte(cursor: &mut &[u8]) -> Result<u8> {
+    let (&byte, rest) = cursor
+        .split_first()
+        .ok_or_else(|| corrupted("truncated transforms".to_string()))?;
+    *cursor = rest;
+    Ok(byte)
+}
+
+fn corrupted(reason: String) -> FBCUError {
+    FBCUError::DecompressionFailed(format!("Fractal: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Señal con autosimilitud de amplitud: rampas y dientes de sierra
+    fn smooth_signal(len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| {
+                let ramp = (i % 256) as u32;
+                let saw = ((i / 64) % 4) as u32 * 16;
+                ((ramp / 2 + saw) % 256) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fractal_roundtrip_exact() {
+        let compressor = FractalCompressor::new(7);
+        let text = "# Template\n\n| campo | valor |\n|---|---|\n".repeat(40)
+            + "Sesión de debugging: el borrow checker rechaza la referencia mutable.";
+
+        let inputs: Vec<Vec<u8>> = vec![
+            Vec::new(),
+            b"abc".to_vec(),
+            vec![0u8; 17],
+            vec![255u8; 1000],
+            text.into_bytes(),
+            smooth_signal(4096),
+            (0..3000u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8).collect(),
+        ];
+
+        for input in inputs {
+            let compressed = compressor.compress(&input).unwrap();
+            assert_eq!(compressor.decompress(&compressed).unwrap(), input);
+            // Las iteraciones viajan en el flujo: otro nivel descomprime igual
+            assert_eq!(FractalCompressor::new(1).decompress(&compressed).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_fractal_uses_affine_maps_on_self_similar_signal() {
+        let compressor = FractalCompressor::new(7);
+        let signal = smooth_signal(8192);
+
+        let maps = compressor.search(&signal);
+        let affine = maps.iter().filter(|m| **m != RangeMap::Literal).count();
+        assert!(affine * 2 > maps.len(), "only {} of {} ranges mapped", affine, maps.len());
+
+        let compressed = compressor.compress(&signal).unwrap();
+        assert!(compressed.len() * 4 < signal.len());
+    }
+
+    #[test]
+    fn test_fractal_level_bounds_domain_search() {
+        assert_eq!(FractalCompressor::new(0).level(), 1);
+        assert_eq!(FractalCompressor::new(42).level(), 10);
+        assert_eq!(FractalCompressor::new(3).max_domains, 32);
+
+        assert_eq!(nearest_domains(5, 10, 5).collect::<Vec<_>>(), vec![5, 6, 4, 7, 3]);
+        assert_eq!(nearest_domains(0, 4, 10).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(nearest_domains(50, 4, 2).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(nearest_domains(0, 0, 8).count(), 0);
+    }
+
+    #[test]
+    fn test_fractal_rejects_corrupted_streams() {
+        let compressor = FractalCompressor::new(5);
+        let compressed = compressor.compress(&smooth_signal(512)).unwrap();
+
+        assert!(compressor.decompress(&compressed[..4]).is_err());
+        assert!(compressor.decompress(&compressed[..compressed.len() / 2]).is_err());
+
+        let mut wrong_version = compressed.clone();
+        wrong_version[0] = 99;
+        assert!(compressor.decompress(&wrong_version).is_err());
+
+        // Longitud declarada enorme: error, no reserva gigante
+        let mut wrong_len = compressed.clone();
+        wrong_len[3..7].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(compressor.decompress(&wrong_len).is_err());
+    }
+
+    #[test]
+    fn test_fractal_read_map_rejects_extreme_values() {
+        // Delta de dominio que desborda index + delta, o que lo deja negativo
+        for delta in [i64::MAX, i64::MIN] {
+            let mut stream = vec![1];
+            write_varint(&mut stream, zigzag(delta));
+            assert!(matches!(
+                read_map(&mut stream.as_slice(), 3, 64),
+                Err(FBCUError::DecompressionFailed(_))
+            ));
+        }
+
+        // Offset i64::MIN (|offset| no cabe en i64)
+        let mut stream = vec![0];
+        write_varint(&mut stream, zigzag(i64::MIN));
+        assert!(matches!(
+            read_map(&mut stream.as_slice(), 3, 64),
+            Err(FBCUError::DecompressionFailed(_))
+        ));
+
+        // Valores válidos siguen funcionando
+        let mut stream = vec![1];
+        write_varint(&mut stream, zigzag(-3));
+        write_varint(&mut stream, zigzag(-5));
+        assert_eq!(
+            read_map(&mut stream.as_slice(), 3, 64).unwrap(),
+            RangeMap::Affine { domain: 0, scale: 1, reversed: false, offset: -5 }
+        );
+    }
+}
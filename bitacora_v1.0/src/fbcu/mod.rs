@@ -1,10 +1,23 @@
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 use thiserror::Error;
 
+mod cache;
+mod dictionary;
+mod entropy;
+mod fractal;
+mod visual_dna;
+mod wavelet;
+
+pub use cache::{FBCUCache, FBCUCacheStats};
+pub use dictionary::FBCUDictionary;
+pub use fractal::FractalCompressor;
+pub use visual_dna::{QuantumVisualCompressor, VisualDNA, RGB, VISUAL_DNA_WIDTH};
+pub use wavelet::WaveletTransform;
+
 /// Errores del FBCU Engine
 #[derive(Error, Debug)]
 pub enum FBCUError {
@@ -51,8 +64,28 @@ pub struct FBCUConfig {
     /// Habilitar Visual DNA encoding
     pub enable_visual_dna: bool,
     
-    /// Tamaño de cache de descompresión (entries)
-    pub cache_size: usize,
+    /// Presupuesto de la cache de descompresión (bytes de datos originales)
+    pub cache_capacity_bytes: usize,
+    
+    /// Codecs que `compress` prueba sobre una muestra del dato (en orden de
+    /// preferencia ante empates)
+    ///
+    /// Por defecto solo los LZ (cada llamada los prueba todos): `Wavelet`
+    /// conviene agregarlo para señales suaves; `Fractal` y `Hybrid` comprimen
+    /// a ~170 KiB/s y no superan a GZIP sobre texto.
+    pub codec_candidates: Vec<CompressionType>,
+    
+    /// Tamaño de la muestra (bytes) con la que se eligen los codecs
+    pub codec_sample_size: usize,
+    
+    /// Velocidad mínima de compresión (MiB/s) para elegir un codec (opcional)
+    ///
+    /// Depende del tiempo medido, así que con `Some` la elección deja de ser
+    /// determinista. Con `None` decide solo el tamaño sobre la muestra.
+    pub min_codec_throughput: Option<f64>,
+    
+    /// Tamaño máximo del diccionario entrenado (bytes)
+    pub dictionary_size: usize,
 }
 
 impl Default for FBCUConfig {
@@ -62,7 +95,15 @@ impl Default for FBCUConfig {
             wavelet_level: 5,
             fractal_level: 7,
             enable_visual_dna: false,
-            cache_size: 1000,
+            cache_capacity_bytes: 64 * 1024 * 1024,  // 64 MB
+            codec_candidates: vec![
+                CompressionType::Gzip,
+                CompressionType::Zstd,
+                CompressionType::ZstdDictionary,
+            ],
+            codec_sample_size: 16 * 1024,  // 16 KB
+            min_codec_throughput: None,
+            dictionary_size: 16 * 1024,    // 16 KB
         }
     }
 }
@@ -108,11 +149,48 @@ pub enum CompressionType {
     /// Wavelet + Fractal (pipeline completo)
     Hybrid,
     
-    /// Visual DNA + Fractal
+    /// Visual DNA: imagen PNG sin pérdida del core (requiere `enable_visual_dna`)
     QuantumVisual,
     
     /// GZIP (para archivos técnicos .md/.rs)
     Gzip,
+    
+    /// Zstd sin diccionario
+    Zstd,
+    
+    /// Zstd con el diccionario compartido del engine (conceptos pequeños)
+    ZstdDictionary,
+}
+
+impl CompressionType {
+    /// Tag de un byte (formato QPX y frames de FlowPacks)
+    pub fn tag(self) -> u8 {
+        match self {
+            CompressionType::None => 0,
+            CompressionType::Wavelet => 1,
+            CompressionType::Fractal => 2,
+            CompressionType::Hybrid => 3,
+            CompressionType::QuantumVisual => 4,
+            CompressionType::Gzip => 5,
+            CompressionType::Zstd => 6,
+            CompressionType::ZstdDictionary => 7,
+        }
+    }
+    
+    /// Inverso de `tag`
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(CompressionType::None),
+            1 => Some(CompressionType::Wavelet),
+            2 => Some(CompressionType::Fractal),
+            3 => Some(CompressionType::Hybrid),
+            4 => Some(CompressionType::QuantumVisual),
+            5 => Some(CompressionType::Gzip),
+            6 => Some(CompressionType::Zstd),
+            7 => Some(CompressionType::ZstdDictionary),
+            _ => None,
+        }
+    }
 }
 
 /// Metadatos del FBCU Core
@@ -132,6 +210,66 @@ pub struct FBCUMetadata {
     
     /// Nivel fractal usado (si aplica)
     pub fractal_level: Option<u8>,
+    
+    /// ID del diccionario zstd necesario para descomprimir (si aplica)
+    #[serde(default)]
+    pub dictionary_id: Option<u32>,
+    
+    /// Pruebas con las que `compress` eligió el codec (si aplica)
+    #[serde(default)]
+    pub codec_selection: Option<CodecSelection>,
+}
+
+/// Resultado de probar los codecs candidatos sobre una muestra
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodecSelection {
+    /// Bytes de la muestra probada
+    pub sample_size: usize,
+    
+    /// Una prueba por codec candidato disponible
+    pub trials: Vec<CodecTrial>,
+}
+
+/// Prueba de un codec sobre la muestra
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodecTrial {
+    pub compression_type: CompressionType,
+    
+    /// Ratio sobre la muestra
+    pub ratio: f64,
+    
+    /// Tiempo de compresión de la muestra (µs, informativo)
+    pub time_us: u64,
+}
+
+impl CodecSelection {
+    /// Elegir codec: el de mejor ratio sobre la muestra; en empate, el primero
+    /// de los candidatos. Para un mismo dato la elección es siempre la misma.
+    ///
+    /// Con `min_throughput` (MiB/s) se descartan antes los codecs más lentos
+    /// según el tiempo medido (si ninguno llega, se elige entre todos).
+    pub fn choose(&self, min_throughput: Option<f64>) -> Option<&CodecTrial> {
+        let mut pool: Vec<&CodecTrial> = self.trials.iter().collect();
+        if let Some(min_throughput) = min_throughput {
+            let fast_enough: Vec<&CodecTrial> = pool
+                .iter()
+                .copied()
+                .filter(|trial| self.throughput(trial) >= min_throughput)
+                .collect();
+            if !fast_enough.is_empty() {
+                pool = fast_enough;
+            }
+        }
+        
+        pool.into_iter()
+            .reduce(|best, trial| if trial.ratio > best.ratio { trial } else { best })
+    }
+    
+    /// MiB/s de una prueba
+    fn throughput(&self, trial: &CodecTrial) -> f64 {
+        let seconds = trial.time_us.max(1) as f64 / 1_000_000.0;
+        self.sample_size as f64 / (1024.0 * 1024.0) / seconds
+    }
 }
 
 // ============================================================================
@@ -149,8 +287,11 @@ pub struct FBCUEngine {
     /// Compresor Visual DNA (opcional)
     visual_dna: Option<QuantumVisualCompressor>,
     
-    /// Cache LRU de descompresiones
-    cache: HashMap<String, Vec<u8>>,
+    /// Diccionario zstd compartido (opcional)
+    dictionary: Option<FBCUDictionary>,
+    
+    /// Cache LRU de descompresiones (compartible entre engines)
+    cache: Arc<FBCUCache>,
     
     /// Configuración
     config: FBCUConfig,
@@ -162,6 +303,14 @@ pub struct FBCUEngine {
 impl FBCUEngine {
     /// Crear nuevo FBCU Engine
     pub fn new(config: FBCUConfig) -> Result<Self> {
+        let cache = FBCUCache::shared(config.cache_capacity_bytes);
+        Self::with_cache(config, cache)
+    }
+    
+    /// Crear un engine que comparte la cache de descompresión con otros
+    ///
+    /// `cache_capacity_bytes` de la config se ignora: manda el de `cache`.
+    pub fn with_cache(config: FBCUConfig, cache: Arc<FBCUCache>) -> Result<Self> {
         if config.wavelet_level > 10 {
             return Err(FBCUError::InvalidWaveletLevel {
                 level: config.wavelet_level,
@@ -176,62 +325,125 @@ impl FBCUEngine {
             } else {
                 None
             },
-            cache: HashMap::new(),
+            dictionary: None,
+            cache,
             config,
             metrics: FBCUMetrics::default(),
         })
     }
     
-    /// Comprimir datos (usa GZIP para archivos técnicos)
+    /// Comprimir datos eligiendo el codec
+    ///
+    /// Prueba `codec_candidates` sobre los primeros `codec_sample_size`
+    /// bytes, elige según `CodecSelection::choose` y registra las pruebas en
+    /// `FBCUMetadata::codec_selection`. Las pruebas zstd usan un nivel rápido;
+    /// el codec elegido comprime el dato completo a su nivel normal. Por debajo
+    /// de `compression_threshold` solo se prueba el diccionario compartido (si
+    /// hay uno cargado).
     pub fn compress(&mut self, data: &[u8]) -> Result<FBCUCore> {
         let start = Instant::now();
         
         // Verificar umbral de compresión
-        if data.len() < self.config.compression_threshold {
-            return Ok(self.create_uncompressed_core(data, start));
-        }
-        
-        // Comprimir con GZIP (mejor para texto/código)
-        use flate2::Compression;
-        use flate2::write::GzEncoder;
-        use std::io::Write;
+        let candidates = if data.len() < self.config.compression_threshold {
+            vec![CompressionType::ZstdDictionary]
+        } else {
+            self.config.codec_candidates.clone()
+        };
         
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-        encoder.write_all(data)
-            .map_err(|e| FBCUError::CompressionFailed(format!("GZIP write failed: {}", e)))?;
-        let compressed_data = encoder.finish()
-            .map_err(|e| FBCUError::CompressionFailed(format!("GZIP finish failed: {}", e)))?;
+        let sample = &data[..data.len().min(self.config.codec_sample_size)];
+        let sample_is_data = sample.len() == data.len();
+        let mut selection = CodecSelection {
+            sample_size: sample.len(),
+            trials: Vec::new(),
+        };
+        // Salidas reutilizables como resultado final (una por prueba)
+        let mut outputs = Vec::new();
+        
+        for compression_type in candidates {
+            let trial_start = Instant::now();
+            // Codecs no disponibles (sin diccionario, con pérdida) se omiten
+            let Ok(output) = self.encode_at(sample, compression_type, dictionary::ZSTD_TRIAL_LEVEL) else {
+                continue;
+            };
+            selection.trials.push(CodecTrial {
+                compression_type,
+                ratio: sample.len() as f64 / output.len().max(1) as f64,
+                time_us: trial_start.elapsed().as_micros() as u64,
+            });
+            let trial_is_final = sample_is_data
+                && !matches!(compression_type, CompressionType::Zstd | CompressionType::ZstdDictionary);
+            outputs.push(Some(output).filter(|_| trial_is_final));
+        }
         
-        let compression_type = CompressionType::Gzip;
+        let chosen = selection
+            .choose(self.config.min_codec_throughput)
+            .filter(|trial| trial.ratio > 1.0)
+            .map(|trial| trial.compression_type);
+        
+        let mut core = match chosen {
+            None => self.create_uncompressed_core(data, start),
+            Some(compression_type) => {
+                // La muestra era el dato completo: reutilizar su salida
+                let compressed_data = match selection
+                    .trials
+                    .iter()
+                    .position(|trial| trial.compression_type == compression_type)
+                    .and_then(|index| outputs[index].take())
+                {
+                    Some(output) => output,
+                    None => self.encode(data, compression_type)?,
+                };
+                self.finish_core(data, compression_type, compressed_data, start)
+            }
+        };
         
-        let compression_time = start.elapsed().as_millis() as u64;
-        let compression_ratio = data.len() as f64 / compressed_data.len() as f64;
+        if !selection.trials.is_empty() {
+            core.metadata.codec_selection = Some(selection);
+        }
+        Ok(core)
+    }
+    
+    /// Comprimir con un codec explícito (sin umbral ni fallback)
+    pub fn compress_with(&mut self, data: &[u8], compression_type: CompressionType) -> Result<FBCUCore> {
+        let start = Instant::now();
         
-        // Actualizar métricas
-        self.metrics.record_compression(compression_ratio, compression_time);
+        if compression_type == CompressionType::None {
+            return Ok(self.create_uncompressed_core(data, start));
+        }
+        let compressed_data = self.encode(data, compression_type)?;
         
-        Ok(FBCUCore {
-            id: sha256_hex(data),
-            compression_type,
-            compressed_data,
-            original_size: data.len(),
-            compression_ratio,
-            metadata: FBCUMetadata {
-                compressed_at: chrono::Utc::now().to_rfc3339(),
-                compression_time_ms: compression_time,
-                original_hash: sha256_hex(data),
-                wavelet_level: Some(self.config.wavelet_level),
-                fractal_level: Some(self.config.fractal_level),
-            },
-        })
+        Ok(self.finish_core(data, compression_type, compressed_data, start))
+    }
+    
+    /// Cargar el diccionario compartido (para `ZstdDictionary`)
+    pub fn set_dictionary(&mut self, dictionary: FBCUDictionary) {
+        self.dictionary = Some(dictionary);
+    }
+    
+    /// Diccionario compartido cargado
+    pub fn dictionary(&self) -> Option<&FBCUDictionary> {
+        self.dictionary.as_ref()
+    }
+    
+    /// Entrenar un diccionario con el contenido de cores existentes
+    ///
+    /// No lo carga: el llamador debe persistirlo (`as_bytes`) antes de
+    /// `set_dictionary`, o los cores comprimidos con él serán ilegibles.
+    pub fn train_dictionary(&mut self, cores: &[FBCUCore]) -> Result<FBCUDictionary> {
+        let samples = cores
+            .iter()
+            .map(|core| self.decompress(core))
+            .collect::<Result<Vec<_>>>()?;
+        FBCUDictionary::train(&samples, self.config.dictionary_size)
     }
     
     /// Descomprimir FBCU Core
     pub fn decompress(&mut self, core: &FBCUCore) -> Result<Vec<u8>> {
-        // Verificar cache
-        if let Some(cached) = self.cache.get(&core.id) {
+        // Verificar cache: la clave es el hash del original, no `core.id`
+        // (que el llamador puede elegir), así que un acierto ya está verificado
+        if let Some(cached) = self.cache.get(&core.metadata.original_hash) {
             self.metrics.cache_hits += 1;
-            return Ok(cached.clone());
+            return Ok(cached.to_vec());
         }
         
         self.metrics.cache_misses += 1;
@@ -250,6 +462,10 @@ impl FBCUEngine {
                     .map_err(|e| FBCUError::DecompressionFailed(format!("GZIP failed: {}", e)))?;
                 decompressed
             }
+            CompressionType::Zstd => dictionary::zstd_decompress(&core.compressed_data, None)?,
+            CompressionType::ZstdDictionary => {
+                dictionary::zstd_decompress(&core.compressed_data, self.dictionary.as_ref())?
+            }
             CompressionType::Wavelet => self.wavelet.decompress(&core.compressed_data)?,
             CompressionType::Fractal => self.fractal.decompress(&core.compressed_data)?,
             CompressionType::Hybrid => {
@@ -257,10 +473,7 @@ impl FBCUEngine {
                 let fractal_out = self.fractal.decompress(&core.compressed_data)?;
                 self.wavelet.decompress(&fractal_out)?
             }
-            CompressionType::QuantumVisual => {
-                // STUB para v1.0
-                core.compressed_data.clone()
-            }
+            CompressionType::QuantumVisual => QuantumVisualCompressor::decompress(&core.compressed_data)?,
         };
         
         // Verificar integridad
@@ -272,14 +485,8 @@ impl FBCUEngine {
             });
         }
         
-        // Actualizar cache (LRU simple)
-        if self.cache.len() >= self.config.cache_size {
-            // Eliminar primero (FIFO simple para v1.0)
-            if let Some(first_key) = self.cache.keys().next().cloned() {
-                self.cache.remove(&first_key);
-            }
-        }
-        self.cache.insert(core.id.clone(), decompressed.clone());
+        // Actualizar cache (LRU por bytes)
+        self.metrics.cache_evictions += self.cache.insert(hash, decompressed.as_slice().into());
         
         // Actualizar métricas
         let decompression_time = start.elapsed().as_millis() as u64;
@@ -301,8 +508,46 @@ impl FBCUEngine {
         &self.metrics
     }
     
+    /// Cache de descompresión, para compartirla con otro engine
+    pub fn cache(&self) -> &Arc<FBCUCache> {
+        &self.cache
+    }
+    
     // === HELPERS INTERNOS ===
     
+    /// Codificar con un codec concreto (todos menos `None`)
+    fn encode(&self, data: &[u8], compression_type: CompressionType) -> Result<Vec<u8>> {
+        self.encode_at(data, compression_type, dictionary::ZSTD_LEVEL)
+    }
+    
+    /// `encode` con un nivel zstd explícito (el resto de codecs lo ignoran)
+    fn encode_at(&self, data: &[u8], compression_type: CompressionType, zstd_level: i32) -> Result<Vec<u8>> {
+        match compression_type {
+            CompressionType::Gzip => gzip_compress(data),
+            CompressionType::Zstd => dictionary::zstd_compress(data, None, zstd_level),
+            CompressionType::ZstdDictionary => {
+                let dictionary = self.dictionary.as_ref().ok_or_else(|| {
+                    FBCUError::CompressionFailed("No shared dictionary loaded".to_string())
+                })?;
+                dictionary::zstd_compress(data, Some(dictionary), zstd_level)
+            }
+            CompressionType::Fractal => self.try_fractal(data),
+            CompressionType::Wavelet => self.try_wavelet(data),
+            CompressionType::Hybrid => {
+                // Primero wavelet, luego fractal (inverso de `decompress`)
+                let wavelet_out = self.try_wavelet(data)?;
+                self.try_fractal(&wavelet_out)
+            }
+            CompressionType::QuantumVisual => {
+                let visual_dna = self.visual_dna.as_ref().ok_or(FBCUError::VisualDNANotEnabled)?;
+                visual_dna.compress(data)
+            }
+            CompressionType::None => Err(FBCUError::CompressionFailed(
+                "None is not a codec".to_string(),
+            )),
+        }
+    }
+    
     fn try_wavelet(&self, data: &[u8]) -> Result<Vec<u8>> {
         self.wavelet.compress(data)
     }
@@ -311,6 +556,41 @@ impl FBCUEngine {
         self.fractal.compress(data)
     }
     
+    /// Armar el core de un dato comprimido y registrar métricas
+    fn finish_core(
+        &mut self,
+        data: &[u8],
+        compression_type: CompressionType,
+        compressed_data: Vec<u8>,
+        start: Instant,
+    ) -> FBCUCore {
+        let compression_time = start.elapsed().as_millis() as u64;
+        let compression_ratio = data.len() as f64 / compressed_data.len().max(1) as f64;
+        
+        // Actualizar métricas
+        self.metrics.record_compression(compression_ratio, compression_time);
+        
+        FBCUCore {
+            id: sha256_hex(data),
+            compression_type,
+            compressed_data,
+            original_size: data.len(),
+            compression_ratio,
+            metadata: FBCUMetadata {
+                compressed_at: chrono::Utc::now().to_rfc3339(),
+                compression_time_ms: compression_time,
+                original_hash: sha256_hex(data),
+                wavelet_level: Some(self.config.wavelet_level),
+                fractal_level: Some(self.config.fractal_level),
+                dictionary_id: match compression_type {
+                    CompressionType::ZstdDictionary => self.dictionary.as_ref().map(|dict| dict.id()),
+                    _ => None,
+                },
+                codec_selection: None,
+            },
+        }
+    }
+    
     fn create_uncompressed_core(&self, data: &[u8], start: Instant) -> FBCUCore {
         FBCUCore {
             id: sha256_hex(data),
@@ -324,249 +604,21 @@ impl FBCUEngine {
                 original_hash: sha256_hex(data),
                 wavelet_level: None,
                 fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
             },
         }
     }
 }
 
-// ============================================================================
-// WAVELET TRANSFORM (Haar)
-// ============================================================================
-
-/// Transformada Wavelet de Haar
-pub struct WaveletTransform {
-    level: u8,
-}
-
-impl WaveletTransform {
-    pub fn new(level: u8) -> Self {
-        Self { level: level.min(10) }
-    }
-    
-    /// Comprimir datos usando wavelet
-    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
-        // Convertir a f32
-        let mut coeffs: Vec<f32> = data.iter().map(|&b| b as f32).collect();
-        
-        // Asegurar potencia de 2
-        let len = coeffs.len().next_power_of_two();
-        coeffs.resize(len, 0.0);
-        
-        // Aplicar transformada Haar recursivamente
-        for l in 0..self.level {
-            let len = coeffs.len() >> l;
-            if len < 2 {
-                break;
-            }
-            self.haar_step(&mut coeffs[..len]);
-        }
-        
-        // Cuantizar y convertir a bytes
-        Ok(self.quantize_coeffs(&coeffs))
-    }
-    
-    /// Descomprimir datos
-    pub fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>> {
-        // Dequantizar
-        let mut coeffs = self.dequantize_coeffs(compressed);
-        
-        // Aplicar transformada inversa
-        for l in (0..self.level).rev() {
-            let len = coeffs.len() >> l;
-            if len < 2 {
-                break;
-            }
-            self.inverse_haar_step(&mut coeffs[..len]);
-        }
-        
-        // Convertir a bytes
-        Ok(coeffs.iter().map(|&f| f.round().clamp(0.0, 255.0) as u8).collect())
-    }
-    
-    fn haar_step(&self, data: &mut [f32]) {
-        let len = data.len() / 2;
-        let mut temp = vec![0.0; data.len()];
-        
-        for i in 0..len {
-            // Promedio (low-pass)
-            temp[i] = (data[2 * i] + data[2 * i + 1]) / 2.0;
-            
-            // Diferencia (high-pass)
-            temp[len + i] = (data[2 * i] - data[2 * i + 1]) / 2.0;
-        }
-        
-        data.copy_from_slice(&temp);
-    }
-    
-    fn inverse_haar_step(&self, data: &mut [f32]) {
-        let len = data.len() / 2;
-        let mut temp = vec![0.0; data.len()];
-        
-        for i in 0..len {
-            temp[2 * i] = data[i] + data[len + i];
-            temp[2 * i + 1] = data[i] - data[len + i];
-        }
-        
-        data.copy_from_slice(&temp);
-    }
-    
-    fn quantize_coeffs(&self, coeffs: &[f32]) -> Vec<u8> {
-        // Cuantización simple: f32 → u8
-        // En producción usar quantización adaptativa
-        coeffs
-            .iter()
-            .map(|&f| (f.abs().min(255.0)) as u8)
-            .collect()
-    }
-    
-    fn dequantize_coeffs(&self, bytes: &[u8]) -> Vec<f32> {
-        bytes.iter().map(|&b| b as f32).collect()
-    }
-}
-
-// ============================================================================
-// FRACTAL COMPRESSOR (IFS - Iterated Function Systems)
-// ============================================================================
-
-/// Compresor Fractal IFS
-pub struct FractalCompressor {
-    level: u8,
-    max_iterations: usize,
-}
-
-impl FractalCompressor {
-    pub fn new(level: u8) -> Self {
-        Self {
-            level: level.min(10),
-            max_iterations: (level as usize) * 100,
-        }
-    }
-    
-    /// Comprimir usando IFS (STUB para v1.0)
-    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
-        // STUB: En v1.0, aplicamos compresión simple
-        // En v2.0, implementar IFS completo con transformaciones afines
-        
-        // Por ahora, usar RLE (Run-Length Encoding) como placeholder
-        Ok(self.simple_rle(data))
-    }
-    
-    /// Descomprimir desde IFS (STUB para v1.0)
-    pub fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>> {
-        // STUB: descompresión RLE
-        self.simple_rle_decode(compressed)
-    }
-    
-    // RLE simple como placeholder
-    fn simple_rle(&self, data: &[u8]) -> Vec<u8> {
-        let mut compressed = Vec::new();
-        let mut i = 0;
-        
-        while i < data.len() {
-            let byte = data[i];
-            let mut count = 1u8;
-            
-            while (i + count as usize) < data.len()
-                && data[i + count as usize] == byte
-                && count < 255
-            {
-                count += 1;
-            }
-            
-            compressed.push(count);
-            compressed.push(byte);
-            i += count as usize;
-        }
-        
-        compressed
-    }
-    
-    fn simple_rle_decode(&self, compressed: &[u8]) -> Result<Vec<u8>> {
-        let mut decompressed = Vec::new();
-        let mut i = 0;
-        
-        while i + 1 < compressed.len() {
-            let count = compressed[i];
-            let byte = compressed[i + 1];
-            
-            for _ in 0..count {
-                decompressed.push(byte);
-            }
-            
-            i += 2;
-        }
-        
-        Ok(decompressed)
-    }
-}
-
-// ============================================================================
-// QUANTUM VISUAL DNA (opcional)
-// ============================================================================
-
-/// Compresor Visual DNA Cuántico
-pub struct QuantumVisualCompressor {
-    level: u8,
-}
-
-impl QuantumVisualCompressor {
-    pub fn new(level: u8) -> Self {
-        Self { level }
-    }
-    
-    /// Generar Visual DNA desde texto (STUB)
-    pub fn generate_visual_dna(&self, text: &str) -> VisualDNA {
-        let bytes = text.as_bytes();
-        
-        let pixels: Vec<RGB> = bytes
-            .iter()
-            .map(|&byte| self.byte_to_color(byte))
-            .collect();
-        
-        let size = (pixels.len() as f64).sqrt().ceil() as usize;
-        
-        VisualDNA {
-            pixels,
-            width: size,
-            height: size,
-        }
-    }
-    
-    fn byte_to_color(&self, byte: u8) -> RGB {
-        // Hash determinístico
-        let h1 = byte.wrapping_mul(137).wrapping_add(73);
-        let h2 = byte.wrapping_mul(211).wrapping_add(41);
-        let h3 = byte.wrapping_mul(179).wrapping_add(97);
-        
-        RGB {
-            r: h1,
-            g: h2,
-            b: h3,
-        }
-    }
-}
-
-/// Visual DNA representation
-#[derive(Debug, Clone)]
-pub struct VisualDNA {
-    pub pixels: Vec<RGB>,
-    pub width: usize,
-    pub height: usize,
-}
-
-/// RGB color
-#[derive(Debug, Clone, Copy)]
-pub struct RGB {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
-}
-
 // ============================================================================
 // MÉTRICAS
 // ============================================================================
 
 /// Métricas de rendimiento del FBCU
+///
+/// Los contadores de cache son los de este engine; con una cache compartida,
+/// `FBCUCache::stats` da el total.
 #[derive(Debug, Default)]
 pub struct FBCUMetrics {
     pub total_compressions: usize,
@@ -576,9 +628,17 @@ pub struct FBCUMetrics {
     pub avg_decompression_time_ms: u64,
     pub cache_hits: usize,
     pub cache_misses: usize,
+    /// Entradas desalojadas al insertar descompresiones de este engine
+    pub cache_evictions: usize,
 }
 
 impl FBCUMetrics {
+    /// Fracción de descompresiones servidas desde cache
+    pub fn cache_hit_rate(&self) -> f64 {
+        cache::hit_rate(self.cache_hits, self.cache_misses)
+    }
+    
+
     fn record_compression(&mut self, ratio: f64, time_ms: u64) {
         self.total_compressions += 1;
         self.avg_compression_ratio = (self.avg_compression_ratio
@@ -604,8 +664,21 @@ impl FBCUMetrics {
 // UTILIDADES
 // ============================================================================
 
+/// Comprimir con GZIP (nivel máximo)
+fn gzip_compress(data: &[u8]) -> Result<Vec<u8>> {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)
+        .map_err(|e| FBCUError::CompressionFailed(format!("GZIP write failed: {}", e)))?;
+    encoder.finish()
+        .map_err(|e| FBCUError::CompressionFailed(format!("GZIP finish failed: {}", e)))
+}
+
 /// Calcular SHA-256 hex de datos
-fn sha256_hex(data: &[u8]) -> String {
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
     let hash = Sha256::digest(data);
     hex::encode(hash)
 }
@@ -636,6 +709,47 @@ mod tests {
         assert_eq!(original.as_slice(), decompressed.as_slice());
     }
     
+    #[test]
+    fn test_compress_with_fractal_passes_integrity_check() {
+        let mut engine = FBCUEngine::new(FBCUConfig::default()).unwrap();
+        let original = "## Patrón\n\nLínea repetida con variaciones 0123456789.\n".repeat(50);
+        
+        let core = engine.compress_with(original.as_bytes(), CompressionType::Fractal).unwrap();
+        assert_eq!(core.compression_type, CompressionType::Fractal);
+        assert!(core.compression_ratio > 1.0);
+        
+        // `decompress` verifica `original_hash`
+        assert_eq!(engine.decompress(&core).unwrap(), original.as_bytes());
+        
+    }
+    
+    #[test]
+    fn test_quantum_visual_core_roundtrip() {
+        let original = "## Visual DNA\n\nUn core que se puede mirar y comparar.\n".repeat(20);
+        
+        let mut engine = FBCUEngine::new(FBCUConfig::default()).unwrap();
+        assert!(matches!(
+            engine.compress_with(original.as_bytes(), CompressionType::QuantumVisual),
+            Err(FBCUError::VisualDNANotEnabled)
+        ));
+        
+        let mut engine = FBCUEngine::new(FBCUConfig {
+            enable_visual_dna: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let core = engine.compress_with(original.as_bytes(), CompressionType::QuantumVisual).unwrap();
+        assert!(core.compression_ratio > 1.0);
+        
+        // El PNG guardado es el Visual DNA del core
+        let dna = VisualDNA::from_png(&core.compressed_data).unwrap();
+        assert_eq!(dna.width, VISUAL_DNA_WIDTH);
+        assert_eq!(dna.height, original.len().div_ceil(VISUAL_DNA_WIDTH));
+        
+        // `decompress` verifica `original_hash`
+        assert_eq!(engine.decompress(&core).unwrap(), original.as_bytes());
+    }
+    
     #[test]
     fn test_wavelet_transform_reversibility() {
         let wavelet = WaveletTransform::new(3);
@@ -647,8 +761,157 @@ mod tests {
         // Descomprimir
         let reconstructed = wavelet.decompress(&compressed).unwrap();
         
-        // Verificar reversibilidad (con tolerancia)
-        assert_eq!(original.len(), reconstructed.len());
+        // Verificar reversibilidad (exacta: lifting entero)
+        assert_eq!(original, reconstructed);
+    }
+    
+    #[test]
+    fn test_wavelet_and_hybrid_cores_pass_integrity_check() {
+        let mut engine = FBCUEngine::new(FBCUConfig::default()).unwrap();
+        let original: Vec<u8> = (0..4096u32).map(|i| ((i / 16) % 256) as u8).collect();
+        
+        for compression_type in [CompressionType::Wavelet, CompressionType::Hybrid] {
+            let core = engine.compress_with(&original, compression_type).unwrap();
+            assert_eq!(core.compression_type, compression_type);
+            assert!(core.compression_ratio > 4.0, "{:?}: {}", compression_type, core.compression_ratio);
+            assert_eq!(engine.decompress(&core).unwrap(), original);
+        }
+    }
+    
+    #[test]
+    fn test_compress_selects_codec_per_input() {
+        // Wavelet es opcional: se agrega a los candidatos por defecto
+        let mut engine = FBCUEngine::new(FBCUConfig {
+            codec_candidates: vec![
+                CompressionType::Gzip,
+                CompressionType::Zstd,
+                CompressionType::ZstdDictionary,
+                CompressionType::Wavelet,
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+        
+        // Señal suave sin repeticiones (paseo aleatorio): gana la wavelet
+        let mut seed = 0x2545_f491u32;
+        let mut level = 128i32;
+        let signal: Vec<u8> = (0..8192)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                level = (level + [-2, -1, 1, 2][(seed >> 30) as usize]).clamp(16, 240);
+                level as u8
+            })
+            .collect();
+        let core = engine.compress(&signal).unwrap();
+        assert_eq!(core.compression_type, CompressionType::Wavelet);
+        
+        let selection = core.metadata.codec_selection.as_ref().unwrap();
+        assert_eq!(selection.sample_size, signal.len());
+        let tried: Vec<_> = selection.trials.iter().map(|trial| trial.compression_type).collect();
+        // Sin diccionario cargado, `ZstdDictionary` no se prueba
+        assert_eq!(tried, [CompressionType::Gzip, CompressionType::Zstd, CompressionType::Wavelet]);
+        assert_eq!(engine.decompress(&core).unwrap(), signal);
+        
+        // Texto: gana un codec LZ, elegido sobre una muestra
+        let text = "## Concepto\n\nTexto markdown con secciones repetidas.\n".repeat(1000);
+        let core = engine.compress(text.as_bytes()).unwrap();
+        assert!(matches!(core.compression_type, CompressionType::Gzip | CompressionType::Zstd));
+        assert_eq!(core.metadata.codec_selection.as_ref().unwrap().sample_size, 16 * 1024);
+        assert_eq!(engine.decompress(&core).unwrap(), text.as_bytes());
+    }
+    
+    #[test]
+    fn test_default_codec_selection_is_deterministic() {
+        let mut engine = FBCUEngine::new(FBCUConfig::default()).unwrap();
+        let text = "# Sesión\n\nNotas de debugging con el borrow checker y lifetimes.\n".repeat(50);
+        
+        let first = engine.compress(text.as_bytes()).unwrap();
+        let tried: Vec<_> = first
+            .metadata
+            .codec_selection
+            .as_ref()
+            .unwrap()
+            .trials
+            .iter()
+            .map(|trial| trial.compression_type)
+            .collect();
+        assert_eq!(tried, [CompressionType::Gzip, CompressionType::Zstd]);
+        
+        // Misma entrada → mismo codec y mismos bytes, sin importar los tiempos
+        for _ in 0..5 {
+            let again = engine.compress(text.as_bytes()).unwrap();
+            assert_eq!(again.compression_type, first.compression_type);
+            assert_eq!(again.compressed_data, first.compressed_data);
+        }
+        assert_eq!(engine.decompress(&first).unwrap(), text.as_bytes());
+    }
+    
+    #[test]
+    fn test_codec_selection_by_ratio_with_optional_throughput() {
+        let trial = |compression_type, ratio, time_us| CodecTrial { compression_type, ratio, time_us };
+        let selection = CodecSelection {
+            sample_size: 1024 * 1024,
+            trials: vec![
+                trial(CompressionType::Gzip, 3.0, 50_000),
+                trial(CompressionType::Zstd, 3.04, 20_000),
+                trial(CompressionType::Fractal, 5.0, 4_000_000),
+            ],
+        };
+        
+        // Por defecto decide solo el ratio
+        assert_eq!(selection.choose(None).unwrap().compression_type, CompressionType::Fractal);
+        // Con velocidad mínima: Fractal no llega a 1 MiB/s
+        assert_eq!(selection.choose(Some(1.0)).unwrap().compression_type, CompressionType::Zstd);
+        
+        // Empate de ratio: gana el primer candidato, no el más rápido
+        let tie = CodecSelection {
+            sample_size: 1024,
+            trials: vec![
+                trial(CompressionType::Gzip, 2.0, 900),
+                trial(CompressionType::Zstd, 2.0, 10),
+            ],
+        };
+        assert_eq!(tie.choose(None).unwrap().compression_type, CompressionType::Gzip);
+    }
+    
+    #[test]
+    fn test_trained_dictionary_compresses_small_concepts() {
+        let concept = |i: usize| {
+            format!(
+                "---\ntitle: Concepto {i}\ncategory: architecture\ntags: [fbcu, memoria, {tag}]\n---\n\n\
+                 # Concepto {i}\n\n## Contexto\n\nDescripción del concepto número {i} dentro de la bitácora.\n\n\
+                 ## Decisión\n\nSe adopta la alternativa {alt} tras evaluar el impacto.\n\n\
+                 ## Consecuencias\n\n- Ventaja {i}\n- Riesgo {risk}\n",
+                tag = ["rust", "qpx", "voxel", "telescope"][i % 4],
+                alt = i * 7 % 13,
+                risk = i * 3 % 5,
+            )
+        };
+        
+        let mut engine = FBCUEngine::new(FBCUConfig::default()).unwrap();
+        let cores: Vec<FBCUCore> = (0..300)
+            .map(|i| engine.compress_with(concept(i).as_bytes(), CompressionType::Gzip).unwrap())
+            .collect();
+        let dictionary = engine.train_dictionary(&cores).unwrap();
+        
+        // Un concepto nuevo, bajo el umbral: sin diccionario queda sin comprimir
+        let small = concept(1000);
+        assert!(small.len() < engine.config.compression_threshold);
+        assert_eq!(engine.compress(small.as_bytes()).unwrap().compression_type, CompressionType::None);
+        
+        engine.set_dictionary(dictionary.clone());
+        let core = engine.compress(small.as_bytes()).unwrap();
+        assert_eq!(core.compression_type, CompressionType::ZstdDictionary);
+        assert_eq!(core.metadata.dictionary_id, Some(dictionary.id()));
+        let gzip = engine.compress_with(small.as_bytes(), CompressionType::Gzip).unwrap();
+        assert!(core.compression_ratio > 2.0 * gzip.compression_ratio);
+        assert_eq!(engine.decompress(&core).unwrap(), small.as_bytes());
+        
+        // Otro engine: necesita el mismo diccionario (persistido como bytes)
+        let mut other = FBCUEngine::new(FBCUConfig::default()).unwrap();
+        assert!(matches!(other.decompress(&core), Err(FBCUError::DecompressionFailed(_))));
+        other.set_dictionary(FBCUDictionary::from_bytes(dictionary.as_bytes().to_vec()).unwrap());
+        assert_eq!(other.decompress(&core).unwrap(), small.as_bytes());
     }
     
     #[test]
@@ -672,16 +935,18 @@ mod tests {
     #[test]
     fn test_cache_functionality() {
         let mut engine = FBCUEngine::new(FBCUConfig {
-            cache_size: 2,
+            cache_capacity_bytes: 22,  // dos entradas de 11 bytes
             ..Default::default()
         })
         .unwrap();
         
         let data1 = b"Test data 1";
         let data2 = b"Test data 2";
+        let data3 = b"Test data 3";
         
         let core1 = engine.compress(data1).unwrap();
         let core2 = engine.compress(data2).unwrap();
+        let core3 = engine.compress(data3).unwrap();
         
         // Primera descompresión: cache miss
         engine.decompress(&core1).unwrap();
@@ -690,6 +955,53 @@ mod tests {
         // Segunda descompresión mismo dato: cache hit
         engine.decompress(&core1).unwrap();
         assert_eq!(engine.metrics.cache_hits, 1);
+        
+        // core1 es el más reciente: al entrar core3 se desaloja core2
+        engine.decompress(&core2).unwrap();
+        engine.decompress(&core1).unwrap();
+        engine.decompress(&core3).unwrap();
+        assert_eq!(engine.metrics.cache_evictions, 1);
+        engine.decompress(&core1).unwrap();
+        assert_eq!(engine.metrics.cache_hits, 3);
+        engine.decompress(&core2).unwrap();
+        assert_eq!(engine.metrics.cache_misses, 4);
+        assert!((engine.metrics().cache_hit_rate() - 3.0 / 7.0).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_cache_shared_between_engines() {
+        let cache = FBCUCache::shared(1024 * 1024);
+        let mut writer = FBCUEngine::with_cache(FBCUConfig::default(), Arc::clone(&cache)).unwrap();
+        let original = "Concepto compartido entre BStradivarius y FlowPacks.\n".repeat(100);
+        let core = writer.compress(original.as_bytes()).unwrap();
+        writer.decompress(&core).unwrap();
+        
+        // Otro engine en otro thread encuentra el core en la cache común
+        let handle = std::thread::spawn(move || {
+            let mut reader = FBCUEngine::with_cache(FBCUConfig::default(), cache).unwrap();
+            reader.decompress(&core).unwrap();
+            reader.metrics().cache_hits
+        });
+        assert_eq!(handle.join().unwrap(), 1);
+        
+        let stats = writer.cache().stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+        assert_eq!(stats.bytes, original.len());
+    }
+    
+    #[test]
+    fn test_cache_ignores_colliding_core_ids() {
+        let mut engine = FBCUEngine::new(FBCUConfig::default()).unwrap();
+        let mut first = engine.compress(b"Plantilla A").unwrap();
+        let mut second = engine.compress(b"Plantilla B").unwrap();
+        
+        // Dos cores decodificados con el mismo id no comparten entrada
+        first.id = "decoded".into();
+        second.id = "decoded".into();
+        assert_eq!(engine.decompress(&first).unwrap(), b"Plantilla A");
+        assert_eq!(engine.decompress(&second).unwrap(), b"Plantilla B");
+        assert_eq!(engine.decompress(&first).unwrap(), b"Plantilla A");
+        assert_eq!(engine.metrics.cache_hits, 1);
     }
     
     #[test]
@@ -0,0 +1,231 @@
+//! # Visual DNA - Imagen sin pérdida de un core
+//!
+//! Cada byte del core es un pixel de `VISUAL_DNA_WIDTH` por fila, coloreado
+//! con un hash afín por canal (`r = 137·b + 73`, etc. mod 256). Los
+//! multiplicadores son impares, así que el color se invierte exactamente y
+//! la imagen vuelve a dar los bytes originales.
+//!
+//! El ancho fijo alinea las filas entre versiones de un mismo core: dos
+//! imágenes se comparan a ojo o con cualquier herramienta de diff de
+//! imágenes. La última fila se rellena con negro, que no es el color de
+//! ningún byte.
+//!
+//! `CompressionType::QuantumVisual` guarda la imagen como PNG RGB de 8 bits.
+//! Es un modo para inspeccionar, no para ahorrar espacio: sobre
+//! `ROADMAP_V2/07_TEMPLATES` da ratio 1.38 frente a 2.85 de GZIP.
+
+use super::{FBCUError, Result};
+
+/// Pixels (bytes) por fila
+pub const VISUAL_DNA_WIDTH: usize = 64;
+
+/// Relleno de la última fila (ningún byte produce este color)
+const PADDING: RGB = RGB { r: 0, g: 0, b: 0 };
+
+/// Compresor Visual DNA Cuántico
+pub struct QuantumVisualCompressor {
+    level: u8,
+}
+
+impl QuantumVisualCompressor {
+    /// `level` (0-10) elige el esfuerzo de compresión del PNG
+    pub fn new(level: u8) -> Self {
+        Self { level }
+    }
+
+    /// Generar Visual DNA desde texto
+    pub fn generate_visual_dna(&self, text: &str) -> VisualDNA {
+        self.encode(text.as_bytes())
+    }
+
+    /// Generar Visual DNA desde bytes arbitrarios
+    pub fn encode(&self, data: &[u8]) -> VisualDNA {
+        let height = data.len().div_ceil(VISUAL_DNA_WIDTH).max(1);
+
+        let mut pixels: Vec<RGB> = data.iter().map(|&byte| byte_to_color(byte)).collect();
+        pixels.resize(VISUAL_DNA_WIDTH * height, PADDING);
+
+        VisualDNA {
+            pixels,
+            width: VISUAL_DNA_WIDTH,
+            height,
+        }
+    }
+
+    /// Visual DNA codificado como PNG
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let compression = match self.level {
+            0..=3 => png::Compression::Fast,
+            4..=7 => png::Compression::Default,
+            _ => png::Compression::Best,
+        };
+        self.encode(data).to_png(compression)
+    }
+
+    /// Inverso de `compress`
+    pub fn decompress(png_bytes: &[u8]) -> Result<Vec<u8>> {
+        VisualDNA::from_png(png_bytes)?.decode()
+    }
+}
+
+/// Visual DNA representation
+///
+/// `pixels` tiene exactamente `width * height` elementos, fila a fila.
+#[derive(Debug, Clone)]
+pub struct VisualDNA {
+    pub pixels: Vec<RGB>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl VisualDNA {
+    /// Recuperar los bytes originales
+    ///
+    /// Falla si algún pixel no es el color de un byte, o si hay relleno
+    /// antes del final.
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        if self.pixels.len() != self.width * self.height {
+            return Err(corrupted(format!(
+                "{} pixels for a {}x{} image",
+                self.pixels.len(),
+                self.width,
+                self.height
+            )));
+        }
+
+        let len = self.pixels.iter().rposition(|&pixel| pixel != PADDING).map_or(0, |last| last + 1);
+        self.pixels[..len]
+            .iter()
+            .enumerate()
+            .map(|(index, &pixel)| {
+                color_to_byte(pixel).ok_or_else(|| {
+                    corrupted(format!("pixel {} ({}, {}, {}) is not a byte colour", index, pixel.r, pixel.g, pixel.b))
+                })
+            })
+            .collect()
+    }
+
+    /// Codificar como PNG RGB de 8 bits
+    pub fn to_png(&self, compression: png::Compression) -> Result<Vec<u8>> {
+        let png_error = |e: png::EncodingError| FBCUError::CompressionFailed(format!("Visual DNA PNG: {}", e));
+        let (width, height) = match (u32::try_from(self.width), u32::try_from(self.height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(FBCUError::CompressionFailed(format!(
+                    "Visual DNA too large: {}x{}",
+                    self.width, self.height
+                )))
+            }
+        };
+
+        let mut output = Vec::new();
+        let mut encoder = png::Encoder::new(&mut output, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(compression);
+
+        let raw: Vec<u8> = self.pixels.iter().flat_map(|pixel| [pixel.r, pixel.g, pixel.b]).collect();
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&raw).map_err(png_error)?;
+        writer.finish().map_err(png_error)?;
+
+        Ok(output)
+    }
+
+    /// Leer un PNG RGB de 8 bits generado por `to_png`
+    pub fn from_png(png_bytes: &[u8]) -> Result<Self> {
+        let png_error = |e: png::DecodingError| corrupted(e.to_string());
+        let mut reader = png::Decoder::new(png_bytes).read_info().map_err(png_error)?;
+
+        let info = reader.info();
+        if info.color_type != png::ColorType::Rgb || info.bit_depth != png::BitDepth::Eight {
+            return Err(corrupted(format!(
+                "expected 8-bit RGB, got {:?} {:?}",
+                info.color_type, info.bit_depth
+            )));
+        }
+
+        let mut raw = vec![0u8; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut raw).map_err(png_error)?;
+        raw.truncate(frame.buffer_size());
+
+        Ok(Self {
+            pixels: raw.chunks_exact(3).map(|rgb| RGB { r: rgb[0], g: rgb[1], b: rgb[2] }).collect(),
+            width: frame.width as usize,
+            height: frame.height as usize,
+        })
+    }
+}
+
+/// RGB color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RGB {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// Hash determinístico (afín por canal, invertible)
+fn byte_to_color(byte: u8) -> RGB {
+    RGB {
+        r: byte.wrapping_mul(137).wrapping_add(73),
+        g: byte.wrapping_mul(211).wrapping_add(41),
+        b: byte.wrapping_mul(179).wrapping_add(97),
+    }
+}
+
+/// Inverso de `byte_to_color` (185 = 137⁻¹ mod 256); los otros canales
+/// confirman que el pixel no está alterado
+fn color_to_byte(pixel: RGB) -> Option<u8> {
+    let byte = pixel.r.wrapping_sub(73).wrapping_mul(185);
+    (byte_to_color(byte) == pixel).then_some(byte)
+}
+
+fn corrupted(reason: String) -> FBCUError {
+    FBCUError::DecompressionFailed(format!("Visual DNA: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_byte_colour_is_invertible() {
+        for byte in 0..=255u8 {
+            assert_eq!(color_to_byte(byte_to_color(byte)), Some(byte));
+            assert_ne!(byte_to_color(byte), PADDING);
+        }
+    }
+
+    #[test]
+    fn test_visual_dna_png_roundtrip() {
+        let compressor = QuantumVisualCompressor::new(5);
+        let binary: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+
+        for data in [&[][..], b"# Concepto\n", &binary] {
+            let dna = compressor.encode(data);
+            assert_eq!(dna.width, VISUAL_DNA_WIDTH);
+            assert_eq!(dna.height, data.len().div_ceil(VISUAL_DNA_WIDTH).max(1));
+            assert_eq!(dna.pixels.len(), dna.width * dna.height);
+
+            let png = compressor.compress(data).unwrap();
+            assert_eq!(&png[1..4], b"PNG");
+            assert_eq!(QuantumVisualCompressor::decompress(&png).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_visual_dna_rejects_altered_pixels() {
+        let compressor = QuantumVisualCompressor::new(5);
+        let mut dna = compressor.encode(b"Visual DNA");
+
+        // Relleno en medio: ya no es el final del dato
+        dna.pixels[3] = PADDING;
+        assert!(dna.decode().is_err());
+
+        dna.pixels[3] = RGB { r: 1, g: 2, b: 3 };
+        assert!(dna.decode().is_err());
+
+        assert!(QuantumVisualCompressor::decompress(b"not a png").is_err());
+    }
+}
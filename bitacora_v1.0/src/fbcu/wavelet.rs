@@ -0,0 +1,263 @@
+//! # Wavelet Transform - CDF 5/3 entera sin pérdida
+//!
+//! Transformada wavelet CDF 5/3 por lifting entero (la reversible de
+//! JPEG 2000) seguida del codificador aritmético adaptativo de `entropy`:
+//!
+//! 1. Cada nivel parte la banda baja actual en pasa-bajos `s` y pasa-altos
+//!    `d` con extensión simétrica en los bordes:
+//!    - `d[i] = x[2i+1] - ⌊(x[2i] + x[2i+2]) / 2⌋`
+//!    - `s[i] = x[2i] + ⌊(d[i-1] + d[i] + 2) / 4⌋`
+//! 2. Se repite sobre `s` hasta `wavelet_level` niveles (o hasta que la banda
+//!    baja tenga un solo coeficiente).
+//! 3. Los coeficientes se codifican por banda con contexto propio.
+//!
+//! Todo es aritmética entera, así que la inversa reconstruye el original byte
+//! a byte y el `original_hash` del core se verifica sin problemas.
+//!
+//! ## Ratio medido
+//!
+//! `cargo bench --bench fbcu_codecs` sobre `ROADMAP_V2/07_TEMPLATES`
+//! (22 archivos, 200608 bytes): Wavelet 1.14, Hybrid 1.13, frente a GZIP 2.85.
+//! Sobre texto la transformada apenas decorrela; rinde en señales suaves
+//! (embeddings cuantizados, gradientes, pixels), donde supera 3:1.
+//!
+//! ## Formato
+//!
+//! ```text
+//! versión (u8) | niveles (u8) | longitud (u32 LE) | flujo aritmético
+//! ```
+
+use super::entropy::{CoefficientModel, RangeDecoder, RangeEncoder};
+use super::{FBCUError, Result};
+
+const FORMAT_VERSION: u8 = 1;
+
+const HEADER_SIZE: usize = 2 + 4;
+
+/// Transformada Wavelet CDF 5/3 + codificación entrópica
+pub struct WaveletTransform {
+    level: u8,
+}
+
+impl WaveletTransform {
+    pub fn new(level: u8) -> Self {
+        Self { level: level.min(10) }
+    }
+
+    /// Comprimir datos usando wavelet
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let len = u32::try_from(data.len()).map_err(|_| {
+            FBCUError::CompressionFailed(format!("Wavelet input too large: {} bytes", data.len()))
+        })?;
+
+        let levels = effective_levels(data.len(), self.level);
+        let mut coeffs: Vec<i32> = data.iter().map(|&b| b as i32).collect();
+        let mut scratch = Vec::with_capacity(coeffs.len());
+        let mut low_len = coeffs.len();
+        for _ in 0..levels {
+            forward_step(&mut coeffs[..low_len], &mut scratch);
+            low_len = low_len.div_ceil(2);
+        }
+
+        let mut header = Vec::with_capacity(HEADER_SIZE + data.len() / 2);
+        header.push(FORMAT_VERSION);
+        header.push(levels);
+        header.extend_from_slice(&len.to_le_bytes());
+
+        let bands = band_ranges(data.len(), levels);
+        let mut model = CoefficientModel::new(bands.len());
+        let mut encoder = RangeEncoder::new(header);
+        for (band, range) in bands.into_iter().enumerate() {
+            model.start_band();
+            for &value in &coeffs[range] {
+                model.encode(&mut encoder, band, value);
+            }
+        }
+
+        Ok(encoder.finish())
+    }
+
+    /// Descomprimir datos
+    pub fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>> {
+        if compressed.len() < HEADER_SIZE {
+            return Err(corrupted(format!("stream too short: {} bytes", compressed.len())));
+        }
+        if compressed[0] != FORMAT_VERSION {
+            return Err(corrupted(format!("unsupported version {}", compressed[0])));
+        }
+        let levels = compressed[1];
+        let len = u32::from_le_bytes([compressed[2], compressed[3], compressed[4], compressed[5]]) as usize;
+        if levels != effective_levels(len, levels) {
+            return Err(corrupted(format!("{} levels for {} bytes", levels, len)));
+        }
+
+        // Cota: el coder gasta al menos ~0.02 bits por coeficiente
+        if len > (compressed.len() - HEADER_SIZE + 8) * 512 {
+            return Err(corrupted(format!(
+                "{} bytes declared for a {} byte stream",
+                len,
+                compressed.len()
+            )));
+        }
+
+        let bands = band_ranges(len, levels);
+        let mut model = CoefficientModel::new(bands.len());
+        let mut decoder = RangeDecoder::new(&compressed[HEADER_SIZE..]);
+        let mut coeffs = vec![0i32; len];
+        for (band, range) in bands.into_iter().enumerate() {
+            model.start_band();
+            for value in &mut coeffs[range] {
+                *value = model.decode(&mut decoder, band);
+            }
+        }
+
+        let mut scratch = Vec::with_capacity(len);
+        for level in (0..levels).rev() {
+            let low_len = low_band_len(len, level);
+            inverse_step(&mut coeffs[..low_len], &mut scratch);
+        }
+
+        coeffs
+            .into_iter()
+            .map(|value| u8::try_from(value).map_err(|_| corrupted(format!("sample {} out of range", value))))
+            .collect()
+    }
+}
+
+/// Niveles aplicables: cada nivel necesita al menos 2 muestras en la banda baja
+fn effective_levels(len: usize, requested: u8) -> u8 {
+    let mut levels = 0;
+    let mut low_len = len;
+    while levels < requested && low_len >= 2 {
+        low_len = low_len.div_ceil(2);
+        levels += 1;
+    }
+    levels
+}
+
+/// Longitud de la banda baja tras `level` niveles
+fn low_band_len(len: usize, level: u8) -> usize {
+    (0..level).fold(len, |low_len, _| low_len.div_ceil(2))
+}
+
+/// Bandas en el orden del buffer: banda baja final, luego altas de la más
+/// gruesa a la más fina
+fn band_ranges(len: usize, levels: u8) -> Vec<std::ops::Range<usize>> {
+    std::iter::once(0..low_band_len(len, levels))
+        .chain((0..levels).rev().map(|level| low_band_len(len, level + 1)..low_band_len(len, level)))
+        .collect()
+}
+
+/// `d[i]` con extensión simétrica (`d[-1] = d[0]`, `d[n] = d[n-1]`)
+fn detail_at(details: &[i32], index: isize) -> i32 {
+    details[index.clamp(0, details.len() as isize - 1) as usize]
+}
+
+/// Un nivel de lifting: `x` → `[s | d]`
+fn forward_step(x: &mut [i32], scratch: &mut Vec<i32>) {
+    let n = x.len();
+    let evens = n.div_ceil(2);
+    let odds = n / 2;
+
+    scratch.clear();
+    scratch.resize(n, 0);
+    let (low, high) = scratch.split_at_mut(evens);
+
+    for i in 0..odds {
+        let right = if 2 * i + 2 < n { x[2 * i + 2] } else { x[2 * i] };
+        high[i] = x[2 * i + 1] - ((x[2 * i] + right) >> 1);
+    }
+    for i in 0..evens {
+        let update = (detail_at(high, i as isize - 1) + detail_at(high, i as isize) + 2) >> 2;
+        low[i] = x[2 * i] + update;
+    }
+
+    x.copy_from_slice(scratch);
+}
+
+/// Inversa de `forward_step`: `[s | d]` → `x`
+fn inverse_step(x: &mut [i32], scratch: &mut Vec<i32>) {
+    let n = x.len();
+    let evens = n.div_ceil(2);
+    let odds = n / 2;
+
+    scratch.clear();
+    scratch.resize(n, 0);
+    let (low, high) = x.split_at(evens);
+
+    for i in 0..evens {
+        // Wrapping: un flujo corrupto no debe desbordar (el rango se valida al final)
+        let update = detail_at(high, i as isize - 1)
+            .wrapping_add(detail_at(high, i as isize))
+            .wrapping_add(2)
+            >> 2;
+        scratch[2 * i] = low[i].wrapping_sub(update);
+    }
+    for i in 0..odds {
+        let right = if 2 * i + 2 < n { scratch[2 * i + 2] } else { scratch[2 * i] };
+        scratch[2 * i + 1] = high[i].wrapping_add(scratch[2 * i].wrapping_add(right) >> 1);
+    }
+
+    x.copy_from_slice(scratch);
+}
+
+fn corrupted(reason: String) -> FBCUError {
+    FBCUError::DecompressionFailed(format!("Wavelet: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifting_is_exactly_reversible() {
+        let mut scratch = Vec::new();
+        for n in 2..40 {
+            let original: Vec<i32> = (0..n).map(|i| (i * 37 + 11) % 256).collect();
+            let mut coeffs = original.clone();
+            forward_step(&mut coeffs, &mut scratch);
+            assert_ne!(coeffs, original);
+            inverse_step(&mut coeffs, &mut scratch);
+            assert_eq!(coeffs, original, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_wavelet_roundtrip_all_levels() {
+        let text = "## Decisión\n\nUsar CDF 5/3 entera para el path wavelet.\n".repeat(20);
+        let gradient: Vec<u8> = (0..2048).map(|i| (i / 8) as u8).collect();
+
+        for level in 0..=10 {
+            let wavelet = WaveletTransform::new(level);
+            for input in [&[][..], &[42][..], &[0, 255, 0][..], text.as_bytes(), &gradient] {
+                let compressed = wavelet.compress(input).unwrap();
+                assert_eq!(wavelet.decompress(&compressed).unwrap(), input, "level {}", level);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wavelet_compresses_smooth_signals() {
+        let wavelet = WaveletTransform::new(5);
+        let gradient: Vec<u8> = (0..4096).map(|i| ((i as f64 / 40.0).sin() * 100.0 + 128.0) as u8).collect();
+
+        let compressed = wavelet.compress(&gradient).unwrap();
+        assert!(compressed.len() * 3 < gradient.len(), "{} bytes", compressed.len());
+    }
+
+    #[test]
+    fn test_wavelet_rejects_corrupted_streams() {
+        let wavelet = WaveletTransform::new(5);
+        let compressed = wavelet.compress(&[7u8; 300]).unwrap();
+
+        assert!(wavelet.decompress(&compressed[..3]).is_err());
+
+        let mut wrong_levels = compressed.clone();
+        wrong_levels[1] = 20;
+        assert!(wavelet.decompress(&wrong_levels).is_err());
+
+        let mut huge = compressed.clone();
+        huge[2..6].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(wavelet.decompress(&huge).is_err());
+    }
+}
@@ -5,9 +5,9 @@
 
 use super::{FlowPack, FlowPackEntry, FlowPackConfig, Result};
 use super::error::FlowPackError;
-use std::io::Error as IoError;
+use crate::fbcu::{CompressionType, FBCUCache, FBCUConfig, FBCUCore, FBCUEngine, FBCUMetadata};
 use serde::{Serialize, Deserialize};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Estrategia de compresión para FlowPacks
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,49 +76,100 @@ impl CompressionResult {
     }
 }
 
-/// Motor de compresión FBCU (stub - integración real pendiente)
+/// Mensajes más cortos se guardan sin comprimir (el umbral de 1 KB de FBCU
+/// dejaría sin comprimir casi todos los mensajes de chat)
+const MIN_COMPRESSIBLE_LEN: usize = 128;
+
+/// Cabecera del frame: tag del codec (1) + SHA-256 del original (32)
+const FRAME_HEADER_SIZE: usize = 1 + 32;
+
+/// Motor de compresión FBCU
+///
+/// Frame: `tag del codec | SHA-256 del original | datos FBCU`. El hash es el
+/// `id` del core: verifica la integridad y es la clave de la cache de
+/// descompresión, que puede compartirse con BStradivarius.
 pub struct FBCUCompressor {
     config: FlowPackConfig,
-    // TODO: Arc<fbcu::FBCUEngine> cuando se integre FBCU
+    engine: Mutex<FBCUEngine>,
 }
 
 impl FBCUCompressor {
     /// Crea un nuevo compresor FBCU
     pub fn new(config: FlowPackConfig) -> Result<Self> {
-        Ok(Self { config })
+        let cache = FBCUCache::shared(FBCUConfig::default().cache_capacity_bytes);
+        Self::with_cache(config, cache)
+    }
+
+    /// Crea un compresor FBCU con una cache de descompresión compartida
+    pub fn with_cache(config: FlowPackConfig, cache: Arc<FBCUCache>) -> Result<Self> {
+        let fbcu_config = FBCUConfig {
+            compression_threshold: MIN_COMPRESSIBLE_LEN,
+            wavelet_level: config.wavelet_level,
+            fractal_level: config.fractal_level,
+            ..Default::default()
+        };
+        let engine = FBCUEngine::with_cache(fbcu_config, cache)
+            .map_err(|e| FlowPackError::FBCUError(e.to_string()))?;
+
+        Ok(Self { config, engine: Mutex::new(engine) })
     }
 
     /// Comprime texto usando FBCU
     pub fn compress(&self, text: &str) -> Result<Vec<u8>> {
-        // TODO: Integración real con FBCU
-        // Por ahora: simulación usando zlib (placeholder)
-        
-        use std::io::Write;
-        use flate2::Compression;
-        use flate2::write::ZlibEncoder;
-
-        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
-        encoder.write_all(text.as_bytes())
-            .map_err(|e: IoError| FlowPackError::CompressionFailed(e.to_string()))?;
-        
-        encoder.finish()
-            .map_err(|e: IoError| FlowPackError::CompressionFailed(e.to_string()))
+        let core = self.engine()?.compress(text.as_bytes())
+            .map_err(|e| FlowPackError::FBCUError(e.to_string()))?;
+        let hash = hex::decode(&core.id)
+            .map_err(|e| FlowPackError::FBCUError(e.to_string()))?;
+
+        let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + core.compressed_data.len());
+        frame.push(core.compression_type.tag());
+        frame.extend_from_slice(&hash);
+        frame.extend_from_slice(&core.compressed_data);
+        Ok(frame)
     }
 
     /// Descomprime datos FBCU
     pub fn decompress(&self, data: &[u8]) -> Result<String> {
-        // TODO: Integración real con FBCU
-        // Por ahora: simulación usando zlib (placeholder)
-        
-        use std::io::Read;
-        use flate2::read::ZlibDecoder;
+        if data.len() < FRAME_HEADER_SIZE {
+            return Err(FlowPackError::CompressionFailed(format!(
+                "FBCU frame too short: {} bytes", data.len()
+            )));
+        }
+        let compression_type = CompressionType::from_tag(data[0])
+            .ok_or_else(|| FlowPackError::CompressionFailed(format!("Unknown FBCU codec tag {}", data[0])))?;
+        let id = hex::encode(&data[1..FRAME_HEADER_SIZE]);
+
+        // Core mínimo: `decompress` solo necesita codec, datos e id/hash
+        let core = FBCUCore {
+            id: id.clone(),
+            compression_type,
+            compressed_data: data[FRAME_HEADER_SIZE..].to_vec(),
+            original_size: 0,
+            compression_ratio: 0.0,
+            metadata: FBCUMetadata {
+                compressed_at: String::new(),
+                compression_time_ms: 0,
+                original_hash: id,
+                wavelet_level: None,
+                fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
+            },
+        };
+        let bytes = self.engine()?.decompress(&core)
+            .map_err(|e| FlowPackError::FBCUError(e.to_string()))?;
 
-        let mut decoder = ZlibDecoder::new(data);
-        let mut decompressed = String::new();
-        decoder.read_to_string(&mut decompressed)
-            .map_err(|e: IoError| FlowPackError::CompressionFailed(e.to_string()))?;
-        
-        Ok(decompressed)
+        String::from_utf8(bytes).map_err(|e| FlowPackError::CompressionFailed(e.to_string()))
+    }
+
+    /// Cache de descompresión del engine FBCU
+    pub fn cache(&self) -> Result<Arc<FBCUCache>> {
+        Ok(Arc::clone(self.engine()?.cache()))
+    }
+
+    fn engine(&self) -> Result<MutexGuard<'_, FBCUEngine>> {
+        self.engine.lock()
+            .map_err(|e| FlowPackError::FBCUError(e.to_string()))
     }
 
     /// Estima el ratio de compresión esperado
@@ -281,6 +332,14 @@ impl CompressionEngine {
         Ok(Self { fbcu, delta, config })
     }
 
+    /// Crea un motor de compresión con una cache FBCU compartida
+    pub fn with_fbcu_cache(config: FlowPackConfig, cache: Arc<FBCUCache>) -> Result<Self> {
+        let fbcu = FBCUCompressor::with_cache(config.clone(), cache)?;
+        let delta = DeltaCompressor::new(config.clone());
+        
+        Ok(Self { fbcu, delta, config })
+    }
+
     /// Comprime un FlowPack completo
     pub fn compress_flowpack(&self, flowpack: &mut FlowPack) -> Result<CompressionResult> {
         let start = std::time::Instant::now();
@@ -360,6 +419,28 @@ mod tests {
             "Compressed {} should be < original {}", compressed.len(), text.len());
     }
 
+    #[test]
+    fn test_fbcu_cache_shared_between_compressors() {
+        let cache = FBCUCache::shared(1024 * 1024);
+        let writer = FBCUCompressor::with_cache(FlowPackConfig::default(), Arc::clone(&cache)).unwrap();
+        let reader = FBCUCompressor::with_cache(FlowPackConfig::default(), Arc::clone(&cache)).unwrap();
+        
+        let text = "Mensaje repetido del usuario sobre el mismo tema. ".repeat(20);
+        let compressed = writer.compress(&text).unwrap();
+        assert_eq!(writer.decompress(&compressed).unwrap(), text);
+        
+        // El segundo compresor encuentra el mensaje en la cache común
+        assert_eq!(reader.decompress(&compressed).unwrap(), text);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        
+        // Frame corrupto: el hash no coincide
+        let mut corrupted = compressed.clone();
+        corrupted[5] ^= 0xFF;
+        assert!(reader.decompress(&corrupted).is_err());
+        assert!(reader.decompress(&compressed[..10]).is_err());
+    }
+
     #[test]
     fn test_delta_compression() {
         let config = FlowPackConfig::default();
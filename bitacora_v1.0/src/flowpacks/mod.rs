@@ -70,6 +70,7 @@ pub use compression::{
     FBCUCompressor, DeltaCompressor,
 };
 
+use crate::fbcu::FBCUCache;
 use lru::LruCache;
 use std::sync::{Arc, RwLock};
 use std::num::NonZeroUsize;
@@ -100,6 +101,13 @@ pub struct FlowPackEngine {
 impl FlowPackEngine {
     /// Crea un nuevo motor de FlowPacks
     pub fn new(config: FlowPackConfig) -> Result<Self> {
+        let cache = FBCUCache::shared(crate::fbcu::FBCUConfig::default().cache_capacity_bytes);
+        Self::with_fbcu_cache(config, cache)
+    }
+
+    /// Crea un motor de FlowPacks que comparte la cache de descompresión FBCU
+    /// (p. ej. con `FBCUIntegration` de BStradivarius)
+    pub fn with_fbcu_cache(config: FlowPackConfig, fbcu_cache: Arc<FBCUCache>) -> Result<Self> {
         config.validate()
             .map_err(|e| FlowPackError::CompressionFailed(format!("Config validation: {}", e)))?;
         
@@ -108,7 +116,7 @@ impl FlowPackEngine {
         ));
         
         let compression_engine = Arc::new(
-            CompressionEngine::new(config.clone())?
+            CompressionEngine::with_fbcu_cache(config.clone(), fbcu_cache)?
         );
         
         let cache_size = NonZeroUsize::new(config.cache_size)
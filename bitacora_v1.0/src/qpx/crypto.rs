@@ -0,0 +1,311 @@
+//! QPX Encryption-at-rest (ChaCha20-Poly1305)
+//!
+//! Encrypts the whole payload of a record (PixelBlock, QuantumMeta and any
+//! optional section) and marks the header with `QPX_FLAG_ENCRYPTED`:
+//!
+//! ```text
+//! header (48) | nonce (12) | ciphertext | tag (16) | footer (64)
+//! ```
+//!
+//! - The header stays in clear (record type, counts, offsets of the plaintext
+//!   layout) and is authenticated as associated data, so flipping a flag or an
+//!   offset is detected as tampering.
+//! - `footer_offset` and the footer SHA-256 describe the encrypted record, so
+//!   record framing, torn-tail detection and checksum verification keep
+//!   working without the key.
+//! - Decrypting restores the original plaintext record byte for byte.
+//!
+//! Keys are 256-bit, supplied by the caller (`QPXKey::from_bytes`) or read
+//! from a local keyfile (`QPXKey::from_keyfile`).
+
+use std::fmt;
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::qpx::decoder::QPXDecoder;
+use crate::qpx::encoder::QPXEncoder;
+use crate::qpx::error::{QPXError, Result};
+use crate::qpx::header::{QPXHeader, QPX_FLAG_ENCRYPTED, QPX_FOOTER_SIZE};
+
+/// Nonce size (bytes)
+pub const QPX_NONCE_SIZE: usize = 12;
+
+/// Authentication tag size (bytes)
+pub const QPX_TAG_SIZE: usize = 16;
+
+/// Bytes an encrypted record adds to its plaintext form
+const ENCRYPTION_OVERHEAD: usize = QPX_NONCE_SIZE + QPX_TAG_SIZE;
+
+/// 256-bit record encryption key
+#[derive(Clone, PartialEq, Eq)]
+pub struct QPXKey([u8; 32]);
+
+impl QPXKey {
+    /// Key size (bytes)
+    pub const SIZE: usize = 32;
+
+    /// Key from raw bytes
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Random key from the OS RNG
+    pub fn generate() -> Self {
+        Self(ChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    /// Raw key bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Read a keyfile: 64 hex characters (surrounding whitespace ignored) or
+    /// exactly 32 raw bytes
+    pub fn from_keyfile(path: &Path) -> Result<Self> {
+        let contents = std::fs::read(path)?;
+
+        let hex_key = std::str::from_utf8(&contents)
+            .ok()
+            .map(str::trim)
+            .filter(|text| text.len() == 2 * Self::SIZE)
+            .and_then(|text| hex::decode(text).ok());
+        let bytes = match hex_key {
+            Some(bytes) => bytes,
+            None if contents.len() == Self::SIZE => contents,
+            None => {
+                return Err(QPXError::InvalidKey(format!(
+                    "{:?}: expected {} hex characters or {} raw bytes",
+                    path,
+                    2 * Self::SIZE,
+                    Self::SIZE
+                )))
+            }
+        };
+
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&bytes);
+        Ok(Self(key))
+    }
+
+    /// Write the key as hex to a new keyfile (owner-only permissions on Unix)
+    ///
+    /// Fails if `path` already exists, so an existing key is never overwritten.
+    pub fn write_keyfile(&self, path: &Path) -> Result<()> {
+        use std::io::Write;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(path)?;
+        writeln!(file, "{}", hex::encode(self.0))?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(&Key::from(self.0))
+    }
+}
+
+impl fmt::Debug for QPXKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QPXKey(..)")
+    }
+}
+
+/// Whether `bytes` starts with the header of an encrypted record
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    QPXHeader::from_bytes(bytes).is_ok_and(|header| header.is_encrypted())
+}
+
+/// Encrypt a plaintext record (any major type)
+///
+/// Uses a fresh random nonce, so encrypting the same record twice gives
+/// different bytes.
+pub fn encrypt_record(bytes: &[u8], key: &QPXKey) -> Result<Vec<u8>> {
+    let header = read_header(bytes)?;
+    if header.is_encrypted() {
+        return Err(QPXError::EncodingFailed("Record is already encrypted".into()));
+    }
+    QPXDecoder::verify_footer(bytes, &header)?;
+
+    let plaintext = &bytes[QPXHeader::SIZE..header.footer_offset as usize];
+    let mut encrypted_header = header;
+    encrypted_header.flags |= QPX_FLAG_ENCRYPTED;
+    encrypted_header.footer_offset = u32::try_from(header.footer_offset as usize + ENCRYPTION_OVERHEAD)
+        .map_err(|_| QPXError::EncodingFailed("Encrypted record too large".into()))?;
+    let header_bytes = encrypted_header.to_bytes()?;
+
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = key
+        .cipher()
+        .encrypt(&nonce, Payload { msg: plaintext, aad: &header_bytes })
+        .map_err(|_| QPXError::EncodingFailed("Encryption failed".into()))?;
+
+    let mut buffer = Vec::with_capacity(encrypted_header.footer_offset as usize + QPX_FOOTER_SIZE);
+    buffer.extend_from_slice(&header_bytes);
+    buffer.extend_from_slice(&nonce);
+    buffer.extend_from_slice(&ciphertext);
+    QPXEncoder::write_footer(&mut buffer)?;
+
+    Ok(buffer)
+}
+
+/// Decrypt an encrypted record back to its plaintext form
+///
+/// # Errors
+/// * `ChecksumMismatch` / `DecodingFailed` - Corrupted or truncated record
+/// * `AuthenticationFailed` - Wrong key, record modified after encryption, or
+///   record not encrypted at all
+pub fn decrypt_record(bytes: &[u8], key: &QPXKey) -> Result<Vec<u8>> {
+    let header = read_header(bytes)?;
+    if !header.is_encrypted() {
+        // A plaintext record cannot be authenticated: never accept it silently
+        return Err(QPXError::AuthenticationFailed);
+    }
+    QPXDecoder::verify_footer(bytes, &header)?;
+
+    let footer_offset = header.footer_offset as usize;
+    if footer_offset < QPXHeader::SIZE + ENCRYPTION_OVERHEAD {
+        return Err(QPXError::DecodingFailed(format!(
+            "Encrypted payload too short: {} bytes",
+            footer_offset.saturating_sub(QPXHeader::SIZE)
+        )));
+    }
+
+    let mut nonce = Nonce::default();
+    nonce.copy_from_slice(&bytes[QPXHeader::SIZE..QPXHeader::SIZE + QPX_NONCE_SIZE]);
+    let ciphertext = &bytes[QPXHeader::SIZE + QPX_NONCE_SIZE..footer_offset];
+    let plaintext = key
+        .cipher()
+        .decrypt(&nonce, Payload { msg: ciphertext, aad: &bytes[..QPXHeader::SIZE] })
+        .map_err(|_| QPXError::AuthenticationFailed)?;
+
+    let mut plain_header = header;
+    plain_header.flags &= !QPX_FLAG_ENCRYPTED;
+    plain_header.footer_offset -= ENCRYPTION_OVERHEAD as u32;
+
+    let mut buffer = Vec::with_capacity(plain_header.footer_offset as usize + QPX_FOOTER_SIZE);
+    buffer.extend_from_slice(&plain_header.to_bytes()?);
+    buffer.extend_from_slice(&plaintext);
+    QPXEncoder::write_footer(&mut buffer)?;
+
+    Ok(buffer)
+}
+
+fn read_header(bytes: &[u8]) -> Result<QPXHeader> {
+    if bytes.len() < QPXHeader::SIZE + QPX_FOOTER_SIZE {
+        return Err(QPXError::DecodingFailed(format!(
+            "Record too small: {} bytes (minimum {})",
+            bytes.len(),
+            QPXHeader::SIZE + QPX_FOOTER_SIZE
+        )));
+    }
+    let header = QPXHeader::from_bytes(bytes)?;
+    header.validate()?;
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qpx::encoder::QPXPixelBlock;
+    use crate::qpx::{Pixel, QPX_FLAG_TOMBSTONE};
+    use chrono::Utc;
+
+    fn sample_record(flags: u8) -> Vec<u8> {
+        QPXEncoder::encode_pixel_block(&QPXPixelBlock {
+            id: "biography-1".to_string(),
+            timestamp: Utc::now(),
+            pixels: vec![Pixel::new(10, 20, 30, 255); 4],
+            flags,
+            meta: b"sensitive biographical note".to_vec(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let key = QPXKey::generate();
+        let plain = sample_record(QPX_FLAG_TOMBSTONE);
+
+        let encrypted = encrypt_record(&plain, &key).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!is_encrypted(&plain));
+        assert_eq!(encrypted.len(), plain.len() + ENCRYPTION_OVERHEAD);
+        assert!(!encrypted.windows(11).any(|w| w == b"biographica"));
+
+        // Random nonce: same record, different bytes
+        assert_ne!(encrypt_record(&plain, &key).unwrap(), encrypted);
+
+        assert_eq!(decrypt_record(&encrypted, &key).unwrap(), plain);
+        assert!(encrypt_record(&encrypted, &key).is_err());
+        assert!(matches!(decrypt_record(&plain, &key), Err(QPXError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn test_wrong_key_and_tampering_fail_cleanly() {
+        let key = QPXKey::generate();
+        let encrypted = encrypt_record(&sample_record(0), &key).unwrap();
+
+        assert!(matches!(
+            decrypt_record(&encrypted, &QPXKey::generate()),
+            Err(QPXError::AuthenticationFailed)
+        ));
+
+        // Flip a ciphertext or header bit and recompute the footer: the
+        // checksum passes but the tag does not
+        let footer_offset = encrypted.len() - QPX_FOOTER_SIZE;
+        for position in [QPXHeader::SIZE + QPX_NONCE_SIZE + 3, 12] {
+            let mut tampered = encrypted[..footer_offset].to_vec();
+            tampered[position] ^= 0x01;
+            QPXEncoder::write_footer(&mut tampered).unwrap();
+            assert!(matches!(
+                decrypt_record(&tampered, &key),
+                Err(QPXError::AuthenticationFailed)
+            ));
+        }
+
+        // Without recomputing the footer it fails earlier, on the checksum
+        let mut corrupted = encrypted.clone();
+        corrupted[QPXHeader::SIZE + 1] ^= 0x01;
+        assert!(matches!(
+            decrypt_record(&corrupted, &key),
+            Err(QPXError::ChecksumMismatch { .. })
+        ));
+
+        // Keyless decoders reject the record instead of misparsing it
+        assert!(matches!(
+            QPXDecoder::decode_pixel_block(&encrypted),
+            Err(QPXError::EncryptedRecord)
+        ));
+    }
+
+    #[test]
+    fn test_keyfile_formats() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let key = QPXKey::generate();
+
+        let hex_path = temp_dir.path().join("qpx.key");
+        key.write_keyfile(&hex_path).unwrap();
+        assert_eq!(QPXKey::from_keyfile(&hex_path).unwrap(), key);
+        assert!(key.write_keyfile(&hex_path).is_err());
+
+        let raw_path = temp_dir.path().join("qpx.raw");
+        std::fs::write(&raw_path, key.as_bytes()).unwrap();
+        assert_eq!(QPXKey::from_keyfile(&raw_path).unwrap(), key);
+
+        let bad_path = temp_dir.path().join("bad.key");
+        std::fs::write(&bad_path, "not a key").unwrap();
+        assert!(matches!(QPXKey::from_keyfile(&bad_path), Err(QPXError::InvalidKey(_))));
+
+        assert_eq!(format!("{:?}", key), "QPXKey(..)");
+    }
+}
@@ -2,9 +2,12 @@
 //!
 //! Supports decoding:
 //! - QuantumCore (0x60) .qpx files → QPXQuantumCore
-//! - Future: Voxels, Branches, Entanglements
+//! - PixelBlock (0x40) records → QPXPixelBlock
+//! - Entanglement (0x80) records → QPXEntanglementMap
+//! - Branch (0xA0) records → Vec<QPXBranch>
+//! - Future: Voxels
 
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek, SeekFrom};
 use byteorder::{LittleEndian, ReadBytesExt};
 use sha2::{Sha256, Digest};
 use chrono::{DateTime, Utc};
@@ -13,7 +16,13 @@ use crate::fbcu::{FBCUCore, CompressionType, FBCUMetadata};
 use crate::qpx::error::{QPXError, Result};
 use crate::qpx::header::{QPXHeader, QPXMajorType};
 use crate::qpx::pixel::Pixel;
-use crate::qpx::encoder::{QPXQuantumCore, TemplateMetadata};
+use crate::qpx::encoder::{QPXPixelBlock, QPXQuantumCore, TemplateMetadata};
+use crate::qpx::entanglement::{self, QPXBranch, QPXEntanglement, QPXEntanglementMap};
+use crate::qpx::timeline::{self, QPXTimelineEntry};
+use crate::telescopedb::ContextTensor7D;
+use crate::qpx::header::QPX_FOOTER_SIZE;
+use crate::qpx::version::QPXFormatVersion;
+use crate::qpx::crypto::{self, QPXKey};
 use crate::qpx::{QPX_MAGIC, QPX_MAGIC_END, QPX_VERSION};
 
 /// QPX Decoder for reading .qpx binary files
@@ -42,6 +51,7 @@ impl QPXDecoder {
         // 1. Parse and validate header
         let header = QPXHeader::from_bytes(&bytes[..QPXHeader::SIZE])?;
         header.validate()?;
+        Self::ensure_plaintext(&header)?;
 
         if header.major_type != QPXMajorType::QuantumCore as u8 {
             return Err(QPXError::DecodingFailed(
@@ -49,12 +59,239 @@ impl QPXDecoder {
             ));
         }
 
-        // 2. Verify file checksum (exclude footer itself)
+        // 2-3. Verify file checksum and footer magic
+        Self::verify_footer(bytes, &header)?;
+
+        // 4. Read PixelBlock
+        let pixel_block_offset = header.pixel_block_offset as usize;
+        let pixel_count = header.pixel_count as usize;
+        let pixels = Self::read_pixel_block(bytes, pixel_block_offset, pixel_count)?;
+
+        // 5. Read QuantumMeta
+        let quantum_meta_offset = header.quantum_meta_offset as usize;
+        let (fbcu_core, metadata, checksum, timestamp) = 
+            Self::read_quantum_meta(bytes, &header, quantum_meta_offset)?;
+
+        // 6. Read EntanglementMap and BranchTable (absent in older records)
+        let entanglements = Self::read_entanglement_section(bytes, &header)?;
+        let branches = Self::read_branch_section(bytes, &header)?;
+        let timeline = Self::read_timeline_section(bytes, &header)?;
+        let context = Self::read_context_section(bytes, &header)?;
+
+        // 7. Determine alpha from first pixel (convention: all core pixels same alpha)
+        let alpha = pixels.first()
+            .map(|p| p.alpha)
+            .unwrap_or(255);
+
+        Ok(QPXQuantumCore {
+            fbcu_core,
+            pixels,
+            alpha,
+            metadata,
+            timestamp,
+            checksum,
+            entanglements,
+            branches,
+            timeline,
+            context,
+        })
+    }
+
+    /// Decode a standalone Entanglement (0x80) record
+    ///
+    /// `bytes` must contain exactly one record (header through footer).
+    pub fn decode_entanglement_map(bytes: &[u8]) -> Result<QPXEntanglementMap> {
+        let header = Self::read_record_header(bytes, QPXMajorType::Entanglement)?;
+
+        let entanglements = Self::read_entanglement_section(bytes, &header)?;
+
+        let meta_start = header.quantum_meta_offset as usize;
+        let meta_end = header.footer_offset as usize;
+        if meta_start == 0 || meta_start > meta_end {
+            return Err(QPXError::InvalidOffset(format!(
+                "quantum_meta_offset ({}) outside record (footer at {})",
+                meta_start, meta_end
+            )));
+        }
+        let source_id = Self::read_string(&mut Cursor::new(&bytes[meta_start..meta_end]))?;
+
+        Ok(QPXEntanglementMap { source_id, entanglements })
+    }
+
+    /// Decode a standalone Branch (0xA0) record
+    ///
+    /// `bytes` must contain exactly one record (header through footer).
+    pub fn decode_branch_table(bytes: &[u8]) -> Result<Vec<QPXBranch>> {
+        let header = Self::read_record_header(bytes, QPXMajorType::Branch)?;
+        Self::read_branch_section(bytes, &header)
+    }
+
+    /// Parse header of a standalone record, check its type and footer
+    fn read_record_header(bytes: &[u8], expected: QPXMajorType) -> Result<QPXHeader> {
+        if bytes.len() < QPXHeader::SIZE + QPX_FOOTER_SIZE {
+            return Err(QPXError::DecodingFailed(format!(
+                "Record too small: {} bytes (minimum {})",
+                bytes.len(),
+                QPXHeader::SIZE + QPX_FOOTER_SIZE
+            )));
+        }
+
+        let header = QPXHeader::from_bytes(&bytes[..QPXHeader::SIZE])?;
+        header.validate()?;
+        Self::ensure_plaintext(&header)?;
+
+        if header.major_type != expected as u8 {
+            return Err(QPXError::DecodingFailed(format!(
+                "Expected {:?} (0x{:02x}), got 0x{:02x}",
+                expected, expected as u8, header.major_type
+            )));
+        }
+
+        Self::verify_footer(bytes, &header)?;
+        Ok(header)
+    }
+
+    /// Read the EntanglementMap section referenced by the header
+    fn read_entanglement_section(bytes: &[u8], header: &QPXHeader) -> Result<Vec<QPXEntanglement>> {
+        if header.entanglement_count == 0 {
+            return Ok(Vec::new());
+        }
+        let mut section = Self::section(bytes, header, header.entanglement_offset, "entanglement_offset")?;
+        entanglement::read_entanglements(&mut section, header.entanglement_count)
+    }
+
+    /// Read the BranchTable section referenced by the header
+    fn read_branch_section(bytes: &[u8], header: &QPXHeader) -> Result<Vec<QPXBranch>> {
+        if header.branch_count == 0 {
+            return Ok(Vec::new());
+        }
+        let mut section = Self::section(bytes, header, header.branch_table_offset, "branch_table_offset")?;
+        entanglement::read_branches(&mut section, header.branch_count)
+    }
+
+    /// Read the Timeline section referenced by the header (empty if absent)
+    fn read_timeline_section(bytes: &[u8], header: &QPXHeader) -> Result<Vec<QPXTimelineEntry>> {
+        if header.timeline_offset == 0 {
+            return Ok(Vec::new());
+        }
+        let mut section = Self::section(bytes, header, header.timeline_offset, "timeline_offset")?;
+        let available = section.len();
+        timeline::read_timeline(&mut section, available)
+    }
+
+    /// Read the Context section referenced by the header (`None` if absent)
+    fn read_context_section(bytes: &[u8], header: &QPXHeader) -> Result<Option<ContextTensor7D>> {
+        if header.context_offset == 0 {
+            return Ok(None);
+        }
+        let mut section = Self::section(bytes, header, header.context_offset, "context_offset")?;
+        timeline::read_context(&mut section).map(Some)
+    }
+
+    /// Bytes from `offset` up to the footer
+    fn section<'a>(bytes: &'a [u8], header: &QPXHeader, offset: u32, what: &str) -> Result<&'a [u8]> {
+        let start = offset as usize;
+        let end = header.footer_offset as usize;
+        if start < QPXHeader::SIZE || start > end || end > bytes.len() {
+            return Err(QPXError::InvalidOffset(format!(
+                "{} ({}) outside record (footer at {})",
+                what, start, end
+            )));
+        }
+        Ok(&bytes[start..end])
+    }
+
+    /// Decrypt and decode an encrypted QuantumCore record
+    ///
+    /// # Errors
+    /// * `AuthenticationFailed` - Wrong key or tampered record
+    pub fn decode_quantum_core_encrypted(bytes: &[u8], key: &QPXKey) -> Result<QPXQuantumCore> {
+        Self::decode_quantum_core(&crypto::decrypt_record(bytes, key)?)
+    }
+
+    /// Decrypt and decode an encrypted PixelBlock record
+    ///
+    /// # Errors
+    /// * `AuthenticationFailed` - Wrong key or tampered record
+    pub fn decode_pixel_block_encrypted(bytes: &[u8], key: &QPXKey) -> Result<QPXPixelBlock> {
+        Self::decode_pixel_block(&crypto::decrypt_record(bytes, key)?)
+    }
+
+    /// Decode a standalone PixelBlock (0x40) record
+    ///
+    /// `bytes` must contain exactly one record (header through footer).
+    pub fn decode_pixel_block(bytes: &[u8]) -> Result<QPXPixelBlock> {
+        if bytes.len() < QPXHeader::SIZE + QPX_FOOTER_SIZE {
+            return Err(QPXError::DecodingFailed(format!(
+                "Record too small: {} bytes (minimum {})",
+                bytes.len(),
+                QPXHeader::SIZE + QPX_FOOTER_SIZE
+            )));
+        }
+
+        let header = QPXHeader::from_bytes(&bytes[..QPXHeader::SIZE])?;
+        header.validate()?;
+        Self::ensure_plaintext(&header)?;
+
+        if header.major_type != QPXMajorType::PixelBlock as u8 {
+            return Err(QPXError::DecodingFailed(
+                format!("Expected PixelBlock (0x40), got 0x{:02x}", header.major_type)
+            ));
+        }
+
+        Self::verify_footer(bytes, &header)?;
+
+        let pixels = Self::read_pixel_block(
+            bytes,
+            header.pixel_block_offset as usize,
+            header.pixel_count as usize,
+        )?;
+
+        let meta_end = header.footer_offset as usize;
+        let meta_start = header.quantum_meta_offset as usize;
+        if meta_start > meta_end {
+            return Err(QPXError::InvalidOffset(format!(
+                "quantum_meta_offset ({}) after footer ({})",
+                meta_start, meta_end
+            )));
+        }
+        let mut cursor = Cursor::new(&bytes[meta_start..meta_end]);
+
+        let id = Self::read_string(&mut cursor)?;
+        let secs = cursor.read_i64::<LittleEndian>()?;
+        let nanos = cursor.read_u32::<LittleEndian>()?;
+        let timestamp = DateTime::from_timestamp(secs, nanos).ok_or_else(|| {
+            QPXError::DecodingFailed(format!("Invalid timestamp: {}s {}ns", secs, nanos))
+        })?;
+
+        let meta_len = cursor.read_u32::<LittleEndian>()? as usize;
+        let mut meta = vec![0u8; meta_len];
+        cursor.read_exact(&mut meta)?;
+
+        Ok(QPXPixelBlock {
+            id,
+            timestamp,
+            pixels,
+            flags: header.flags,
+            meta,
+        })
+    }
+
+    /// Reject encrypted records (see `decode_quantum_core_encrypted`)
+    fn ensure_plaintext(header: &QPXHeader) -> Result<()> {
+        if header.is_encrypted() {
+            return Err(QPXError::EncryptedRecord);
+        }
+        Ok(())
+    }
+
+    /// Verify footer position, SHA256 checksum and end magic
+    pub(crate) fn verify_footer(bytes: &[u8], header: &QPXHeader) -> Result<()> {
         let footer_offset = header.footer_offset as usize;
-        if footer_offset + 64 != bytes.len() {
+        if footer_offset + QPX_FOOTER_SIZE != bytes.len() {
             return Err(QPXError::DecodingFailed(
                 format!("Footer offset mismatch: expected {}, got {}", 
-                    bytes.len() - 64, footer_offset)
+                    bytes.len().saturating_sub(QPX_FOOTER_SIZE), footer_offset)
             ));
         }
 
@@ -71,7 +308,6 @@ impl QPXDecoder {
             });
         }
 
-        // 3. Verify footer magic
         let magic_end = &bytes[footer_offset + 32..footer_offset + 36];
         if magic_end != QPX_MAGIC_END {
             return Err(QPXError::DecodingFailed(
@@ -80,43 +316,24 @@ impl QPXDecoder {
             ));
         }
 
-        // 4. Read PixelBlock
-        let pixel_block_offset = header.pixel_block_offset as usize;
-        let pixel_count = header.pixel_count as usize;
-        let pixels = Self::read_pixel_block(bytes, pixel_block_offset, pixel_count)?;
-
-        // 5. Read QuantumMeta
-        let quantum_meta_offset = header.quantum_meta_offset as usize;
-        let (fbcu_core, metadata, checksum, timestamp) = 
-            Self::read_quantum_meta(bytes, quantum_meta_offset)?;
-
-        // 6. Determine alpha from first pixel (convention: all core pixels same alpha)
-        let alpha = pixels.first()
-            .map(|p| p.alpha)
-            .unwrap_or(255);
-
-        Ok(QPXQuantumCore {
-            fbcu_core,
-            pixels,
-            alpha,
-            metadata,
-            timestamp,
-            checksum,
-        })
+        Ok(())
     }
 
     /// Read PixelBlock from bytes
     fn read_pixel_block(bytes: &[u8], offset: usize, count: usize) -> Result<Vec<Pixel>> {
+        // `count` comes from the header: check it before reserving memory
+        let available = bytes.len().saturating_sub(offset) / Pixel::SIZE;
+        if count > available {
+            return Err(QPXError::DecodingFailed(format!(
+                "Truncated PixelBlock: {} pixels claimed, {} available at offset {}",
+                count, available, offset
+            )));
+        }
+
         let mut pixels = Vec::with_capacity(count);
         let mut cursor = offset;
 
-        for i in 0..count {
-            if cursor + Pixel::SIZE > bytes.len() {
-                return Err(QPXError::DecodingFailed(
-                    format!("Truncated PixelBlock: pixel {} at offset {}", i, cursor)
-                ));
-            }
-
+        for _ in 0..count {
             let pixel = Pixel::from_bytes(&bytes[cursor..cursor + Pixel::SIZE])?;
             pixels.push(pixel);
             cursor += Pixel::SIZE;
@@ -126,83 +343,127 @@ impl QPXDecoder {
     }
 
     /// Read QuantumMeta block from bytes
+    ///
+    /// Since v1.6 the section starts with its length; fields after the ones
+    /// this decoder knows about are skipped.
     fn read_quantum_meta(
         bytes: &[u8], 
+        header: &QPXHeader,
         offset: usize
     ) -> Result<(FBCUCore, TemplateMetadata, [u8; 32], DateTime<Utc>)> {
-        let mut cursor = Cursor::new(&bytes[offset..]);
+        let end = header.footer_offset as usize;
+        if offset > end || end > bytes.len() {
+            return Err(QPXError::InvalidOffset(format!(
+                "quantum_meta_offset ({}) beyond footer ({})",
+                offset,
+                end
+            )));
+        }
+
+        let mut section = &bytes[offset..end];
+        let version = QPXFormatVersion::from_u16(header.version)?;
+        if version.has_framed_quantum_meta() {
+            let len = section.read_u32::<LittleEndian>()? as usize;
+            section = section.get(..len).ok_or_else(|| {
+                QPXError::DecodingFailed(format!("QuantumMeta length {} exceeds record", len))
+            })?;
+        }
+        let mut cursor = Cursor::new(section);
+        let fields = Self::read_quantum_meta_fields(&mut cursor, true, version)?;
+
+        // Construct FBCUCore (content-addressed: the ID is the original's hash)
+        let original_hash = hex::encode(fields.checksum);
+        let fbcu_core = FBCUCore {
+            id: original_hash.clone(),
+            compression_type: fields.compression_type,
+            compressed_data: fields.compressed_data.unwrap_or_default(),
+            original_size: fields.original_size,
+            compression_ratio: fields.compression_ratio,
+            metadata: FBCUMetadata {
+                compressed_at: chrono::Utc::now().to_rfc3339(),
+                compression_time_ms: 0,
+                original_hash,
+                wavelet_level: None,
+                fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
+            },
+        };
+
+        Ok((fbcu_core, fields.metadata, fields.checksum, fields.timestamp))
+    }
 
+    /// Parse the QuantumMeta section from the reader's current position
+    ///
+    /// With `with_data = false` the FBCU blob is skipped with a seek instead
+    /// of being read, so summaries never touch the compressed payload.
+    /// `version` selects the fields present in the record.
+    pub(crate) fn read_quantum_meta_fields<R: Read + Seek>(
+        reader: &mut R,
+        with_data: bool,
+        version: QPXFormatVersion,
+    ) -> Result<QuantumMetaFields> {
         // 1. Read compressed_data
-        let compressed_data_len = cursor.read_u32::<LittleEndian>()? as usize;
-        let mut compressed_data = vec![0u8; compressed_data_len];
-        cursor.read_exact(&mut compressed_data)?;
+        let compressed_data_len = reader.read_u32::<LittleEndian>()? as usize;
+        let compressed_data = if with_data {
+            let mut compressed_data = vec![0u8; compressed_data_len];
+            reader.read_exact(&mut compressed_data)?;
+            Some(compressed_data)
+        } else {
+            reader.seek(SeekFrom::Current(compressed_data_len as i64))?;
+            None
+        };
 
         // 2. Read FBCU metadata
-        let original_size = cursor.read_u64::<LittleEndian>()? as usize;
-        let compressed_size = cursor.read_u64::<LittleEndian>()? as usize;
-        let compression_ratio = cursor.read_f32::<LittleEndian>()? as f64;
+        let original_size = reader.read_u64::<LittleEndian>()? as usize;
+        let compressed_size = reader.read_u64::<LittleEndian>()? as usize;
+        let compression_ratio = reader.read_f32::<LittleEndian>()? as f64;
         
         // 2b. Read compression type
-        let comp_type_byte = cursor.read_u8()?;
-        let compression_type = match comp_type_byte {
-            0 => CompressionType::None,
-            1 => CompressionType::Wavelet,
-            2 => CompressionType::Fractal,
-            3 => CompressionType::Hybrid,
-            4 => CompressionType::QuantumVisual,
-            5 => CompressionType::Gzip,
-            _ => CompressionType::None, // Fallback
-        };
+        let comp_type_byte = reader.read_u8()?;
+        let compression_type = CompressionType::from_tag(comp_type_byte)
+            .unwrap_or(CompressionType::None); // Fallback
 
         // 3. Read template metadata
-        let concept_name = Self::read_string(&mut cursor)?;
-        let category = Self::read_string(&mut cursor)?;
+        let concept_name = Self::read_string(reader)?;
+        let category = Self::read_string(reader)?;
         
-        let tags_count = cursor.read_u8()?;
+        let tags_count = reader.read_u8()?;
         let mut tags = Vec::with_capacity(tags_count as usize);
         for _ in 0..tags_count {
-            tags.push(Self::read_string(&mut cursor)?);
+            tags.push(Self::read_string(reader)?);
         }
         
         // 3b. Read original filename (Option<String>)
-        let original_filename = if cursor.read_u8()? == 1 {
-            Some(Self::read_string(&mut cursor)?)
+        let original_filename = if reader.read_u8()? == 1 {
+            Some(Self::read_string(reader)?)
         } else {
             None
         };
         
         // 3c. Read file extension (Option<String>)
-        let file_extension = if cursor.read_u8()? == 1 {
-            Some(Self::read_string(&mut cursor)?)
+        let file_extension = if reader.read_u8()? == 1 {
+            Some(Self::read_string(reader)?)
         } else {
             None
         };
 
         // 4. Read timestamps (NOTE: encoder writes created_at twice as created_at and updated_at)
-        let created_at = cursor.read_i64::<LittleEndian>()?;
-        let _updated_at = cursor.read_i64::<LittleEndian>()?; // Not used currently
+        let created_at = reader.read_i64::<LittleEndian>()?;
+        let _updated_at = reader.read_i64::<LittleEndian>()?; // Not used currently
 
         // 5. Read checksum
         let mut checksum = [0u8; 32];
-        cursor.read_exact(&mut checksum)?;
+        reader.read_exact(&mut checksum)?;
 
-        // 6. Construct FBCUCore
-        let fbcu_core = FBCUCore {
-            id: format!("decoded_{}", chrono::Utc::now().timestamp_millis()),
-            compression_type,
-            compressed_data,
-            original_size,
-            compression_ratio,
-            metadata: FBCUMetadata {
-                compressed_at: chrono::Utc::now().to_rfc3339(),
-                compression_time_ms: 0,
-                original_hash: hex::encode(&checksum),
-                wavelet_level: None,
-                fractal_level: None,
-            },
+        // 5b. Sub-second nanos (v1.7+)
+        let subsec_nanos = if version.has_subsec_nanos() {
+            reader.read_u32::<LittleEndian>()?
+        } else {
+            0
         };
 
-        // 7. Construct TemplateMetadata
+        // 6. Construct TemplateMetadata
         let metadata = TemplateMetadata {
             concept_name,
             category,
@@ -212,18 +473,27 @@ impl QPXDecoder {
             file_extension,
         };
 
-        // 8. Convert timestamp to DateTime
-        let timestamp = DateTime::from_timestamp(created_at, 0)
+        // 7. Convert timestamp to DateTime
+        let timestamp = DateTime::from_timestamp(created_at, subsec_nanos)
             .unwrap_or_else(|| chrono::Utc::now());
 
-        Ok((fbcu_core, metadata, checksum, timestamp))
+        Ok(QuantumMetaFields {
+            compressed_data,
+            compressed_size,
+            original_size,
+            compression_ratio,
+            compression_type,
+            metadata,
+            timestamp,
+            checksum,
+        })
     }
 
     /// Read a length-prefixed string (u16 length + bytes)
-    fn read_string(cursor: &mut Cursor<&[u8]>) -> Result<String> {
-        let len = cursor.read_u16::<LittleEndian>()? as usize;
+    fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+        let len = reader.read_u16::<LittleEndian>()? as usize;
         let mut bytes = vec![0u8; len];
-        cursor.read_exact(&mut bytes)?;
+        reader.read_exact(&mut bytes)?;
         
         String::from_utf8(bytes).map_err(|e| 
             QPXError::DecodingFailed(format!("Invalid UTF-8 string: {}", e))
@@ -231,6 +501,19 @@ impl QPXDecoder {
     }
 }
 
+/// Parsed QuantumMeta section (shared by `QPXDecoder` and `QPXReader`)
+pub(crate) struct QuantumMetaFields {
+    /// FBCU blob; `None` when it was skipped
+    pub compressed_data: Option<Vec<u8>>,
+    pub compressed_size: usize,
+    pub original_size: usize,
+    pub compression_ratio: f64,
+    pub compression_type: CompressionType,
+    pub metadata: TemplateMetadata,
+    pub timestamp: DateTime<Utc>,
+    pub checksum: [u8; 32],
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,6 +532,8 @@ mod tests {
                 original_hash: "def456".into(),
                 wavelet_level: Some(5),
                 fractal_level: Some(3),
+                dictionary_id: None,
+                codec_selection: None,
             },
         };
 
@@ -263,9 +548,15 @@ mod tests {
                 category: "Testing".into(),
                 tags: vec!["test".into(), "decoder".into()],
                 original_path: "/tmp/test.md".into(),
+                original_filename: None,
+                file_extension: None,
             },
             timestamp: chrono::Utc::now(),
             checksum: [0xAB; 32],
+            entanglements: Vec::new(),
+            branches: Vec::new(),
+            timeline: Vec::new(),
+            context: None,
         }
     }
 
@@ -296,6 +587,9 @@ mod tests {
         // Compression ratio loses precision in f32 encoding, use approximate comparison
         assert!((decoded.fbcu_core.compression_ratio - original.fbcu_core.compression_ratio).abs() < 0.0001,
             "Compression ratio mismatch: {} vs {}", decoded.fbcu_core.compression_ratio, original.fbcu_core.compression_ratio);
+        // The ID is derived from content, so decoding twice gives the same one
+        assert_eq!(decoded.fbcu_core.id, decoded.fbcu_core.metadata.original_hash);
+        assert_eq!(QPXDecoder::decode_quantum_core(&encoded).unwrap().fbcu_core.id, decoded.fbcu_core.id);
 
         // Verify pixels
         assert_eq!(decoded.pixels.len(), original.pixels.len());
@@ -306,6 +600,7 @@ mod tests {
         assert_eq!(decoded.metadata.category, original.metadata.category);
         assert_eq!(decoded.metadata.tags, original.metadata.tags);
         assert_eq!(decoded.alpha, original.alpha);
+        assert_eq!(decoded.timestamp, original.timestamp);
     }
 
     #[test]
@@ -368,6 +663,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_pixel_block_count_is_checked_before_allocating() {
+        let bytes = vec![0u8; QPXHeader::SIZE + Pixel::SIZE * 2];
+        assert!(matches!(
+            QPXDecoder::read_pixel_block(&bytes, QPXHeader::SIZE, u32::MAX as usize),
+            Err(QPXError::DecodingFailed(_))
+        ));
+        assert_eq!(QPXDecoder::read_pixel_block(&bytes, QPXHeader::SIZE, 2).unwrap().len(), 2);
+    }
+
     #[test]
     fn test_wrong_major_type() {
         let core = create_test_core();
@@ -380,4 +685,136 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), QPXError::DecodingFailed(_)));
     }
+
+    #[test]
+    fn test_pixel_block_roundtrip() {
+        let block = QPXPixelBlock {
+            id: "pixel-001".into(),
+            timestamp: DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap(),
+            pixels: vec![Pixel::with_full_metadata(10, 20, 30, 200, 0, 7, 0); 4],
+            flags: 0,
+            meta: vec![9, 8, 7],
+        };
+
+        let encoded = QPXEncoder::encode_pixel_block(&block).unwrap();
+        assert_eq!(encoded[7], QPXMajorType::PixelBlock as u8);
+
+        let decoded = QPXDecoder::decode_pixel_block(&encoded).unwrap();
+        assert_eq!(decoded, block);
+
+        // A PixelBlock is not a QuantumCore
+        assert!(QPXDecoder::decode_quantum_core(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_empty_pixel_block_tombstone() {
+        let block = QPXPixelBlock {
+            id: "pixel-001".into(),
+            timestamp: chrono::Utc::now(),
+            pixels: Vec::new(),
+            flags: crate::qpx::QPX_FLAG_TOMBSTONE,
+            meta: Vec::new(),
+        };
+
+        let encoded = QPXEncoder::encode_pixel_block(&block).unwrap();
+        let decoded = QPXDecoder::decode_pixel_block(&encoded).unwrap();
+        assert_eq!(decoded, block);
+    }
+
+    #[test]
+    fn test_quantum_core_entanglements_and_branches_roundtrip() {
+        use crate::qpx::entanglement::EntanglementKind;
+
+        let mut core = create_test_core();
+        core.entanglements = vec![
+            QPXEntanglement {
+                target_id: "telescope_42".into(),
+                kind: EntanglementKind::Telescope,
+                weight: 0.5,
+                label: "biographical origin".into(),
+                created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            },
+            QPXEntanglement::new("template_7", EntanglementKind::Supersedes),
+        ];
+        core.branches = vec![QPXBranch {
+            branch_id: "main".into(),
+            name: "Main".into(),
+            parent_id: None,
+            head_id: "template_7".into(),
+            created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }];
+
+        let encoded = QPXEncoder::encode_quantum_core(&core).unwrap();
+        let header = QPXHeader::from_bytes(&encoded[..QPXHeader::SIZE]).unwrap();
+        assert_eq!(header.entanglement_count, 2);
+        assert_eq!(header.branch_count, 1);
+        assert!(header.entanglement_offset > header.quantum_meta_offset);
+        assert!(header.branch_table_offset > header.entanglement_offset);
+
+        let decoded = QPXDecoder::decode_quantum_core(&encoded).unwrap();
+        assert_eq!(decoded.entanglements, core.entanglements);
+        assert_eq!(decoded.branches, core.branches);
+        assert_eq!(decoded.metadata.concept_name, core.metadata.concept_name);
+    }
+
+    #[test]
+    fn test_standalone_entanglement_and_branch_records() {
+        use crate::qpx::entanglement::EntanglementKind;
+
+        let map = QPXEntanglementMap {
+            source_id: "src/fbcu/mod.rs".into(),
+            entanglements: vec![QPXEntanglement::new("docs/fbcu.md", EntanglementKind::Implements)],
+        };
+        let encoded = QPXEncoder::encode_entanglement_map(&map).unwrap();
+        assert_eq!(encoded[7], QPXMajorType::Entanglement as u8);
+        assert_eq!(QPXDecoder::decode_entanglement_map(&encoded).unwrap(), map);
+        assert!(QPXDecoder::decode_branch_table(&encoded).is_err());
+
+        let empty = QPXEntanglementMap { source_id: "lonely".into(), entanglements: Vec::new() };
+        let encoded = QPXEncoder::encode_entanglement_map(&empty).unwrap();
+        assert_eq!(QPXDecoder::decode_entanglement_map(&encoded).unwrap(), empty);
+
+        let branches = vec![QPXBranch {
+            branch_id: "exp".into(),
+            name: "Experiment".into(),
+            parent_id: Some("main".into()),
+            head_id: "core_1".into(),
+            created_at: DateTime::from_timestamp(1_700_000_000, 9).unwrap(),
+        }];
+        let encoded = QPXEncoder::encode_branch_table(&branches).unwrap();
+        assert_eq!(encoded[7], QPXMajorType::Branch as u8);
+        assert_eq!(QPXDecoder::decode_branch_table(&encoded).unwrap(), branches);
+    }
+
+    #[test]
+    fn test_quantum_core_timeline_and_context_roundtrip() {
+        use crate::qpx::timeline::TimelineEventKind;
+
+        let mut core = create_test_core();
+        let created = DateTime::from_timestamp(1_700_000_000, 500).unwrap();
+        QPXTimelineEntry::push(&mut core.timeline, TimelineEventKind::Created, created);
+        QPXTimelineEntry::push(&mut core.timeline, TimelineEventKind::Updated, created + chrono::Duration::hours(1));
+        core.context = ContextTensor7D::from_vec(&[0.8, 0.6, 0.9, 0.7, 0.5, 0.4, 0.3]);
+
+        let encoded = QPXEncoder::encode_quantum_core(&core).unwrap();
+        let header = QPXHeader::from_bytes(&encoded[..QPXHeader::SIZE]).unwrap();
+        assert!(header.timeline_offset > header.quantum_meta_offset);
+        assert!(header.context_offset > header.timeline_offset);
+
+        let decoded = QPXDecoder::decode_quantum_core(&encoded).unwrap();
+        assert_eq!(decoded.timeline, core.timeline);
+        assert_eq!(decoded.timeline[1].version, 2);
+        assert_eq!(
+            decoded.context.unwrap().to_vec(),
+            core.context.as_ref().unwrap().to_vec()
+        );
+
+        // Sin secciones opcionales los offsets quedan en 0
+        let plain = QPXEncoder::encode_quantum_core(&create_test_core()).unwrap();
+        let header = QPXHeader::from_bytes(&plain[..QPXHeader::SIZE]).unwrap();
+        assert_eq!((header.timeline_offset, header.context_offset), (0, 0));
+        let decoded = QPXDecoder::decode_quantum_core(&plain).unwrap();
+        assert!(decoded.timeline.is_empty());
+        assert!(decoded.context.is_none());
+    }
 }
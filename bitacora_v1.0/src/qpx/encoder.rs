@@ -2,7 +2,10 @@
 //!
 //! Supports encoding:
 //! - FBCUCore → QuantumCore (0x60) .qpx files
-//! - Future: Voxels, Branches, Entanglements
+//! - Pixel records → PixelBlock (0x40) records
+//! - Entanglement maps → Entanglement (0x80) records
+//! - Branch tables → Branch (0xA0) records
+//! - Future: Voxels
 
 use std::io::{Cursor, Write};
 use byteorder::{LittleEndian, WriteBytesExt};
@@ -11,6 +14,12 @@ use chrono::{DateTime, Utc};
 
 use crate::fbcu::{FBCUCore, CompressionType};
 use crate::qpx::{QPXHeader, QPXMajorType, Pixel, QPX_MAGIC_END, error::*};
+use crate::qpx::entanglement::{
+    self, QPXBranch, QPXEntanglement, QPXEntanglementMap,
+};
+use crate::qpx::timeline::{self, QPXTimelineEntry};
+use crate::qpx::crypto::{self, QPXKey};
+use crate::telescopedb::ContextTensor7D;
 
 /// Template metadata for QPX storage
 #[derive(Debug, Clone)]
@@ -44,6 +53,39 @@ pub struct QPXQuantumCore {
     
     /// Content checksum (SHA256 of original markdown)
     pub checksum: [u8; 32],
+
+    /// Links to other cores (EntanglementMap section)
+    pub entanglements: Vec<QPXEntanglement>,
+
+    /// QuantumDao branches (BranchTable section)
+    pub branches: Vec<QPXBranch>,
+
+    /// Versioned history (Timeline section, omitted when empty)
+    pub timeline: Vec<QPXTimelineEntry>,
+
+    /// 7D context (Context section, omitted when `None`)
+    pub context: Option<ContextTensor7D>,
+}
+
+/// Standalone PixelBlock record (append-only pixel stores)
+///
+/// `meta` is opaque to QPX: the owner of the record defines its layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QPXPixelBlock {
+    /// Record ID
+    pub id: String,
+
+    /// Record timestamp (nanosecond precision)
+    pub timestamp: DateTime<Utc>,
+
+    /// Pixels (may be empty, e.g. for tombstones)
+    pub pixels: Vec<Pixel>,
+
+    /// Header flags (`QPX_FLAG_*`)
+    pub flags: u8,
+
+    /// Owner-defined metadata
+    pub meta: Vec<u8>,
 }
 
 pub struct QPXEncoder;
@@ -54,7 +96,11 @@ impl QPXEncoder {
     /// Structure:
     /// - Header (48 bytes)
     /// - PixelBlock (pixels.len() * 8 bytes)
-    /// - QuantumMeta (variable)
+    /// - QuantumMeta (u32 length + variable)
+    /// - EntanglementMap (optional, `entanglement_offset`)
+    /// - BranchTable (optional, `branch_table_offset`)
+    /// - Timeline (optional, `timeline_offset`)
+    /// - Context (optional, `context_offset`)
     /// - Footer (64 bytes)
     pub fn encode_quantum_core(core: &QPXQuantumCore) -> Result<Vec<u8>> {
         let mut buffer = Vec::new();
@@ -75,6 +121,8 @@ impl QPXEncoder {
         header.pixel_block_offset = pixel_block_offset;
         header.quantum_meta_offset = quantum_meta_offset;
         header.footer_offset = footer_offset;
+        header.entanglement_count = entanglement::section_count(core.entanglements.len(), "entanglements")?;
+        header.branch_count = entanglement::section_count(core.branches.len(), "branches")?;
         
         // 3. Write header
         buffer.extend_from_slice(&header.to_bytes()?);
@@ -84,27 +132,153 @@ impl QPXEncoder {
             buffer.extend_from_slice(&pixel.to_bytes()?);
         }
         
-        // 5. Write QuantumMeta
+        // 5. Write QuantumMeta, prefixed with its length (v1.6+)
         let meta_start = buffer.len();
+        buffer.extend_from_slice(&[0u8; 4]);
         Self::write_quantum_meta(&mut buffer, core)?;
         let actual_meta_size = (buffer.len() - meta_start) as u32;
+        buffer[meta_start..meta_start + 4].copy_from_slice(&(actual_meta_size - 4).to_le_bytes());
+        
+        // 6. Write optional sections: EntanglementMap, BranchTable, Timeline, Context
+        let mut updated_header = header;
+        if !core.entanglements.is_empty() {
+            updated_header.entanglement_offset = buffer.len() as u32;
+            entanglement::write_entanglements(&mut buffer, &core.entanglements)?;
+        }
+        if !core.branches.is_empty() {
+            updated_header.branch_table_offset = buffer.len() as u32;
+            entanglement::write_branches(&mut buffer, &core.branches)?;
+        }
+        if !core.timeline.is_empty() {
+            updated_header.timeline_offset = buffer.len() as u32;
+            timeline::write_timeline(&mut buffer, &core.timeline)?;
+        }
+        if let Some(context) = &core.context {
+            updated_header.context_offset = buffer.len() as u32;
+            timeline::write_context(&mut buffer, context)?;
+        }
         
-        // 6. Update footer_offset in header if meta size changed
-        let actual_footer_offset = quantum_meta_offset + actual_meta_size;
-        if actual_footer_offset != footer_offset {
-            // Rewrite header with correct footer offset
-            let mut updated_header = header;
-            updated_header.footer_offset = actual_footer_offset;
+        // 7. Update offsets in header if meta size changed or sections were added
+        let actual_footer_offset = buffer.len() as u32;
+        debug_assert!(actual_footer_offset >= quantum_meta_offset + actual_meta_size);
+        updated_header.footer_offset = actual_footer_offset;
+        if updated_header != header {
             let header_bytes = updated_header.to_bytes()?;
             buffer[0..48].copy_from_slice(&header_bytes);
         }
         
-        // 7. Write Footer
+        // 8. Write Footer
         Self::write_footer(&mut buffer)?;
         
         Ok(buffer)
     }
     
+    /// Encode a QuantumCore and encrypt its payload (see `crypto`)
+    pub fn encode_quantum_core_encrypted(core: &QPXQuantumCore, key: &QPXKey) -> Result<Vec<u8>> {
+        crypto::encrypt_record(&Self::encode_quantum_core(core)?, key)
+    }
+    
+    /// Encode a standalone PixelBlock (0x40) record
+    ///
+    /// Structure:
+    /// - Header (48 bytes, offsets relative to record start)
+    /// - PixelBlock (pixels.len() * 8 bytes)
+    /// - QuantumMeta: id (u16 len + bytes), timestamp (i64 secs + u32 nanos),
+    ///   meta (u32 len + bytes)
+    /// - Footer (64 bytes)
+    pub fn encode_pixel_block(block: &QPXPixelBlock) -> Result<Vec<u8>> {
+        if block.id.len() > u16::MAX as usize {
+            return Err(QPXError::EncodingFailed(format!(
+                "PixelBlock id too long: {} bytes",
+                block.id.len()
+            )));
+        }
+
+        let pixel_block_size = (block.pixels.len() * Pixel::SIZE) as u32;
+        let quantum_meta_offset = QPXHeader::SIZE as u32 + pixel_block_size;
+        let meta_size = (2 + block.id.len() + 8 + 4 + 4 + block.meta.len()) as u32;
+
+        let mut header = QPXHeader::new(QPXMajorType::PixelBlock);
+        header.flags = block.flags;
+        header.pixel_count = block.pixels.len() as u32;
+        header.pixel_block_offset = if block.pixels.is_empty() { 0 } else { QPXHeader::SIZE as u32 };
+        header.quantum_meta_offset = quantum_meta_offset;
+        header.footer_offset = quantum_meta_offset + meta_size;
+
+        let mut buffer = Vec::with_capacity(header.footer_offset as usize + 64);
+        buffer.extend_from_slice(&header.to_bytes()?);
+
+        for pixel in &block.pixels {
+            buffer.extend_from_slice(&pixel.to_bytes()?);
+        }
+
+        buffer.write_u16::<LittleEndian>(block.id.len() as u16)?;
+        buffer.write_all(block.id.as_bytes())?;
+        buffer.write_i64::<LittleEndian>(block.timestamp.timestamp())?;
+        buffer.write_u32::<LittleEndian>(block.timestamp.timestamp_subsec_nanos())?;
+        buffer.write_u32::<LittleEndian>(block.meta.len() as u32)?;
+        buffer.write_all(&block.meta)?;
+
+        Self::write_footer(&mut buffer)?;
+
+        Ok(buffer)
+    }
+    
+    /// Encode a PixelBlock and encrypt its payload (see `crypto`)
+    pub fn encode_pixel_block_encrypted(block: &QPXPixelBlock, key: &QPXKey) -> Result<Vec<u8>> {
+        crypto::encrypt_record(&Self::encode_pixel_block(block)?, key)
+    }
+
+    /// Encode a standalone Entanglement (0x80) record
+    ///
+    /// Structure:
+    /// - Header (48 bytes, `entanglement_count` links at `entanglement_offset`)
+    /// - EntanglementMap (variable)
+    /// - QuantumMeta: source_id (u16 len + bytes)
+    /// - Footer (64 bytes)
+    pub fn encode_entanglement_map(map: &QPXEntanglementMap) -> Result<Vec<u8>> {
+        let map_size = entanglement::entanglements_size(&map.entanglements) as u32;
+        let quantum_meta_offset = QPXHeader::SIZE as u32 + map_size;
+
+        let mut header = QPXHeader::new(QPXMajorType::Entanglement);
+        header.entanglement_count = entanglement::section_count(map.entanglements.len(), "entanglements")?;
+        header.entanglement_offset = if map.entanglements.is_empty() { 0 } else { QPXHeader::SIZE as u32 };
+        header.quantum_meta_offset = quantum_meta_offset;
+        header.footer_offset = quantum_meta_offset + 2 + map.source_id.len() as u32;
+
+        let mut buffer = Vec::with_capacity(header.footer_offset as usize + 64);
+        buffer.extend_from_slice(&header.to_bytes()?);
+        entanglement::write_entanglements(&mut buffer, &map.entanglements)?;
+        entanglement::write_string(&mut buffer, &map.source_id)?;
+
+        Self::write_footer(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Encode a standalone Branch (0xA0) record
+    ///
+    /// Structure:
+    /// - Header (48 bytes, `branch_count` branches at `branch_table_offset`)
+    /// - BranchTable (variable)
+    /// - Footer (64 bytes)
+    pub fn encode_branch_table(branches: &[QPXBranch]) -> Result<Vec<u8>> {
+        let table_size = entanglement::branches_size(branches) as u32;
+
+        let mut header = QPXHeader::new(QPXMajorType::Branch);
+        header.branch_count = entanglement::section_count(branches.len(), "branches")?;
+        header.branch_table_offset = if branches.is_empty() { 0 } else { QPXHeader::SIZE as u32 };
+        header.footer_offset = QPXHeader::SIZE as u32 + table_size;
+
+        let mut buffer = Vec::with_capacity(header.footer_offset as usize + 64);
+        buffer.extend_from_slice(&header.to_bytes()?);
+        entanglement::write_branches(&mut buffer, branches)?;
+
+        Self::write_footer(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
     fn estimate_meta_size(core: &QPXQuantumCore) -> u32 {
         // compressed_data_len (4) + data + original_size (8) + compression_ratio (8)
         let fbcu_size = 4 + core.fbcu_core.compressed_data.len() + 8 + 8;
@@ -114,8 +288,8 @@ impl QPXEncoder {
                            2 + core.metadata.category.len() +
                            1 + core.metadata.tags.iter().map(|t| 2 + t.len()).sum::<usize>();
         
-        // timestamps (8 + 8) + checksum (32)
-        let misc_size = 16 + 32;
+        // length prefix (4) + timestamps (8 + 8) + checksum (32) + subsec nanos (4)
+        let misc_size = 4 + 16 + 32 + 4;
         
         (fbcu_size + metadata_size + misc_size) as u32
     }
@@ -136,14 +310,7 @@ impl QPXEncoder {
         cursor.write_f32::<LittleEndian>(core.fbcu_core.compression_ratio as f32)?;
         
         // Compression type (1 byte)
-        let comp_type = match core.fbcu_core.compression_type {
-            CompressionType::None => 0u8,
-            CompressionType::Wavelet => 1u8,
-            CompressionType::Fractal => 2u8,
-            CompressionType::Hybrid => 3u8,
-            CompressionType::QuantumVisual => 4u8,
-            CompressionType::Gzip => 5u8,
-        };
+        let comp_type = core.fbcu_core.compression_type.tag();
         cursor.write_u8(comp_type)?;
         
         // Template metadata
@@ -184,10 +351,14 @@ impl QPXEncoder {
         // Checksum
         cursor.write_all(&core.checksum)?;
         
+        // Sub-second part of the timestamp (v1.7+; v1.6 decoders skip it
+        // thanks to the length prefix)
+        cursor.write_u32::<LittleEndian>(core.timestamp.timestamp_subsec_nanos())?;
+        
         Ok(())
     }
     
-    fn write_footer(buffer: &mut Vec<u8>) -> Result<()> {
+    pub(crate) fn write_footer(buffer: &mut Vec<u8>) -> Result<()> {
         // Calculate file checksum (SHA256 of everything written so far)
         let mut hasher = Sha256::new();
         hasher.update(&buffer);
@@ -232,6 +403,8 @@ mod tests {
                 original_hash: "abc123".into(),
                 wavelet_level: Some(6),
                 fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
             },
         };
         
@@ -247,9 +420,15 @@ mod tests {
                 category: "test".into(),
                 tags: vec!["tag1".into(), "tag2".into()],
                 original_path: "test.md".into(),
+                original_filename: None,
+                file_extension: None,
             },
             timestamp: Utc::now(),
             checksum: [0u8; 32],
+            entanglements: Vec::new(),
+            branches: Vec::new(),
+            timeline: Vec::new(),
+            context: None,
         }
     }
     
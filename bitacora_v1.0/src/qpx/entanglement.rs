@@ -0,0 +1,360 @@
+//! QPX Entanglements (0x80) and Branches (0xA0)
+//!
+//! - **EntanglementMap:** typed, weighted links from a core to other core IDs
+//!   (TelescopeDB entries, related documents, superseded templates...)
+//! - **BranchTable:** QuantumDao branches (named lines of evolution of a core)
+//!
+//! Both are written as sections inside a QuantumCore (located through
+//! `entanglement_offset`/`entanglement_count` and
+//! `branch_table_offset`/`branch_count`) or as standalone records with major
+//! type `Entanglement`/`Branch`.
+//!
+//! Entry layout (little-endian):
+//! - Entanglement: kind (u8), weight (f32), created_at (i64 secs + u32 nanos),
+//!   target_id (u16 len + bytes), label (u16 len + bytes)
+//! - Branch: branch_id, name (u16 len + bytes), parent (u8 flag + string),
+//!   head_id (u16 len + bytes), created_at (i64 secs + u32 nanos)
+
+use std::io::{Read, Write};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use chrono::{DateTime, Utc};
+
+use crate::qpx::error::{QPXError, Result};
+
+/// Kind of link between two cores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntanglementKind {
+    /// Generic reference to another core
+    References,
+
+    /// Link to a TelescopeDB biographical entry
+    Telescope,
+
+    /// Source imports the target (use statement)
+    Imports,
+
+    /// Source implements the design described by the target
+    Implements,
+
+    /// Source (session/ADR) takes a decision affecting the target
+    Decides,
+
+    /// Source tests the target
+    Tests,
+
+    /// Source is an updated version of the target
+    Supersedes,
+
+    /// Source needs the target for context
+    DependsOn,
+
+    /// Kind written by a newer encoder (kept as-is on round-trip)
+    ///
+    /// Values up to `LAST_KNOWN` belong to the named kinds and are rejected
+    /// by the encoder; build it with `EntanglementKind::other`.
+    Other(u8),
+}
+
+impl EntanglementKind {
+    /// Highest wire value assigned to a named kind
+    pub const LAST_KNOWN: u8 = 7;
+
+    /// `Other(value)` for an unassigned wire value, `None` if it names a known kind
+    pub fn other(value: u8) -> Option<Self> {
+        (value > Self::LAST_KNOWN).then_some(Self::Other(value))
+    }
+
+    /// Wire value
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::References => 0,
+            Self::Telescope => 1,
+            Self::Imports => 2,
+            Self::Implements => 3,
+            Self::Decides => 4,
+            Self::Tests => 5,
+            Self::Supersedes => 6,
+            Self::DependsOn => 7,
+            Self::Other(value) => value,
+        }
+    }
+
+    /// Parse wire value (unknown values → `Other`)
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::References,
+            1 => Self::Telescope,
+            2 => Self::Imports,
+            3 => Self::Implements,
+            4 => Self::Decides,
+            5 => Self::Tests,
+            6 => Self::Supersedes,
+            7 => Self::DependsOn,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Typed, weighted link to another core
+#[derive(Debug, Clone, PartialEq)]
+pub struct QPXEntanglement {
+    /// ID of the linked core (template ID, TelescopeDB ID, document path...)
+    pub target_id: String,
+
+    /// Link type
+    pub kind: EntanglementKind,
+
+    /// Link strength (0.0-1.0 by convention)
+    pub weight: f32,
+
+    /// Free-form description
+    pub label: String,
+
+    /// When the link was established
+    pub created_at: DateTime<Utc>,
+}
+
+impl QPXEntanglement {
+    /// Create a link with weight 1.0 and no label, timestamped now
+    pub fn new(target_id: impl Into<String>, kind: EntanglementKind) -> Self {
+        Self {
+            target_id: target_id.into(),
+            kind,
+            weight: 1.0,
+            label: String::new(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Standalone entanglement record: every outgoing link of one source
+#[derive(Debug, Clone, PartialEq)]
+pub struct QPXEntanglementMap {
+    /// ID of the source core
+    pub source_id: String,
+
+    /// Outgoing links
+    pub entanglements: Vec<QPXEntanglement>,
+}
+
+/// QuantumDao branch of a core
+#[derive(Debug, Clone, PartialEq)]
+pub struct QPXBranch {
+    /// Branch ID
+    pub branch_id: String,
+
+    /// Human-readable name
+    pub name: String,
+
+    /// Branch this one was forked from (`None` for the root branch)
+    pub parent_id: Option<String>,
+
+    /// Core ID at the tip of the branch
+    pub head_id: String,
+
+    /// Fork timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+/// Encoded size of an entanglement section
+pub(crate) fn entanglements_size(entanglements: &[QPXEntanglement]) -> usize {
+    entanglements
+        .iter()
+        .map(|e| 1 + 4 + 12 + 2 + e.target_id.len() + 2 + e.label.len())
+        .sum()
+}
+
+/// Encoded size of a branch table section
+pub(crate) fn branches_size(branches: &[QPXBranch]) -> usize {
+    branches
+        .iter()
+        .map(|b| {
+            2 + b.branch_id.len()
+                + 2 + b.name.len()
+                + 1 + b.parent_id.as_ref().map_or(0, |p| 2 + p.len())
+                + 2 + b.head_id.len()
+                + 12
+        })
+        .sum()
+}
+
+/// Section entry count as stored in the header (u16)
+pub(crate) fn section_count(len: usize, what: &str) -> Result<u16> {
+    u16::try_from(len).map_err(|_| {
+        QPXError::EncodingFailed(format!("Too many {}: {} (max {})", what, len, u16::MAX))
+    })
+}
+
+pub(crate) fn write_entanglements<W: Write>(writer: &mut W, entanglements: &[QPXEntanglement]) -> Result<()> {
+    // `Other(1)` would come back as `Telescope`
+    for entanglement in entanglements {
+        if let EntanglementKind::Other(value) = entanglement.kind {
+            if value <= EntanglementKind::LAST_KNOWN {
+                return Err(QPXError::EncodingFailed(format!(
+                    "EntanglementKind::Other({}) is reserved for {:?}",
+                    value,
+                    EntanglementKind::from_u8(value)
+                )));
+            }
+        }
+    }
+
+    for entanglement in entanglements {
+        writer.write_u8(entanglement.kind.to_u8())?;
+        writer.write_f32::<LittleEndian>(entanglement.weight)?;
+        write_timestamp(writer, &entanglement.created_at)?;
+        write_string(writer, &entanglement.target_id)?;
+        write_string(writer, &entanglement.label)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_entanglements<R: Read>(reader: &mut R, count: u16) -> Result<Vec<QPXEntanglement>> {
+    let mut entanglements = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let kind = EntanglementKind::from_u8(reader.read_u8()?);
+        let weight = reader.read_f32::<LittleEndian>()?;
+        let created_at = read_timestamp(reader)?;
+        let target_id = read_string(reader)?;
+        let label = read_string(reader)?;
+        entanglements.push(QPXEntanglement { target_id, kind, weight, label, created_at });
+    }
+    Ok(entanglements)
+}
+
+pub(crate) fn write_branches<W: Write>(writer: &mut W, branches: &[QPXBranch]) -> Result<()> {
+    for branch in branches {
+        write_string(writer, &branch.branch_id)?;
+        write_string(writer, &branch.name)?;
+        match &branch.parent_id {
+            Some(parent_id) => {
+                writer.write_u8(1)?;
+                write_string(writer, parent_id)?;
+            }
+            None => writer.write_u8(0)?,
+        }
+        write_string(writer, &branch.head_id)?;
+        write_timestamp(writer, &branch.created_at)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_branches<R: Read>(reader: &mut R, count: u16) -> Result<Vec<QPXBranch>> {
+    let mut branches = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let branch_id = read_string(reader)?;
+        let name = read_string(reader)?;
+        let parent_id = match reader.read_u8()? {
+            0 => None,
+            _ => Some(read_string(reader)?),
+        };
+        let head_id = read_string(reader)?;
+        let created_at = read_timestamp(reader)?;
+        branches.push(QPXBranch { branch_id, name, parent_id, head_id, created_at });
+    }
+    Ok(branches)
+}
+
+pub(crate) fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    let len = u16::try_from(value.len()).map_err(|_| {
+        QPXError::EncodingFailed(format!("String too long: {} bytes", value.len()))
+    })?;
+    writer.write_u16::<LittleEndian>(len)?;
+    writer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let len = reader.read_u16::<LittleEndian>()? as usize;
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes)
+        .map_err(|e| QPXError::DecodingFailed(format!("Invalid UTF-8: {}", e)))
+}
+
+pub(crate) fn write_timestamp<W: Write>(writer: &mut W, timestamp: &DateTime<Utc>) -> Result<()> {
+    writer.write_i64::<LittleEndian>(timestamp.timestamp())?;
+    writer.write_u32::<LittleEndian>(timestamp.timestamp_subsec_nanos())?;
+    Ok(())
+}
+
+pub(crate) fn read_timestamp<R: Read>(reader: &mut R) -> Result<DateTime<Utc>> {
+    let secs = reader.read_i64::<LittleEndian>()?;
+    let nanos = reader.read_u32::<LittleEndian>()?;
+    DateTime::from_timestamp(secs, nanos).ok_or_else(|| {
+        QPXError::DecodingFailed(format!("Invalid timestamp: {}s {}ns", secs, nanos))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_wire_values_round_trip() {
+        for value in 0..=u8::MAX {
+            assert_eq!(EntanglementKind::from_u8(value).to_u8(), value);
+        }
+        assert_eq!(EntanglementKind::from_u8(1), EntanglementKind::Telescope);
+        assert_eq!(EntanglementKind::from_u8(42), EntanglementKind::Other(42));
+        assert_eq!(EntanglementKind::other(42), Some(EntanglementKind::Other(42)));
+        assert_eq!(EntanglementKind::other(1), None);
+    }
+
+    #[test]
+    fn test_reserved_other_kind_is_rejected() {
+        let reserved = [QPXEntanglement::new("tpl_1", EntanglementKind::Other(1))];
+        let mut bytes = Vec::new();
+        assert!(matches!(
+            write_entanglements(&mut bytes, &reserved),
+            Err(QPXError::EncodingFailed(_))
+        ));
+        assert!(bytes.is_empty());
+
+        let unknown = [QPXEntanglement::new("tpl_1", EntanglementKind::Other(42))];
+        write_entanglements(&mut bytes, &unknown).unwrap();
+        assert_eq!(read_entanglements(&mut bytes.as_slice(), 1).unwrap()[0].kind, EntanglementKind::Other(42));
+    }
+
+    #[test]
+    fn test_sections_round_trip_and_sizes() {
+        let entanglements = vec![
+            QPXEntanglement {
+                target_id: "telescope_1".into(),
+                kind: EntanglementKind::Telescope,
+                weight: 0.75,
+                label: "origin".into(),
+                created_at: DateTime::from_timestamp(1_700_000_000, 123).unwrap(),
+            },
+            QPXEntanglement::new("tpl_2", EntanglementKind::Supersedes),
+        ];
+        let branches = vec![
+            QPXBranch {
+                branch_id: "main".into(),
+                name: "Main".into(),
+                parent_id: None,
+                head_id: "tpl_2".into(),
+                created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            },
+            QPXBranch {
+                branch_id: "exp".into(),
+                name: "Experiment".into(),
+                parent_id: Some("main".into()),
+                head_id: "tpl_3".into(),
+                created_at: DateTime::from_timestamp(1_700_000_100, 5).unwrap(),
+            },
+        ];
+
+        let mut buffer = Vec::new();
+        write_entanglements(&mut buffer, &entanglements).unwrap();
+        assert_eq!(buffer.len(), entanglements_size(&entanglements));
+        let split = buffer.len();
+        write_branches(&mut buffer, &branches).unwrap();
+        assert_eq!(buffer.len() - split, branches_size(&branches));
+
+        let mut reader = &buffer[..];
+        assert_eq!(read_entanglements(&mut reader, 2).unwrap(), entanglements);
+        assert_eq!(read_branches(&mut reader, 2).unwrap(), branches);
+        assert!(reader.is_empty());
+    }
+}
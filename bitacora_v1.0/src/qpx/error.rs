@@ -29,6 +29,15 @@ pub enum QPXError {
     #[error("Invalid offset: {0}")]
     InvalidOffset(String),
     
+    #[error("QPX record is encrypted: a key is required to read it")]
+    EncryptedRecord,
+    
+    #[error("Invalid encryption key: {0}")]
+    InvalidKey(String),
+    
+    #[error("QPX decryption failed: wrong key or tampered record")]
+    AuthenticationFailed,
+    
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 }
@@ -4,7 +4,7 @@
 
 use std::io::{Cursor, Read, Write};
 use byteorder::{LittleEndian, WriteBytesExt, ReadBytesExt};
-use crate::qpx::{QPX_MAGIC, QPX_VERSION, error::*};
+use crate::qpx::{QPX_MAGIC, QPX_VERSION, QPXFormatVersion, error::*};
 
 /// QPX Header - 48 bytes fixed size
 #[repr(C)]
@@ -13,7 +13,7 @@ pub struct QPXHeader {
     /// Magic bytes "QPX\0" (4 bytes)
     pub magic: [u8; 4],
     
-    /// Version number (2 bytes) - 0x0015 for v1.5
+    /// Version number (2 bytes) - 0x0017 for v1.7 (see `QPXFormatVersion`)
     pub version: u16,
     
     /// Flags (1 byte) - Bit flags for compression, encryption, etc.
@@ -56,6 +56,16 @@ pub struct QPXHeader {
     pub reserved: u32,
 }
 
+/// Header flag: record marks the deletion of a previous record with the same id
+pub const QPX_FLAG_TOMBSTONE: u8 = 0b0000_0001;
+
+/// Header flag: payload (everything between header and footer) is encrypted
+/// with ChaCha20-Poly1305 (see `qpx::crypto`)
+pub const QPX_FLAG_ENCRYPTED: u8 = 0b0000_0010;
+
+/// Size of the footer (checksum + magic + file size + padding)
+pub const QPX_FOOTER_SIZE: usize = 64;
+
 /// QPX Major Types (block type identifier)
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -196,6 +206,11 @@ impl QPXHeader {
         })
     }
     
+    /// Whether the record payload is encrypted (`QPX_FLAG_ENCRYPTED`)
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & QPX_FLAG_ENCRYPTED != 0
+    }
+    
     /// Validate header integrity
     pub fn validate(&self) -> Result<()> {
         // Check magic
@@ -203,10 +218,8 @@ impl QPXHeader {
             return Err(QPXError::InvalidHeader("Invalid magic bytes".into()));
         }
         
-        // Check version
-        if self.version != QPX_VERSION {
-            return Err(QPXError::UnsupportedVersion(self.version, QPX_VERSION));
-        }
+        // Check version (any version in the decoder registry)
+        QPXFormatVersion::from_u16(self.version)?;
         
         // Validate major type
         match self.major_type {
@@ -270,7 +283,7 @@ mod tests {
         assert_eq!(&bytes[0..4], b"QPX\0");
         
         // Verify version (little-endian)
-        assert_eq!(bytes[4], 0x15);
+        assert_eq!(bytes[4], 0x17);
         assert_eq!(bytes[5], 0x00);
         
         // Verify major_type
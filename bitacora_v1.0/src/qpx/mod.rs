@@ -14,6 +14,7 @@
 //! - **Alpha multi-purpose:** 255=core, 128=helper, 50=deprecated, etc.
 //! - **Entanglements:** Relaciones cuánticas entre cores nativo
 //! - **Checksums:** SHA256 para integridad (header + footer)
+//! - **Cifrado en reposo:** ChaCha20-Poly1305 opcional (`crypto`, `QPX_FLAG_ENCRYPTED`)
 //! 
 //! ## Tipos QPX
 //! 
@@ -49,15 +50,25 @@ pub mod pixel;
 pub mod error;
 pub mod encoder;
 pub mod decoder;
+pub mod entanglement;
+pub mod timeline;
+pub mod version;
+pub mod crypto;
+pub mod reader;
 
-pub use header::{QPXHeader, QPXMajorType};
-pub use pixel::Pixel;
+pub use header::{QPXHeader, QPXMajorType, QPX_FLAG_ENCRYPTED, QPX_FLAG_TOMBSTONE, QPX_FOOTER_SIZE};
+pub use pixel::{embedding_to_pixels, pixels_to_embedding, Pixel};
 pub use error::{QPXError, Result};
-pub use encoder::{QPXEncoder, QPXQuantumCore, TemplateMetadata};
+pub use encoder::{QPXEncoder, QPXPixelBlock, QPXQuantumCore, TemplateMetadata};
 pub use decoder::QPXDecoder;
+pub use entanglement::{EntanglementKind, QPXBranch, QPXEntanglement, QPXEntanglementMap};
+pub use timeline::{QPXTimelineEntry, TimelineEventKind};
+pub use version::{upgrade_file, upgrade_record, QPXFormatVersion};
+pub use crypto::{decrypt_record, encrypt_record, is_encrypted, QPXKey};
+pub use reader::{QPXCoreSummary, QPXReader, ReadSeek};
 
-/// QPX Version 1.5
-pub const QPX_VERSION: u16 = 0x0015;
+/// QPX Version 1.7 (written by the encoder; see `version` for readable versions)
+pub const QPX_VERSION: u16 = 0x0017;
 
 /// Magic bytes "QPX\0"
 pub const QPX_MAGIC: [u8; 4] = [0x51, 0x50, 0x58, 0x00];
@@ -71,7 +82,7 @@ mod tests {
     
     #[test]
     fn test_constants() {
-        assert_eq!(QPX_VERSION, 0x0015);
+        assert_eq!(QPX_VERSION, 0x0017);
         assert_eq!(QPX_MAGIC, [0x51, 0x50, 0x58, 0x00]);
         assert_eq!(QPX_MAGIC_END, [0x51, 0x50, 0x58, 0xFF]);
     }
@@ -127,6 +127,43 @@ impl Default for Pixel {
     }
 }
 
+/// Channel value that encodes 0.0 (symmetric quantization: [-1, 1] → 1..=255)
+const EMBEDDING_ZERO: u8 = 128;
+
+/// Pack an embedding into RGB pixels (3 dimensions per pixel)
+/// 
+/// Values are scaled by the largest magnitude before quantizing, so the
+/// direction of the vector (what cosine similarity measures) survives even
+/// when components are tiny. Quantization is symmetric around
+/// `EMBEDDING_ZERO`: 0.0 round-trips exactly and `x` / `-x` decode to
+/// opposite values. The last pixel is zero-padded if the dimension is not a
+/// multiple of 3; `index` holds the pixel position (mod 256).
+pub fn embedding_to_pixels(embedding: &[f32], alpha: u8) -> Vec<Pixel> {
+    let max_abs = embedding.iter().fold(0.0f32, |acc, x| acc.max(x.abs()));
+    let scale = if max_abs > 0.0 { 1.0 / max_abs } else { 0.0 };
+    let quantize = |x: f32| (((x * scale).clamp(-1.0, 1.0) * 127.0).round() as i16 + EMBEDDING_ZERO as i16) as u8;
+    
+    embedding
+        .chunks(3)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let channel = |c: usize| chunk.get(c).map_or(quantize(0.0), |x| quantize(*x));
+            Pixel::with_full_metadata(channel(0), channel(1), channel(2), alpha, 0, 0, i as u8)
+        })
+        .collect()
+}
+
+/// Unpack RGB pixels back into an embedding (3 dimensions per pixel, in [-1, 1])
+/// 
+/// Inverse of `embedding_to_pixels` up to quantization and scale.
+pub fn pixels_to_embedding(pixels: &[Pixel]) -> Vec<f32> {
+    pixels
+        .iter()
+        .flat_map(|p| [p.r, p.g, p.b])
+        .map(|c| ((c as f32 - EMBEDDING_ZERO as f32) / 127.0).max(-1.0))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +226,36 @@ mod tests {
         assert_eq!(pixel.rgb(), (10, 20, 30));
         assert_eq!(pixel.rgba(), (10, 20, 30, 40));
     }
+    
+    #[test]
+    fn test_embedding_pixel_roundtrip_preserves_direction() {
+        let embedding: Vec<f32> = (0..384).map(|i| ((i * 37 % 101) as f32 - 50.0) / 1000.0).collect();
+        let pixels = embedding_to_pixels(&embedding, 128);
+        assert_eq!(pixels.len(), 128);
+        assert!(pixels.iter().all(|p| p.alpha == 128));
+        
+        let decoded = pixels_to_embedding(&pixels);
+        assert_eq!(decoded.len(), 384);
+        
+        let dot: f32 = embedding.iter().zip(&decoded).map(|(a, b)| a * b).sum();
+        let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let cosine = dot / (norm(&embedding) * norm(&decoded));
+        assert!(cosine > 0.999, "cosine = {}", cosine);
+    }
+    
+    #[test]
+    fn test_embedding_quantization_is_symmetric() {
+        let decoded = pixels_to_embedding(&embedding_to_pixels(&[1.0, -1.0, 0.0, 0.3, -0.3], 255));
+        
+        // Zero (including the padding channel) decodes exactly
+        assert_eq!(decoded.len(), 6);
+        assert_eq!(decoded[2], 0.0);
+        assert_eq!(decoded[5], 0.0);
+        assert_eq!(decoded[0], 1.0);
+        assert_eq!(decoded[1], -1.0);
+        assert_eq!(decoded[3], -decoded[4]);
+        
+        // An all-zero embedding stays the zero vector
+        assert!(pixels_to_embedding(&embedding_to_pixels(&[0.0; 6], 255)).iter().all(|x| *x == 0.0));
+    }
 }
@@ -0,0 +1,438 @@
+//! QPX Reader - Partial/streaming access to QPX records over `Read + Seek`
+//!
+//! `QPXDecoder` needs the whole record in memory and decodes everything.
+//! `QPXReader` parses only the 48-byte header up front and seeks to the
+//! section that is actually requested:
+//! - `read_summary` → QuantumMeta without the FBCU blob (concept listings)
+//! - `read_pixels` → PixelBlock only (embeddings)
+//! - `read_entanglements` / `read_branches` → links and QuantumDao branches
+//! - `read_timeline` / `read_context` → versioned history and 7D context
+//! - `read_quantum_core` → full record, footer verified (same as the decoder)
+//!
+//! Partial reads do not verify the footer checksum; call `verify_checksum`
+//! when integrity matters more than I/O.
+
+use std::io::{Read, Seek, SeekFrom};
+use byteorder::{LittleEndian, ReadBytesExt};
+use chrono::{DateTime, Utc};
+use sha2::{Sha256, Digest};
+
+use crate::fbcu::CompressionType;
+use crate::qpx::decoder::QPXDecoder;
+use crate::qpx::encoder::{QPXQuantumCore, TemplateMetadata};
+use crate::qpx::entanglement::{self, QPXBranch, QPXEntanglement};
+use crate::qpx::timeline::{self, QPXTimelineEntry};
+use crate::telescopedb::ContextTensor7D;
+use crate::qpx::error::{QPXError, Result};
+use crate::qpx::header::{QPXHeader, QPXMajorType, QPX_FOOTER_SIZE};
+use crate::qpx::pixel::Pixel;
+use crate::qpx::version::QPXFormatVersion;
+use crate::qpx::QPX_MAGIC_END;
+
+/// `Read + Seek` in one object-safe trait (for `Box<dyn ReadSeek>`)
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// QuantumCore contents except the FBCU blob and the pixels
+#[derive(Debug, Clone)]
+pub struct QPXCoreSummary {
+    /// Template metadata (concept name, category, tags, filename)
+    pub metadata: TemplateMetadata,
+
+    /// Creation timestamp
+    pub timestamp: DateTime<Utc>,
+
+    /// Content checksum (SHA256 of original markdown)
+    pub checksum: [u8; 32],
+
+    /// FBCU compression type
+    pub compression_type: CompressionType,
+
+    /// Original (uncompressed) size in bytes
+    pub original_size: usize,
+
+    /// Size of the skipped FBCU blob in bytes
+    pub compressed_size: usize,
+
+    /// Number of pixels in the PixelBlock
+    pub pixel_count: u32,
+}
+
+/// Lazy reader over a single QPX record
+///
+/// The record may start anywhere in the stream (e.g. inside a pack file):
+/// header offsets are resolved relative to the position at `new`.
+pub struct QPXReader<R> {
+    inner: R,
+    header: QPXHeader,
+    base: u64,
+}
+
+impl<R: Read + Seek> QPXReader<R> {
+    /// Parse and validate the header at the stream's current position
+    pub fn new(mut inner: R) -> Result<Self> {
+        let base = inner.stream_position()?;
+
+        let mut header_bytes = [0u8; QPXHeader::SIZE];
+        inner.read_exact(&mut header_bytes).map_err(|e| {
+            QPXError::InvalidHeader(format!("Header too short: {}", e))
+        })?;
+        let header = QPXHeader::from_bytes(&header_bytes)?;
+        header.validate()?;
+        if header.is_encrypted() {
+            return Err(QPXError::EncryptedRecord);
+        }
+
+        Ok(Self { inner, header, base })
+    }
+
+    /// Parsed header
+    pub fn header(&self) -> &QPXHeader {
+        &self.header
+    }
+
+    /// Total record length (header through footer)
+    pub fn record_len(&self) -> u64 {
+        self.header.footer_offset as u64 + QPX_FOOTER_SIZE as u64
+    }
+
+    /// Read only the PixelBlock
+    ///
+    /// `pixel_count` comes from an untrusted header, so the block size is
+    /// checked against both the footer offset and the stream length before
+    /// allocating.
+    pub fn read_pixels(&mut self) -> Result<Vec<Pixel>> {
+        let count = self.header.pixel_count as u64;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let offset = self.header.pixel_block_offset;
+        let stream_len = self.inner.seek(SeekFrom::End(0))?;
+        self.seek_to(offset, "pixel_block_offset")?;
+
+        let size = count * Pixel::SIZE as u64;
+        let before_footer = (self.header.footer_offset - offset) as u64;
+        let in_stream = stream_len.saturating_sub(self.base + offset as u64);
+        if size > before_footer.min(in_stream) {
+            return Err(QPXError::DecodingFailed(format!(
+                "PixelBlock claims {} pixels ({} bytes) but only {} bytes remain",
+                count,
+                size,
+                before_footer.min(in_stream)
+            )));
+        }
+
+        let mut bytes = vec![0u8; size as usize];
+        self.inner.read_exact(&mut bytes).map_err(|_| {
+            QPXError::DecodingFailed(format!("Truncated PixelBlock: expected {} pixels", count))
+        })?;
+
+        bytes.chunks_exact(Pixel::SIZE).map(Pixel::from_bytes).collect()
+    }
+
+    /// Read the QuantumMeta section, seeking past the FBCU blob
+    pub fn read_summary(&mut self) -> Result<QPXCoreSummary> {
+        self.expect_quantum_core()?;
+        self.seek_to(self.header.quantum_meta_offset, "quantum_meta_offset")?;
+
+        let version = QPXFormatVersion::from_u16(self.header.version)?;
+        if version.has_framed_quantum_meta() {
+            // Length prefix: not needed, fields are read sequentially
+            self.inner.seek(SeekFrom::Current(4))?;
+        }
+        let fields = QPXDecoder::read_quantum_meta_fields(&mut self.inner, false, version)?;
+
+        Ok(QPXCoreSummary {
+            metadata: fields.metadata,
+            timestamp: fields.timestamp,
+            checksum: fields.checksum,
+            compression_type: fields.compression_type,
+            original_size: fields.original_size,
+            compressed_size: fields.compressed_size,
+            pixel_count: self.header.pixel_count,
+        })
+    }
+
+    /// Read only the EntanglementMap section (empty if the record has none)
+    pub fn read_entanglements(&mut self) -> Result<Vec<QPXEntanglement>> {
+        if self.header.entanglement_count == 0 {
+            return Ok(Vec::new());
+        }
+        self.seek_to(self.header.entanglement_offset, "entanglement_offset")?;
+        entanglement::read_entanglements(&mut self.inner, self.header.entanglement_count)
+    }
+
+    /// Read only the BranchTable section (empty if the record has none)
+    pub fn read_branches(&mut self) -> Result<Vec<QPXBranch>> {
+        if self.header.branch_count == 0 {
+            return Ok(Vec::new());
+        }
+        self.seek_to(self.header.branch_table_offset, "branch_table_offset")?;
+        entanglement::read_branches(&mut self.inner, self.header.branch_count)
+    }
+
+    /// Read only the Timeline section (empty if the record has none)
+    pub fn read_timeline(&mut self) -> Result<Vec<QPXTimelineEntry>> {
+        if self.header.timeline_offset == 0 {
+            return Ok(Vec::new());
+        }
+        self.seek_to(self.header.timeline_offset, "timeline_offset")?;
+        let available = (self.header.footer_offset - self.header.timeline_offset) as usize;
+        timeline::read_timeline(&mut self.inner, available)
+    }
+
+    /// Read only the Context section (`None` if the record has none)
+    pub fn read_context(&mut self) -> Result<Option<ContextTensor7D>> {
+        if self.header.context_offset == 0 {
+            return Ok(None);
+        }
+        self.seek_to(self.header.context_offset, "context_offset")?;
+        timeline::read_context(&mut self.inner).map(Some)
+    }
+
+    /// Read and decode the full QuantumCore (footer checksum verified)
+    pub fn read_quantum_core(&mut self) -> Result<QPXQuantumCore> {
+        let bytes = self.read_record()?;
+        QPXDecoder::decode_quantum_core(&bytes)
+    }
+
+    /// Stream the record through SHA256 and compare with the footer
+    pub fn verify_checksum(&mut self) -> Result<()> {
+        self.seek_to(0, "record start")?;
+
+        let mut hasher = Sha256::new();
+        let mut remaining = self.header.footer_offset as u64;
+        let mut chunk = [0u8; 8192];
+        while remaining > 0 {
+            let n = remaining.min(chunk.len() as u64) as usize;
+            self.inner.read_exact(&mut chunk[..n])?;
+            hasher.update(&chunk[..n]);
+            remaining -= n as u64;
+        }
+        let computed = hasher.finalize();
+
+        let mut footer = [0u8; 36];
+        self.inner.read_exact(&mut footer)?;
+        if computed[..] != footer[..32] {
+            return Err(QPXError::ChecksumMismatch {
+                expected: hex::encode(&footer[..32]),
+                actual: hex::encode(computed),
+            });
+        }
+        if footer[32..36] != QPX_MAGIC_END {
+            return Err(QPXError::DecodingFailed(format!(
+                "Invalid footer magic: expected {:?}, got {:?}",
+                QPX_MAGIC_END,
+                &footer[32..36]
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Read the raw record bytes (header through footer)
+    ///
+    /// The record length comes from the untrusted `footer_offset`, so it is
+    /// checked against the stream length before allocating.
+    pub fn read_record(&mut self) -> Result<Vec<u8>> {
+        let len = self.record_len();
+        let in_stream = self.inner.seek(SeekFrom::End(0))?.saturating_sub(self.base);
+        if len > in_stream {
+            return Err(QPXError::DecodingFailed(format!(
+                "Record claims {} bytes but only {} bytes remain",
+                len, in_stream
+            )));
+        }
+
+        self.seek_to(0, "record start")?;
+        let mut bytes = vec![0u8; len as usize];
+        self.inner.read_exact(&mut bytes).map_err(|_| {
+            QPXError::DecodingFailed(format!("Truncated record: expected {} bytes", bytes.len()))
+        })?;
+        Ok(bytes)
+    }
+
+    /// Give back the underlying stream
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn expect_quantum_core(&self) -> Result<()> {
+        if self.header.major_type != QPXMajorType::QuantumCore as u8 {
+            return Err(QPXError::DecodingFailed(format!(
+                "Expected QuantumCore (0x60), got 0x{:02x}",
+                self.header.major_type
+            )));
+        }
+        Ok(())
+    }
+
+    fn seek_to(&mut self, offset: u32, what: &str) -> Result<()> {
+        if offset > self.header.footer_offset {
+            return Err(QPXError::InvalidOffset(format!(
+                "{} ({}) after footer ({})",
+                what, offset, self.header.footer_offset
+            )));
+        }
+        self.inner.seek(SeekFrom::Start(self.base + offset as u64))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::fbcu::{FBCUCore, FBCUMetadata};
+    use crate::qpx::encoder::QPXEncoder;
+    use crate::qpx::entanglement::EntanglementKind;
+
+    fn create_test_core() -> QPXQuantumCore {
+        QPXQuantumCore {
+            fbcu_core: FBCUCore {
+                id: "reader_test".into(),
+                compression_type: CompressionType::Gzip,
+                compressed_data: vec![0xEE; 4096],
+                original_size: 9000,
+                compression_ratio: 0.45,
+                metadata: FBCUMetadata {
+                    compressed_at: "2025-11-30T15:00:00Z".into(),
+                    compression_time_ms: 3,
+                    original_hash: "abc".into(),
+                    wavelet_level: None,
+                    fractal_level: None,
+                    dictionary_id: None,
+                    codec_selection: None,
+                },
+            },
+            pixels: (0..16).map(|i| Pixel::new(i, i * 2, i * 3, 128)).collect(),
+            alpha: 128,
+            metadata: TemplateMetadata {
+                concept_name: "Streaming".into(),
+                category: "Technical".into(),
+                tags: vec!["qpx".into()],
+                original_path: String::new(),
+                original_filename: Some("streaming.md".into()),
+                file_extension: Some("md".into()),
+            },
+            timestamp: DateTime::from_timestamp(1_700_000_000, 250_000_001).unwrap(),
+            checksum: [0x11; 32],
+            entanglements: vec![QPXEntanglement::new("telescope_1", EntanglementKind::Telescope)],
+            branches: Vec::new(),
+            timeline: Vec::new(),
+            context: ContextTensor7D::from_vec(&[0.1; 7]),
+        }
+    }
+
+    /// Counts bytes actually read from the underlying stream
+    struct CountingReader<R> {
+        inner: R,
+        bytes_read: usize,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.bytes_read += n;
+            Ok(n)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_summary_skips_fbcu_blob() {
+        let encoded = QPXEncoder::encode_quantum_core(&create_test_core()).unwrap();
+        let counting = CountingReader { inner: Cursor::new(&encoded), bytes_read: 0 };
+
+        let mut reader = QPXReader::new(counting).unwrap();
+        let summary = reader.read_summary().unwrap();
+
+        assert_eq!(summary.metadata.concept_name, "Streaming");
+        assert_eq!(summary.metadata.original_filename.as_deref(), Some("streaming.md"));
+        assert_eq!(summary.compression_type, CompressionType::Gzip);
+        assert_eq!(summary.compressed_size, 4096);
+        assert_eq!(summary.original_size, 9000);
+        assert_eq!(summary.pixel_count, 16);
+        assert_eq!(summary.timestamp, DateTime::from_timestamp(1_700_000_000, 250_000_001).unwrap());
+
+        let bytes_read = reader.into_inner().bytes_read;
+        assert!(bytes_read < 512, "summary read {} bytes", bytes_read);
+    }
+
+    #[test]
+    fn test_read_pixels_and_full_core_at_offset() {
+        let core = create_test_core();
+        let encoded = QPXEncoder::encode_quantum_core(&core).unwrap();
+
+        // Record embedded after unrelated bytes (e.g. inside a pack)
+        let mut stream = vec![0xAB; 100];
+        stream.extend_from_slice(&encoded);
+        let mut cursor = Cursor::new(stream);
+        cursor.set_position(100);
+
+        let mut reader = QPXReader::new(cursor).unwrap();
+        assert_eq!(reader.record_len(), encoded.len() as u64);
+        assert_eq!(reader.read_pixels().unwrap(), core.pixels);
+        assert_eq!(reader.read_entanglements().unwrap(), core.entanglements);
+        assert!(reader.read_branches().unwrap().is_empty());
+        assert!(reader.read_timeline().unwrap().is_empty());
+        assert_eq!(reader.read_context().unwrap().unwrap().to_vec(), vec![0.1; 7]);
+        reader.verify_checksum().unwrap();
+
+        let decoded = reader.read_quantum_core().unwrap();
+        assert_eq!(decoded.fbcu_core.compressed_data, core.fbcu_core.compressed_data);
+        assert_eq!(decoded.metadata.concept_name, "Streaming");
+    }
+
+    #[test]
+    fn test_verify_checksum_detects_tampering() {
+        let mut encoded = QPXEncoder::encode_quantum_core(&create_test_core()).unwrap();
+        encoded[QPXHeader::SIZE + 3] ^= 0xFF;
+
+        let mut reader = QPXReader::new(Cursor::new(encoded)).unwrap();
+        assert!(matches!(reader.verify_checksum(), Err(QPXError::ChecksumMismatch { .. })));
+        assert!(reader.read_quantum_core().is_err());
+    }
+
+    #[test]
+    fn test_read_pixels_rejects_oversized_pixel_count() {
+        let encoded = QPXEncoder::encode_quantum_core(&create_test_core()).unwrap();
+
+        // pixel_count beyond the footer
+        let mut huge = encoded.clone();
+        huge[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut reader = QPXReader::new(Cursor::new(huge)).unwrap();
+        assert!(matches!(reader.read_pixels(), Err(QPXError::DecodingFailed(_))));
+
+        // Header intact but the stream is cut inside the PixelBlock
+        let truncated = encoded[..QPXHeader::SIZE + Pixel::SIZE * 2].to_vec();
+        let mut reader = QPXReader::new(Cursor::new(truncated)).unwrap();
+        assert!(matches!(reader.read_pixels(), Err(QPXError::DecodingFailed(_))));
+    }
+
+    #[test]
+    fn test_read_record_rejects_oversized_footer_offset() {
+        let mut encoded = QPXEncoder::encode_quantum_core(&create_test_core()).unwrap();
+
+        // footer_offset (bytes 40..44) far beyond the stream
+        encoded[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut reader = QPXReader::new(Cursor::new(encoded)).unwrap();
+        assert!(matches!(reader.read_record(), Err(QPXError::DecodingFailed(_))));
+        assert!(matches!(reader.read_quantum_core(), Err(QPXError::DecodingFailed(_))));
+    }
+
+    #[test]
+    fn test_reader_rejects_short_header() {
+        assert!(matches!(
+            QPXReader::new(Cursor::new(vec![0u8; 10])),
+            Err(QPXError::InvalidHeader(_))
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+//! QPX Timeline and Context sections
+//!
+//! - **Timeline** (`timeline_offset`): versioned history of a core, one entry
+//!   per change (version, event kind, timestamp)
+//! - **Context** (`context_offset`): the `ContextTensor7D` the core was built
+//!   from
+//!
+//! Both sections are optional; an offset of 0 means "absent".
+//!
+//! Layout (little-endian):
+//! - Timeline: entry count (u32), then per entry version (u32), kind (u8),
+//!   timestamp (i64 secs + u32 nanos)
+//! - Context: 7 × f64 in `ContextTensor7D::to_vec` order
+
+use std::io::{Read, Write};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use chrono::{DateTime, Utc};
+
+use crate::qpx::entanglement::{read_timestamp, write_timestamp};
+use crate::qpx::error::{QPXError, Result};
+use crate::telescopedb::ContextTensor7D;
+
+/// Encoded size of one timeline entry
+const TIMELINE_ENTRY_SIZE: usize = 4 + 1 + 12;
+
+/// What happened to the core at a timeline entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimelineEventKind {
+    /// Core created
+    Created,
+
+    /// Content or context changed
+    Updated,
+
+    /// Core retired (history kept)
+    Deleted,
+
+    /// Snapshot taken
+    Snapshot,
+
+    /// Core queried
+    Query,
+
+    /// Pattern detected on the core
+    PatternDetected,
+
+    /// Core renamed
+    Renamed,
+
+    /// Entanglement added
+    Linked,
+
+    /// Kind written by a newer encoder (kept as-is on round-trip)
+    Other(u8),
+}
+
+impl TimelineEventKind {
+    /// Wire value
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Created => 0,
+            Self::Updated => 1,
+            Self::Deleted => 2,
+            Self::Snapshot => 3,
+            Self::Query => 4,
+            Self::PatternDetected => 5,
+            Self::Renamed => 6,
+            Self::Linked => 7,
+            Self::Other(value) => value,
+        }
+    }
+
+    /// Parse wire value (unknown values → `Other`)
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Created,
+            1 => Self::Updated,
+            2 => Self::Deleted,
+            3 => Self::Snapshot,
+            4 => Self::Query,
+            5 => Self::PatternDetected,
+            6 => Self::Renamed,
+            7 => Self::Linked,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// One versioned entry of a core's history
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QPXTimelineEntry {
+    /// Version number (1 = creation)
+    pub version: u32,
+
+    /// Event kind
+    pub kind: TimelineEventKind,
+
+    /// When it happened
+    pub timestamp: DateTime<Utc>,
+}
+
+impl QPXTimelineEntry {
+    /// Append an entry with the next version number; returns that version
+    pub fn push(timeline: &mut Vec<QPXTimelineEntry>, kind: TimelineEventKind, timestamp: DateTime<Utc>) -> u32 {
+        let version = timeline.last().map_or(1, |entry| entry.version + 1);
+        timeline.push(QPXTimelineEntry { version, kind, timestamp });
+        version
+    }
+}
+
+pub(crate) fn write_timeline<W: Write>(writer: &mut W, timeline: &[QPXTimelineEntry]) -> Result<()> {
+    let count = u32::try_from(timeline.len()).map_err(|_| {
+        QPXError::EncodingFailed(format!("Too many timeline entries: {}", timeline.len()))
+    })?;
+    writer.write_u32::<LittleEndian>(count)?;
+    for entry in timeline {
+        writer.write_u32::<LittleEndian>(entry.version)?;
+        writer.write_u8(entry.kind.to_u8())?;
+        write_timestamp(writer, &entry.timestamp)?;
+    }
+    Ok(())
+}
+
+/// Read a timeline section; `available` bounds the entry count
+pub(crate) fn read_timeline<R: Read>(reader: &mut R, available: usize) -> Result<Vec<QPXTimelineEntry>> {
+    let count = reader.read_u32::<LittleEndian>()? as usize;
+    if count.saturating_mul(TIMELINE_ENTRY_SIZE) > available.saturating_sub(4) {
+        return Err(QPXError::DecodingFailed(format!(
+            "Timeline claims {} entries but only {} bytes remain",
+            count, available
+        )));
+    }
+
+    let mut timeline = Vec::with_capacity(count);
+    for _ in 0..count {
+        let version = reader.read_u32::<LittleEndian>()?;
+        let kind = TimelineEventKind::from_u8(reader.read_u8()?);
+        let timestamp = read_timestamp(reader)?;
+        timeline.push(QPXTimelineEntry { version, kind, timestamp });
+    }
+    Ok(timeline)
+}
+
+pub(crate) fn write_context<W: Write>(writer: &mut W, context: &ContextTensor7D) -> Result<()> {
+    for value in context.to_vec() {
+        writer.write_f64::<LittleEndian>(value)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_context<R: Read>(reader: &mut R) -> Result<ContextTensor7D> {
+    let mut values = [0f64; 7];
+    for value in values.iter_mut() {
+        *value = reader.read_f64::<LittleEndian>()?;
+    }
+    ContextTensor7D::from_vec(&values)
+        .ok_or_else(|| QPXError::DecodingFailed("Invalid ContextTensor7D".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_push_and_round_trip() {
+        let mut timeline = Vec::new();
+        let t0 = DateTime::from_timestamp(1_700_000_000, 1).unwrap();
+        assert_eq!(QPXTimelineEntry::push(&mut timeline, TimelineEventKind::Created, t0), 1);
+        assert_eq!(QPXTimelineEntry::push(&mut timeline, TimelineEventKind::Other(200), t0), 2);
+
+        let mut buffer = Vec::new();
+        write_timeline(&mut buffer, &timeline).unwrap();
+        assert_eq!(buffer.len(), 4 + 2 * TIMELINE_ENTRY_SIZE);
+        assert_eq!(read_timeline(&mut &buffer[..], buffer.len()).unwrap(), timeline);
+
+        // Count larger than the section → error instead of huge allocation
+        buffer[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read_timeline(&mut &buffer[..], buffer.len()).is_err());
+    }
+
+    #[test]
+    fn test_context_round_trip() {
+        let context = ContextTensor7D::from_vec(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7]).unwrap();
+
+        let mut buffer = Vec::new();
+        write_context(&mut buffer, &context).unwrap();
+        assert_eq!(buffer.len(), 7 * 8);
+        assert_eq!(read_context(&mut &buffer[..]).unwrap().to_vec(), context.to_vec());
+    }
+}
@@ -0,0 +1,257 @@
+//! QPX Versions - Decoder registry and upgrade path
+//!
+//! Every QPX record carries its format version in the header. The decoder
+//! accepts every version listed in `QPXFormatVersion::ALL` and adapts to its
+//! layout; the encoder always writes `QPXFormatVersion::CURRENT`.
+//!
+//! ## Versions
+//!
+//! - **v1.5 (0x0015):** QuantumMeta is a bare sequence of fields
+//! - **v1.6 (0x0016):** QuantumMeta is prefixed with its length (u32), so
+//!   fields appended by newer encoders are skipped instead of misparsed
+//! - **v1.7 (0x0017):** QuantumMeta ends with the sub-second nanos of the
+//!   timestamp (u32); older records have whole-second precision
+//!
+//! In every version, bytes between the last known section and the footer
+//! (sections added by newer encoders) are ignored.
+//!
+//! `upgrade_record` / `upgrade_file` rewrite older records to the current
+//! version.
+
+use std::path::Path;
+
+use crate::qpx::decoder::QPXDecoder;
+use crate::qpx::encoder::QPXEncoder;
+use crate::qpx::error::{QPXError, Result};
+use crate::qpx::header::{QPXHeader, QPXMajorType};
+use crate::qpx::QPX_VERSION;
+
+/// Known QPX format versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QPXFormatVersion {
+    /// v1.5 (0x0015): unframed QuantumMeta
+    V1_5,
+
+    /// v1.6 (0x0016): length-prefixed QuantumMeta
+    V1_6,
+
+    /// v1.7 (0x0017): sub-second timestamp in QuantumMeta
+    V1_7,
+}
+
+impl QPXFormatVersion {
+    /// Version written by the encoder
+    pub const CURRENT: Self = Self::V1_7;
+
+    /// Every version the decoder can read (oldest first)
+    pub const ALL: [Self; 3] = [Self::V1_5, Self::V1_6, Self::V1_7];
+
+    /// Header value
+    pub fn as_u16(self) -> u16 {
+        match self {
+            Self::V1_5 => 0x0015,
+            Self::V1_6 => 0x0016,
+            Self::V1_7 => 0x0017,
+        }
+    }
+
+    /// Look up a header value in the registry
+    pub fn from_u16(version: u16) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|v| v.as_u16() == version)
+            .ok_or(QPXError::UnsupportedVersion(version, QPX_VERSION))
+    }
+
+    /// Whether QuantumMeta starts with its own length (u32)
+    pub fn has_framed_quantum_meta(self) -> bool {
+        self >= Self::V1_6
+    }
+
+    /// Whether QuantumMeta stores the sub-second part of the timestamp
+    pub fn has_subsec_nanos(self) -> bool {
+        self >= Self::V1_7
+    }
+}
+
+/// Rewrite a single QPX record to the current version
+///
+/// Returns `None` if the record is already current. The record is fully
+/// decoded (checksum verified) and re-encoded, so the result is equivalent
+/// to what the current encoder writes for the same data.
+pub fn upgrade_record(bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+    let header = QPXHeader::from_bytes(bytes)?;
+    if QPXFormatVersion::from_u16(header.version)? == QPXFormatVersion::CURRENT {
+        return Ok(None);
+    }
+
+    let upgraded = match header.major_type {
+        t if t == QPXMajorType::QuantumCore as u8 => {
+            QPXEncoder::encode_quantum_core(&QPXDecoder::decode_quantum_core(bytes)?)?
+        }
+        t if t == QPXMajorType::PixelBlock as u8 => {
+            QPXEncoder::encode_pixel_block(&QPXDecoder::decode_pixel_block(bytes)?)?
+        }
+        t if t == QPXMajorType::Entanglement as u8 => {
+            QPXEncoder::encode_entanglement_map(&QPXDecoder::decode_entanglement_map(bytes)?)?
+        }
+        t if t == QPXMajorType::Branch as u8 => {
+            QPXEncoder::encode_branch_table(&QPXDecoder::decode_branch_table(bytes)?)?
+        }
+        other => {
+            return Err(QPXError::DecodingFailed(format!(
+                "Cannot upgrade records of major type 0x{:02x}",
+                other
+            )))
+        }
+    };
+
+    Ok(Some(upgraded))
+}
+
+/// Upgrade a `.qpxf` file in place (temporary file + atomic rename)
+///
+/// Returns `true` if the file was rewritten.
+pub fn upgrade_file(path: &Path) -> Result<bool> {
+    let bytes = std::fs::read(path)?;
+    let Some(upgraded) = upgrade_record(&bytes)? else {
+        return Ok(false);
+    };
+
+    let tmp_path = path.with_extension("qpx.upgrade.tmp");
+    std::fs::write(&tmp_path, upgraded)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbcu::CompressionType;
+
+    const V1_5_QUANTUM_CORE: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/qpx/v1_5_quantum_core.qpx"));
+    const V1_5_PIXEL_BLOCK: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/qpx/v1_5_pixel_block.qpx"));
+    const V1_6_QUANTUM_CORE: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/qpx/v1_6_quantum_core.qpx"));
+    const V1_6_FUTURE_SECTIONS: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/qpx/v1_6_future_sections.qpx"));
+    const V1_7_QUANTUM_CORE: &[u8] =
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/qpx/v1_7_quantum_core.qpx"));
+
+    fn version_of(bytes: &[u8]) -> u16 {
+        QPXHeader::from_bytes(bytes).unwrap().version
+    }
+
+    #[test]
+    fn test_registry() {
+        assert_eq!(QPXFormatVersion::CURRENT.as_u16(), QPX_VERSION);
+        for version in QPXFormatVersion::ALL {
+            assert_eq!(QPXFormatVersion::from_u16(version.as_u16()).unwrap(), version);
+        }
+        assert!(matches!(
+            QPXFormatVersion::from_u16(0x0014),
+            Err(QPXError::UnsupportedVersion(0x0014, QPX_VERSION))
+        ));
+    }
+
+    #[test]
+    fn test_decode_v1_5_fixtures() {
+        assert_eq!(version_of(V1_5_QUANTUM_CORE), 0x0015);
+
+        let core = QPXDecoder::decode_quantum_core(V1_5_QUANTUM_CORE).unwrap();
+        assert_eq!(core.metadata.concept_name, "fixture_template");
+        assert_eq!(core.metadata.tags, vec!["qpx".to_string(), "fixture".to_string()]);
+        assert_eq!(core.metadata.original_filename.as_deref(), Some("fixture.md"));
+        assert_eq!(core.fbcu_core.compression_type, CompressionType::Gzip);
+        assert_eq!(core.fbcu_core.compressed_data, b"# Fixture\n\nQPX v1.5 template body");
+        assert_eq!(core.pixels.len(), 8);
+        assert_eq!(core.timestamp.timestamp(), 1_764_504_000);
+
+        let block = QPXDecoder::decode_pixel_block(V1_5_PIXEL_BLOCK).unwrap();
+        assert_eq!(block.id, "fixture-pixel");
+        assert_eq!(block.meta, vec![7, 7, 7]);
+    }
+
+    #[test]
+    fn test_decode_v1_6_fixtures() {
+        assert_eq!(version_of(V1_6_QUANTUM_CORE), 0x0016);
+
+        let core = QPXDecoder::decode_quantum_core(V1_6_QUANTUM_CORE).unwrap();
+        assert_eq!(core.metadata.concept_name, "fixture_template");
+        assert_eq!(core.entanglements.len(), 1);
+        assert_eq!(core.timeline.len(), 2);
+        assert!(core.context.is_some());
+
+        // Extra fields at the end of QuantumMeta and an unknown section before
+        // the footer (written by a newer encoder) are ignored
+        let future = QPXDecoder::decode_quantum_core(V1_6_FUTURE_SECTIONS).unwrap();
+        assert_eq!(future.metadata.concept_name, core.metadata.concept_name);
+        assert_eq!(future.fbcu_core.compressed_data, core.fbcu_core.compressed_data);
+        assert_eq!(future.timeline, core.timeline);
+        assert_eq!(future.timestamp, core.timestamp);
+
+        let mut reader = crate::qpx::QPXReader::new(std::io::Cursor::new(V1_6_FUTURE_SECTIONS)).unwrap();
+        assert_eq!(reader.read_summary().unwrap().metadata.concept_name, "fixture_template");
+        reader.verify_checksum().unwrap();
+    }
+
+    #[test]
+    fn test_decode_v1_7_fixture() {
+        assert_eq!(version_of(V1_7_QUANTUM_CORE), 0x0017);
+
+        let v1_6 = QPXDecoder::decode_quantum_core(V1_6_QUANTUM_CORE).unwrap();
+        let core = QPXDecoder::decode_quantum_core(V1_7_QUANTUM_CORE).unwrap();
+        assert_eq!(core.metadata.concept_name, v1_6.metadata.concept_name);
+        assert_eq!(core.fbcu_core.compressed_data, v1_6.fbcu_core.compressed_data);
+        assert_eq!(core.entanglements.len(), 1);
+        assert_eq!(core.timeline, v1_6.timeline);
+        assert_eq!(core.timestamp.timestamp(), v1_6.timestamp.timestamp());
+        assert_eq!(core.timestamp.timestamp_subsec_nanos(), 123_456_789);
+
+        let mut reader = crate::qpx::QPXReader::new(std::io::Cursor::new(V1_7_QUANTUM_CORE)).unwrap();
+        assert_eq!(reader.read_summary().unwrap().timestamp, core.timestamp);
+    }
+
+    #[test]
+    fn test_upgrade_v1_5_fixtures() {
+        let upgraded = upgrade_record(V1_5_QUANTUM_CORE).unwrap().unwrap();
+        assert_eq!(version_of(&upgraded), QPX_VERSION);
+        let before = QPXDecoder::decode_quantum_core(V1_5_QUANTUM_CORE).unwrap();
+        let after = QPXDecoder::decode_quantum_core(&upgraded).unwrap();
+        assert_eq!(after.fbcu_core.compressed_data, before.fbcu_core.compressed_data);
+        assert_eq!(after.metadata.concept_name, before.metadata.concept_name);
+        assert_eq!(after.pixels, before.pixels);
+        assert_eq!(after.checksum, before.checksum);
+
+        let upgraded = upgrade_record(V1_5_PIXEL_BLOCK).unwrap().unwrap();
+        assert_eq!(version_of(&upgraded), QPX_VERSION);
+        assert_eq!(
+            QPXDecoder::decode_pixel_block(&upgraded).unwrap(),
+            QPXDecoder::decode_pixel_block(V1_5_PIXEL_BLOCK).unwrap()
+        );
+
+        let upgraded = upgrade_record(V1_6_QUANTUM_CORE).unwrap().unwrap();
+        assert_eq!(version_of(&upgraded), QPX_VERSION);
+        let before = QPXDecoder::decode_quantum_core(V1_6_QUANTUM_CORE).unwrap();
+        let after = QPXDecoder::decode_quantum_core(&upgraded).unwrap();
+        assert_eq!(after.timestamp, before.timestamp);
+        assert_eq!(after.timeline, before.timeline);
+
+        // Already current: nothing to do
+        assert!(upgrade_record(V1_7_QUANTUM_CORE).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_upgrade_file_in_place() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("202511_fixture.qpxf");
+        std::fs::write(&path, V1_5_QUANTUM_CORE).unwrap();
+
+        assert!(upgrade_file(&path).unwrap());
+        assert_eq!(version_of(&std::fs::read(&path).unwrap()), QPX_VERSION);
+        assert!(!upgrade_file(&path).unwrap());
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+}
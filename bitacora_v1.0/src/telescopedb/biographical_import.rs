@@ -11,7 +11,8 @@
 //! - ⏸️ API externa pendiente para v2.0
 
 use crate::telescopedb::{
-    ContextTensor7D, SphericalCoords, TelescopeDB, TelescopeDBError,
+    AtomicCore, ContextTensor7D, Embedding, InsertOutcome, SphericalCoords, TelescopeDB,
+    TelescopeDBError,
 };
 use chrono::Utc;
 use std::path::Path;
@@ -41,6 +42,9 @@ pub struct ImportResult {
     
     /// Número de entradas que fallaron
     pub failed_count: usize,
+
+    /// Entradas que ya existían (mismo contenido → mismo ID)
+    pub deduplicated_count: usize,
     
     /// IDs de las entradas insertadas
     pub entry_ids: Vec<String>,
@@ -214,14 +218,18 @@ impl BiographicalImporter {
         
         let mut success_count = 0;
         let mut failed_count = 0;
+        let mut deduplicated_count = 0;
         let mut entry_ids = Vec::new();
         let mut errors = Vec::new();
         
         for (idx, raw_entry) in entries.into_iter().enumerate() {
             match self.process_single_entry(raw_entry).await {
-                Ok(entry_id) => {
+                Ok(outcome) => {
                     success_count += 1;
-                    entry_ids.push(entry_id);
+                    if !outcome.is_new() {
+                        deduplicated_count += 1;
+                    }
+                    entry_ids.push(outcome.into_id());
                 }
                 Err(e) => {
                     failed_count += 1;
@@ -235,6 +243,7 @@ impl BiographicalImporter {
         Ok(ImportResult {
             success_count,
             failed_count,
+            deduplicated_count,
             entry_ids,
             errors,
             duration_ms,
@@ -245,19 +254,24 @@ impl BiographicalImporter {
     async fn process_single_entry(
         &mut self,
         raw: BiographicalRawEntry,
-    ) -> Result<String, TelescopeDBError> {
+    ) -> Result<InsertOutcome, TelescopeDBError> {
         // 1. Parsear timestamp
         let timestamp = chrono::DateTime::parse_from_rfc3339(&raw.timestamp)
             .map_err(|e| TelescopeDBError::InvalidTimestamp(e.to_string()))?
-            .timestamp_millis() as u64;
+            .with_timezone(&Utc);
         
         // 2. Generar Context Token 7D sintético basado en contenido
         let ctx7d = self.generate_synthetic_ctx7d(&raw.content);
         
-        // 3. Insertar en TelescopeDB usando el método existente
-        let id = self.db.insert_from_ctx7d(&ctx7d).await?;
+        // 3. Insertar en TelescopeDB (content-addressed: contenido + tensor)
+        let atomic_core = AtomicCore {
+            embedding: Embedding::empty("mock".to_string()),
+            anchors: raw.tags,
+            timestamp,
+            content: raw.content.into_bytes(),
+        };
         
-        Ok(id)
+        self.db.insert_atomic(atomic_core, &ctx7d, raw.metadata).await
     }
     
     /// Genera Context Token 7D sintético basado en análisis del contenido
@@ -451,4 +465,22 @@ mod tests {
         let entries = result.unwrap();
         assert_eq!(entries.len(), 10); // STUB retorna 10 sintéticas
     }
+    
+    #[tokio::test]
+    async fn test_reimport_is_deduplicated() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = TelescopeDB::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut importer = BiographicalImporter::new(db);
+        
+        let entries = SyntheticDataGenerator::new(7).generate(20);
+        
+        let first = importer.import_entries(entries.clone()).await.unwrap();
+        assert_eq!(first.failed_count, 0);
+        
+        // Reimportar las mismas entradas no crea cores nuevos
+        let second = importer.import_entries(entries).await.unwrap();
+        assert_eq!(second.success_count, 20);
+        assert_eq!(second.deduplicated_count, 20);
+        assert_eq!(first.entry_ids, second.entry_ids);
+    }
 }
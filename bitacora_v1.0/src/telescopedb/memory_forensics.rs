@@ -2,10 +2,21 @@
 //!
 //! Sistema de análisis temporal: snapshots, diffs, pattern detection.
 //! Timeline reconstruction y state verification.
+//!
+//! ## Detección de patrones
+//!
+//! - **Clustering espacial:** DBSCAN sobre coordenadas esféricas (`eps` =
+//!   `spatial_cluster_threshold`, `min_points` = `dbscan_min_points`).
+//!   Confianza = fracción de core points × compacidad (1 − radio medio / eps).
+//! - **Secuencias temporales:** la timeline se divide en sesiones (huecos >
+//!   `temporal_window_secs`) y se minan n-gramas de tipos de evento.
+//!   Soporte = sesiones que contienen la secuencia; confianza = soporte de la
+//!   secuencia / soporte de su prefijo (regla `prefijo → último evento`).
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::telescopedb::spatial_index::SphericalIndex;
 use crate::telescopedb::{Result, TelescopeDBError, FBCUCore, SphericalCoords};
 
 /// Evento en la línea temporal
@@ -47,6 +58,19 @@ pub enum EventType {
     PatternDetected,
 }
 
+impl From<EventType> for crate::qpx::TimelineEventKind {
+    fn from(event_type: EventType) -> Self {
+        match event_type {
+            EventType::Insert => Self::Created,
+            EventType::Update => Self::Updated,
+            EventType::Delete => Self::Deleted,
+            EventType::Snapshot => Self::Snapshot,
+            EventType::Query => Self::Query,
+            EventType::PatternDetected => Self::PatternDetected,
+        }
+    }
+}
+
 /// Diferencia entre dos FBCU Cores
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoreDiff {
@@ -146,12 +170,21 @@ pub struct MemoryForensics {
 /// Configuración de análisis forense
 #[derive(Debug, Clone)]
 pub struct ForensicsConfig {
-    /// Umbral para clustering espacial
+    /// Umbral para clustering espacial (radio `eps` de DBSCAN)
     pub spatial_cluster_threshold: f64,
 
+    /// Vecinos mínimos (incluyendo el propio core) para ser core point en DBSCAN
+    pub dbscan_min_points: usize,
+
     /// Ventana temporal para secuencias (en segundos)
     pub temporal_window_secs: i64,
 
+    /// Sesiones mínimas en las que debe aparecer una secuencia
+    pub sequence_min_support: usize,
+
+    /// Longitud máxima de las secuencias minadas (≥ 2)
+    pub sequence_max_length: usize,
+
     /// Umbral de confianza mínima para patrones
     pub min_pattern_confidence: f64,
 }
@@ -160,7 +193,10 @@ impl Default for ForensicsConfig {
     fn default() -> Self {
         Self {
             spatial_cluster_threshold: 0.3,
+            dbscan_min_points: 2,
             temporal_window_secs: 3600, // 1 hora
+            sequence_min_support: 2,
+            sequence_max_length: 3,
             min_pattern_confidence: 0.7,
         }
     }
@@ -178,11 +214,19 @@ impl MemoryForensics {
 
     /// Registra un evento en la timeline
     pub fn record_event(&mut self, event: TimelineEvent) {
+        // Caso común (eventos en orden): append sin reordenar
+        let in_order = self
+            .timeline
+            .last()
+            .map(|last| last.timestamp <= event.timestamp)
+            .unwrap_or(true);
+
         self.timeline.push(event);
 
-        // Ordenar por timestamp
-        self.timeline
-            .sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        // Ordenar por timestamp (estable: eventos simultáneos conservan su orden)
+        if !in_order {
+            self.timeline.sort_by_key(|e| e.timestamp);
+        }
     }
 
     /// Calcula la diferencia entre dos cores
@@ -240,34 +284,63 @@ impl MemoryForensics {
         }
     }
 
-    /// Detecta clustering espacial en cores
+    /// Detecta clustering espacial en cores (DBSCAN)
+    ///
+    /// Los cores sin vecindario denso (ruido) no forman parte de ningún patrón.
+    /// Sólo se reportan clusters con confianza ≥ `min_pattern_confidence`.
     pub fn detect_spatial_clustering(&mut self, cores: &[FBCUCore]) -> Vec<MemoryPattern> {
+        let eps = self.config.spatial_cluster_threshold;
+        let min_points = self.config.dbscan_min_points.max(1);
+
+        // Vecindarios eps vía octree (IDs posicionales: tolera IDs repetidos)
+        let mut index = SphericalIndex::new();
+        for (i, core) in cores.iter().enumerate() {
+            index.insert(i.to_string(), core.coords);
+        }
+        let neighborhoods: Vec<Vec<usize>> = cores
+            .iter()
+            .map(|core| {
+                index
+                    .within_radius(core.coords, eps)
+                    .into_iter()
+                    .filter_map(|(id, _)| id.parse().ok())
+                    .collect()
+            })
+            .collect();
+        let is_core_point: Vec<bool> = neighborhoods.iter().map(|n| n.len() >= min_points).collect();
+
+        // Expansión de clusters desde cada core point no visitado
+        let mut assigned = vec![false; cores.len()];
         let mut clusters = Vec::new();
 
-        // Algoritmo simple de clustering por umbral de distancia
-        for i in 0..cores.len() {
-            let mut cluster_cores = vec![cores[i].id.clone()];
+        for seed in 0..cores.len() {
+            if assigned[seed] || !is_core_point[seed] {
+                continue;
+            }
 
-            for j in (i + 1)..cores.len() {
-                let distance = cores[i].coords.distance(&cores[j].coords);
+            let mut members = Vec::new();
+            let mut queue = VecDeque::from([seed]);
+            assigned[seed] = true;
 
-                if distance <= self.config.spatial_cluster_threshold {
-                    cluster_cores.push(cores[j].id.clone());
+            while let Some(point) = queue.pop_front() {
+                members.push(point);
+                if !is_core_point[point] {
+                    continue; // border point: no expande
+                }
+                for &neighbor in &neighborhoods[point] {
+                    if !assigned[neighbor] {
+                        assigned[neighbor] = true;
+                        queue.push_back(neighbor);
+                    }
                 }
             }
 
-            // Solo considerar clusters con al menos 2 cores
-            if cluster_cores.len() >= 2 {
-                let pattern = MemoryPattern {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    pattern_type: PatternType::SpatialClustering,
-                    core_ids: cluster_cores.clone(),
-                    detected_at: Utc::now(),
-                    confidence: 0.85, // TODO: Calcular confianza real
-                    description: format!("Spatial cluster with {} cores", cluster_cores.len()),
-                    metadata: HashMap::new(),
-                };
+            if members.len() < 2 {
+                continue;
+            }
 
+            let pattern = self.spatial_cluster_pattern(cores, &members, &is_core_point);
+            if pattern.confidence >= self.config.min_pattern_confidence {
                 clusters.push(pattern.clone());
                 self.patterns.push(pattern);
             }
@@ -276,58 +349,97 @@ impl MemoryForensics {
         clusters
     }
 
-    /// Detecta secuencias temporales
+    /// Detecta secuencias temporales frecuentes de tipos de evento
+    ///
+    /// Cada secuencia reportada aparece en al menos `sequence_min_support`
+    /// sesiones. `core_ids` lista los cores involucrados en sus ocurrencias.
     pub fn detect_temporal_sequences(&mut self) -> Vec<MemoryPattern> {
-        let mut sequences = Vec::new();
+        let sessions = self.sessions();
+        let max_length = self.config.sequence_max_length.max(2);
+        let min_support = self.config.sequence_min_support.max(1);
+
+        // Soporte por n-grama (n = 1..max_length): sesiones que lo contienen
+        let mut support: HashMap<Vec<EventType>, usize> = HashMap::new();
+        let mut occurrences: HashMap<Vec<EventType>, Vec<&[&TimelineEvent]>> = HashMap::new();
+
+        for session in &sessions {
+            let mut seen = HashSet::new();
+            for n in 1..=max_length.min(session.len()) {
+                for window in session.windows(n) {
+                    let key: Vec<EventType> = window.iter().map(|e| e.event_type).collect();
+                    if n >= 2 {
+                        occurrences.entry(key.clone()).or_default().push(window);
+                    }
+                    if seen.insert(key.clone()) {
+                        *support.entry(key).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
 
-        // Agrupar eventos por ventana temporal
-        let mut current_sequence = Vec::new();
+        let mut candidates: Vec<(&Vec<EventType>, usize)> = support
+            .iter()
+            .filter(|(key, count)| key.len() >= 2 && **count >= min_support)
+            .map(|(key, count)| (key, *count))
+            .collect();
+        // Orden determinista: más soporte primero, luego secuencias más largas
+        candidates.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then(b.0.len().cmp(&a.0.len()))
+                .then_with(|| format!("{:?}", a.0).cmp(&format!("{:?}", b.0)))
+        });
 
-        for i in 0..self.timeline.len() {
-            if current_sequence.is_empty() {
-                current_sequence.push(self.timeline[i].id.clone());
+        let mut sequences = Vec::new();
+        for (key, count) in candidates {
+            let prefix_support = support[&key[..key.len() - 1]];
+            let confidence = count as f64 / prefix_support as f64;
+            if confidence < self.config.min_pattern_confidence {
                 continue;
             }
 
-            // Verificar si está dentro de la ventana temporal
-            let last_idx = current_sequence.len() - 1;
-            let last_event = &self.timeline
-                .iter()
-                .find(|e| e.id == current_sequence[last_idx])
-                .unwrap();
-
-            let time_diff = self.timeline[i]
-                .timestamp
-                .signed_duration_since(last_event.timestamp)
-                .num_seconds();
-
-            if time_diff.abs() <= self.config.temporal_window_secs {
-                current_sequence.push(self.timeline[i].id.clone());
-            } else {
-                // Nueva secuencia
-                if current_sequence.len() >= 2 {
-                    let pattern = MemoryPattern {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        pattern_type: PatternType::TemporalSequence,
-                        core_ids: current_sequence.clone(),
-                        detected_at: Utc::now(),
-                        confidence: 0.80,
-                        description: format!(
-                            "Temporal sequence with {} events",
-                            current_sequence.len()
-                        ),
-                        metadata: HashMap::new(),
-                    };
-
-                    sequences.push(pattern.clone());
-                    self.patterns.push(pattern);
+            let mut core_ids = Vec::new();
+            let mut seen_cores = HashSet::new();
+            for window in &occurrences[key] {
+                for event in window.iter() {
+                    if let Some(core_id) = &event.core_id {
+                        if seen_cores.insert(core_id.clone()) {
+                            core_ids.push(core_id.clone());
+                        }
+                    }
                 }
-
-                current_sequence.clear();
-                current_sequence.push(self.timeline[i].id.clone());
             }
+
+            let label = key
+                .iter()
+                .map(|t| format!("{:?}", t))
+                .collect::<Vec<_>>()
+                .join("→");
+
+            let mut metadata = HashMap::new();
+            metadata.insert("sequence".to_string(), label.clone());
+            metadata.insert("support".to_string(), count.to_string());
+            metadata.insert("prefix_support".to_string(), prefix_support.to_string());
+            metadata.insert("sessions".to_string(), sessions.len().to_string());
+
+            let pattern = MemoryPattern {
+                id: uuid::Uuid::new_v4().to_string(),
+                pattern_type: PatternType::TemporalSequence,
+                core_ids,
+                detected_at: Utc::now(),
+                confidence,
+                description: format!(
+                    "Temporal sequence {} in {}/{} sessions",
+                    label,
+                    count,
+                    sessions.len()
+                ),
+                metadata,
+            };
+
+            sequences.push(pattern);
         }
 
+        self.patterns.extend(sequences.iter().cloned());
         sequences
     }
 
@@ -365,6 +477,87 @@ impl MemoryForensics {
         }
     }
 
+    // === Métodos privados ===
+
+    /// Construye el patrón de un cluster DBSCAN con su confianza
+    fn spatial_cluster_pattern(
+        &self,
+        cores: &[FBCUCore],
+        members: &[usize],
+        is_core_point: &[bool],
+    ) -> MemoryPattern {
+        let eps = self.config.spatial_cluster_threshold;
+
+        // Centroide y radio en cartesianas: sin acos, puntos idénticos o un
+        // centroide en el origen dan radio 0 en vez de NaN
+        let n = members.len() as f64;
+        let points: Vec<(f64, f64, f64)> = members.iter().map(|&i| cores[i].coords.to_cartesian()).collect();
+        let (mut cx, mut cy, mut cz) = (0.0, 0.0, 0.0);
+        for &(x, y, z) in &points {
+            cx += x / n;
+            cy += y / n;
+            cz += z / n;
+        }
+
+        let mean_radius = points
+            .iter()
+            .map(|&(x, y, z)| ((x - cx).powi(2) + (y - cy).powi(2) + (z - cz).powi(2)).sqrt())
+            .sum::<f64>()
+            / n;
+        let core_points = members.iter().filter(|&&i| is_core_point[i]).count();
+
+        let compactness = if eps > 0.0 {
+            (1.0 - mean_radius / eps).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let core_ratio = core_points as f64 / n;
+        let confidence = core_ratio * compactness;
+
+        let mut metadata = HashMap::new();
+        metadata.insert("core_points".to_string(), core_points.to_string());
+        metadata.insert("border_points".to_string(), (members.len() - core_points).to_string());
+        metadata.insert("mean_radius".to_string(), format!("{:.6}", mean_radius));
+        metadata.insert("eps".to_string(), eps.to_string());
+
+        MemoryPattern {
+            id: uuid::Uuid::new_v4().to_string(),
+            pattern_type: PatternType::SpatialClustering,
+            core_ids: members.iter().map(|&i| cores[i].id.clone()).collect(),
+            detected_at: Utc::now(),
+            confidence,
+            description: format!(
+                "Spatial cluster with {} cores (mean radius {:.3})",
+                members.len(),
+                mean_radius
+            ),
+            metadata,
+        }
+    }
+
+    /// Divide la timeline en sesiones separadas por huecos > `temporal_window_secs`
+    fn sessions(&self) -> Vec<Vec<&TimelineEvent>> {
+        let mut sessions: Vec<Vec<&TimelineEvent>> = Vec::new();
+
+        for event in &self.timeline {
+            let starts_new = match sessions.last().and_then(|s| s.last()) {
+                Some(last) => {
+                    event.timestamp.signed_duration_since(last.timestamp).num_seconds()
+                        > self.config.temporal_window_secs
+                }
+                None => true,
+            };
+
+            if starts_new {
+                sessions.push(vec![event]);
+            } else if let Some(session) = sessions.last_mut() {
+                session.push(event);
+            }
+        }
+
+        sessions
+    }
+
     /// Obtiene todos los patrones detectados
     pub fn patterns(&self) -> &[MemoryPattern] {
         &self.patterns
@@ -542,4 +735,133 @@ mod tests {
         assert!(stats.first_event.is_some());
         assert!(stats.last_event.is_some());
     }
+
+    #[test]
+    fn test_dbscan_separates_clusters_and_noise() {
+        let config = ForensicsConfig {
+            spatial_cluster_threshold: 0.2,
+            dbscan_min_points: 3,
+            min_pattern_confidence: 0.0,
+            ..Default::default()
+        };
+        let mut forensics = MemoryForensics::new(config);
+
+        let mut cores = Vec::new();
+        // Cluster denso A (5 cores) y cluster B (4 cores) bien separados
+        for i in 0..5 {
+            let coords = SphericalCoords::new(1.0 + 0.02 * i as f64, 0.5, 1.0).unwrap();
+            cores.push(create_test_core(format!("a-{}", i), coords));
+        }
+        for i in 0..4 {
+            let coords = SphericalCoords::new(3.0, 2.0 + 0.02 * i as f64, 2.0).unwrap();
+            cores.push(create_test_core(format!("b-{}", i), coords));
+        }
+        // Ruido aislado
+        cores.push(create_test_core("noise".to_string(), SphericalCoords::new(6.0, 4.0, 0.5).unwrap()));
+
+        let clusters = forensics.detect_spatial_clustering(&cores);
+        assert_eq!(clusters.len(), 2);
+
+        let mut sizes: Vec<usize> = clusters.iter().map(|c| c.core_ids.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![4, 5]);
+        assert!(clusters.iter().all(|c| !c.core_ids.contains(&"noise".to_string())));
+
+        // Cluster compacto → confianza alta; un cluster más disperso baja
+        assert!(clusters.iter().all(|c| c.confidence > 0.7 && c.confidence <= 1.0));
+        let spread: Vec<FBCUCore> = (0..5)
+            .map(|i| {
+                let coords = SphericalCoords::new(1.0 + 0.09 * i as f64, 0.5, 1.0).unwrap();
+                create_test_core(format!("s-{}", i), coords)
+            })
+            .collect();
+        let spread_clusters = forensics.detect_spatial_clustering(&spread);
+        assert_eq!(spread_clusters.len(), 1);
+        assert!(spread_clusters[0].confidence < clusters[0].confidence.min(clusters[1].confidence));
+
+        // El umbral de confianza filtra clusters débiles
+        let mut strict = MemoryForensics::new(ForensicsConfig {
+            spatial_cluster_threshold: 0.2,
+            dbscan_min_points: 3,
+            min_pattern_confidence: 0.99,
+            ..Default::default()
+        });
+        assert!(strict.detect_spatial_clustering(&spread).is_empty());
+    }
+
+    #[test]
+    fn test_spatial_cluster_of_identical_points() {
+        let mut forensics = MemoryForensics::new(ForensicsConfig {
+            spatial_cluster_threshold: 0.2,
+            dbscan_min_points: 3,
+            min_pattern_confidence: 0.0,
+            ..Default::default()
+        });
+
+        let coords = SphericalCoords::new(1.0, 0.5, 1.0).unwrap();
+        let cores: Vec<FBCUCore> = (0..4)
+            .map(|i| create_test_core(format!("same-{}", i), coords))
+            .collect();
+
+        let clusters = forensics.detect_spatial_clustering(&cores);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].metadata["mean_radius"], "0.000000");
+        assert_eq!(clusters[0].confidence, 1.0);
+    }
+
+    #[test]
+    fn test_sequence_miner_support_and_confidence() {
+        let mut forensics = MemoryForensics::new(ForensicsConfig {
+            min_pattern_confidence: 0.5,
+            ..Default::default()
+        });
+
+        let coords = SphericalCoords::new(1.0, 0.5, 1.0).unwrap();
+        let start = Utc::now() - chrono::Duration::days(10);
+
+        // 4 sesiones (separadas por días): Insert→Update en todas,
+        // Insert→Update→Delete en 3 de ellas
+        for session in 0..4 {
+            let base = start + chrono::Duration::days(session * 2);
+            let mut kinds = vec![EventType::Insert, EventType::Update];
+            if session < 3 {
+                kinds.push(EventType::Delete);
+            }
+            for (i, kind) in kinds.into_iter().enumerate() {
+                forensics.record_event(TimelineEvent {
+                    id: format!("s{}-e{}", session, i),
+                    timestamp: base + chrono::Duration::minutes(i as i64),
+                    event_type: kind,
+                    core_id: Some(format!("core-{}", session)),
+                    coords,
+                    metadata: HashMap::new(),
+                });
+            }
+        }
+
+        let sequences = forensics.detect_temporal_sequences();
+        let find = |label: &str| {
+            sequences
+                .iter()
+                .find(|p| p.metadata["sequence"] == label)
+                .cloned()
+        };
+
+        let insert_update = find("Insert→Update").unwrap();
+        assert_eq!(insert_update.metadata["support"], "4");
+        assert_eq!(insert_update.confidence, 1.0);
+        assert_eq!(insert_update.core_ids.len(), 4);
+
+        let full = find("Insert→Update→Delete").unwrap();
+        assert_eq!(full.metadata["support"], "3");
+        assert!((full.confidence - 0.75).abs() < 1e-12);
+        assert_eq!(sequences[0].metadata["sequence"], "Insert→Update");
+
+        // Con soporte mínimo 4 la secuencia larga desaparece
+        forensics.config.sequence_min_support = 4;
+        assert!(forensics
+            .detect_temporal_sequences()
+            .iter()
+            .all(|p| p.metadata["sequence"] != "Insert→Update→Delete"));
+    }
 }
@@ -9,7 +9,10 @@
 //! TelescopeDB
 //! ├── pixel_storage       → Almacenamiento de píxeles con metadata
 //! ├── memory_forensics    → Análisis temporal y pattern detection
-//! └── snapshot_manager    → Gestión de snapshots y comparaciones
+//! ├── snapshot_manager    → Gestión de snapshots y comparaciones
+//! ├── persistence         → Write-ahead log + segmentos compactados
+//! ├── spatial_index       → Octree cartesiano para queries contextuales
+//! └── semantic_index      → HNSW sobre embeddings para queries semánticos
 //! ```
 //!
 //! ## Ejemplo de Uso
@@ -20,12 +23,14 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
-//!     // Crear nueva instancia de TelescopeDB
+//!     // Crear (o reabrir) TelescopeDB: reproduce WAL + segmentos existentes
 //!     let mut telescope = TelescopeDB::new(PathBuf::from(".bitacora/telescope"))?;
 //!     
 //!     // Insertar FBCU Core desde Context Token 7D
 //!     let ctx7d = create_context_token();
-//!     let id = telescope.insert_from_ctx7d(&ctx7d).await?;
+//!     // (content-addressed: reinsertar la misma experiencia es idempotente)
+//!     let outcome = telescope.insert_from_ctx7d(&ctx7d).await?;
+//!     println!("Core {} (new: {})", outcome.id(), outcome.is_new());
 //!     
 //!     // Query contextual (búsqueda esférica)
 //!     let coords = SphericalCoords { r: 0.8, theta: 1.2, phi: 0.5 };
@@ -33,6 +38,9 @@
 //!     
 //!     println!("Found {} similar experiences", results.len());
 //!     
+//!     // Top-k configurable, con distancias
+//!     let nearest = telescope.query_contextual_top_k(coords, 0.3, 25).await?;
+//!     
 //!     Ok(())
 //! }
 //! ```
@@ -41,6 +49,9 @@ pub mod pixel_storage;
 pub mod memory_forensics;
 pub mod snapshot_manager;
 pub mod biographical_import;
+pub mod persistence;
+pub mod spatial_index;
+pub mod semantic_index;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -50,12 +61,27 @@ use thiserror::Error;
 
 // Re-exports
 pub use pixel_storage::{PixelData, PixelStore};
-pub use memory_forensics::{MemoryForensics, TimelineEvent};
-pub use snapshot_manager::{Snapshot, SnapshotManager};
+pub use memory_forensics::{CoreDiff, EventType, ForensicsConfig, MemoryForensics, TimelineEvent};
+pub use snapshot_manager::{Snapshot, SnapshotComparison, SnapshotManager};
 pub use biographical_import::{
     BiographicalImporter, BiographicalRawEntry, ImportResult, 
     SyntheticDataGenerator, import_from_sandbox,
 };
+pub use persistence::{CoreVersion, PersistenceConfig, StorageEngine, StoreState, WalOp};
+pub use spatial_index::SphericalIndex;
+pub use semantic_index::{HnswConfig, HnswIndex};
+
+/// Número de resultados por defecto de `query_contextual`
+pub const DEFAULT_CONTEXTUAL_TOP_K: usize = 10;
+
+/// Número de resultados por defecto de `query_semantic`
+pub const DEFAULT_SEMANTIC_TOP_K: usize = 10;
+
+/// Similitud coseno mínima por defecto de `query_semantic`
+pub const DEFAULT_SEMANTIC_THRESHOLD: f64 = 0.7;
+
+/// Etiqueta del estado vivo en las comparaciones de `restore_snapshot`
+pub const CURRENT_STATE_ID: &str = "current";
 
 /// Errores específicos de TelescopeDB
 #[derive(Debug, Error)]
@@ -63,6 +89,12 @@ pub enum TelescopeDBError {
     #[error("FBCU Core not found: {0}")]
     CoreNotFound(String),
 
+    #[error("FBCU Core already exists: {0}")]
+    CoreAlreadyExists(String),
+
+    #[error("Snapshot not found: {0}")]
+    SnapshotNotFound(String),
+
     #[error("Invalid spherical coordinates: r={r}, theta={theta}, phi={phi}")]
     InvalidCoordinates { r: f64, theta: f64, phi: f64 },
 
@@ -93,8 +125,14 @@ pub enum TelescopeDBError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("Storage corrupted: {0}")]
+    StorageCorrupted(String),
+
     #[error("Context Token 7D conversion failed: {0}")]
     CTX7DConversionError(String),
+
+    #[error("QPX error: {0}")]
+    QPXError(#[from] crate::qpx::QPXError),
 }
 
 pub type Result<T> = std::result::Result<T, TelescopeDBError>;
@@ -291,13 +329,88 @@ pub struct FBCUCore {
     pub metadata: HashMap<String, String>,
 }
 
+impl FBCUCore {
+    /// Calcula el ID content-addressed: SHA-256 del contenido atómico + tensor 7D
+    ///
+    /// El timestamp no participa, así que la misma experiencia produce el mismo ID.
+    pub fn content_id(content: &[u8], tensor: &ContextTensor7D) -> String {
+        let mut buffer = Vec::with_capacity(content.len() + 7 * 8);
+        buffer.extend_from_slice(content);
+        for value in tensor.to_vec() {
+            buffer.extend_from_slice(&value.to_le_bytes());
+        }
+
+        crate::fbcu::sha256_hex(&buffer)
+    }
+}
+
+/// Resultado de una inserción en TelescopeDB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// Core nuevo, persistido en el WAL
+    Inserted(String),
+
+    /// Ya existía un core con el mismo contenido (no se escribió nada)
+    Deduplicated(String),
+}
+
+impl InsertOutcome {
+    /// ID del core (nuevo o existente)
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Inserted(id) | Self::Deduplicated(id) => id,
+        }
+    }
+
+    /// Consume el resultado retornando el ID
+    pub fn into_id(self) -> String {
+        match self {
+            Self::Inserted(id) | Self::Deduplicated(id) => id,
+        }
+    }
+
+    /// Indica si el core fue insertado por primera vez
+    pub fn is_new(&self) -> bool {
+        matches!(self, Self::Inserted(_))
+    }
+}
+
+/// Cambios a aplicar sobre un core existente
+///
+/// Los campos en `None` conservan el valor de la versión actual. Si cambian el
+/// contenido o el tensor, el core pasa a su nuevo `content_id` y el ID anterior
+/// queda como alias (ver `TelescopeDB::resolve_id`).
+#[derive(Debug, Clone, Default)]
+pub struct CoreUpdate {
+    /// Nuevo tensor 7D (recalcula coordenadas esféricas)
+    pub context_tensor: Option<ContextTensor7D>,
+
+    /// Nuevo núcleo atómico (contenido, anchors, embedding)
+    pub atomic_core: Option<AtomicCore>,
+
+    /// Nueva metadata (reemplaza la anterior)
+    pub metadata: Option<HashMap<String, String>>,
+}
+
 /// Estructura principal de TelescopeDB
 pub struct TelescopeDB {
     /// Directorio raíz de almacenamiento
     storage_path: PathBuf,
 
-    /// Mapa de cores (content-addressable por SHA-256)
-    cores: HashMap<String, FBCUCore>,
+    /// Cores vivos (content-addressable por SHA-256) + historial de versiones
+    state: StoreState,
+
+    /// Timeline forense alimentada por inserts, updates y deletes
+    forensics: MemoryForensics,
+
+    /// WAL + segmentos en disco
+    storage: StorageEngine,
+
+    /// Índice espacial sobre coordenadas esféricas
+    spatial_index: SphericalIndex,
+
+    /// Índice ANN (HNSW) sobre embeddings
+    semantic_index: HnswIndex,
 
     /// Métricas de uso
     metrics: TelescopeMetrics,
@@ -307,6 +420,7 @@ pub struct TelescopeDB {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TelescopeMetrics {
     pub total_cores: usize,
+    pub deduplicated_inserts: usize,
     pub total_queries: usize,
     pub avg_query_time_ms: f64,
     pub compression_ratio: f64,
@@ -314,102 +428,510 @@ pub struct TelescopeMetrics {
 
 impl TelescopeDB {
     /// Crea nueva instancia de TelescopeDB
+    ///
+    /// Si `storage_path` ya contiene un store, se reabre reproduciendo
+    /// el último segmento compactado y el WAL.
     pub fn new(storage_path: PathBuf) -> Result<Self> {
+        Self::open_with_config(storage_path, PersistenceConfig::default())
+    }
+
+    /// Abre un store existente (falla si el directorio no existe)
+    pub fn open(storage_path: PathBuf) -> Result<Self> {
+        if !storage_path.is_dir() {
+            return Err(TelescopeDBError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("TelescopeDB store not found: {:?}", storage_path),
+            )));
+        }
+
+        Self::open_with_config(storage_path, PersistenceConfig::default())
+    }
+
+    /// Crea o abre TelescopeDB con configuración de persistencia explícita
+    pub fn open_with_config(storage_path: PathBuf, config: PersistenceConfig) -> Result<Self> {
         // Crear directorio si no existe
         std::fs::create_dir_all(&storage_path)?;
 
+        let (storage, state) = StorageEngine::open(&storage_path, config)?;
+        let (spatial_index, semantic_index) = build_indexes(&state);
+
+        // La timeline se reconstruye desde el historial persistido
+        let mut forensics = MemoryForensics::new(ForensicsConfig::default());
+        let mut versions: Vec<&CoreVersion> = state.history.values().flatten().collect();
+        versions.sort_by_key(|v| v.recorded_at);
+        for version in versions {
+            forensics.record_event(timeline_event(version));
+        }
+
+        let metrics = TelescopeMetrics {
+            total_cores: state.cores.len(),
+            ..Default::default()
+        };
+
         Ok(Self {
             storage_path,
-            cores: HashMap::new(),
-            metrics: TelescopeMetrics::default(),
+            state,
+            forensics,
+            storage,
+            spatial_index,
+            semantic_index,
+            metrics,
         })
     }
 
     /// Inserta FBCU Core desde Context Token 7D
-    pub async fn insert_from_ctx7d(&mut self, token: &ContextTensor7D) -> Result<String> {
+    pub async fn insert_from_ctx7d(&mut self, token: &ContextTensor7D) -> Result<InsertOutcome> {
+        let atomic_core = AtomicCore {
+            embedding: Embedding::empty("mock".to_string()),
+            anchors: Vec::new(),
+            timestamp: Utc::now(),
+            content: Vec::new(),
+        };
+
+        self.insert_atomic(atomic_core, token, HashMap::new()).await
+    }
+
+    /// Inserta FBCU Core con núcleo atómico explícito (contenido, anchors, embedding)
+    ///
+    /// Idempotente: si ya existe un core con el mismo `content_id`, no se escribe
+    /// nada y se retorna `InsertOutcome::Deduplicated`.
+    pub async fn insert_atomic(
+        &mut self,
+        atomic_core: AtomicCore,
+        token: &ContextTensor7D,
+        metadata: HashMap<String, String>,
+    ) -> Result<InsertOutcome> {
+        let id = FBCUCore::content_id(&atomic_core.content, token);
+
+        if self.state.cores.contains_key(&id) {
+            self.metrics.deduplicated_inserts += 1;
+            return Ok(InsertOutcome::Deduplicated(id));
+        }
+
         // Calcular coordenadas esféricas
         let coords = token.to_spherical_coords();
 
-        // Generar ID temporal (en implementación real: SHA-256 del contenido)
-        let id = uuid::Uuid::new_v4().to_string();
-
         // Crear FBCU Core
         let fbcu_core = FBCUCore {
             id: id.clone(),
-            atomic_core: AtomicCore {
-                embedding: Embedding::empty("mock".to_string()),
-                anchors: Vec::new(),
-                timestamp: Utc::now(),
-                content: Vec::new(),
-            },
+            atomic_core,
             context_tensor: token.clone(),
             coords,
-            metadata: HashMap::new(),
+            metadata,
         };
 
-        // Guardar en HashMap
-        self.cores.insert(id.clone(), fbcu_core);
+        // Write-ahead: primero a disco, luego a memoria
+        self.apply_op(WalOp::Insert(fbcu_core))?;
         self.metrics.total_cores += 1;
 
-        Ok(id)
+        // Actualizar índices
+        let core = &self.state.cores[&id];
+        self.spatial_index.insert(id.clone(), core.coords);
+        self.semantic_index.insert(&id, &core.atomic_core.embedding);
+
+        self.maybe_compact()?;
+
+        Ok(InsertOutcome::Inserted(id))
+    }
+
+    /// Corrige un core existente creando una nueva versión
+    ///
+    /// La versión anterior queda en el historial y el diff (vía
+    /// `MemoryForensics::compute_diff`) se registra en la timeline. Si cambia
+    /// el contenido o el tensor, el core se re-indexa bajo su nuevo
+    /// `content_id` (`CoreDiff::new_id`); el ID anterior sigue resolviendo.
+    ///
+    /// Falla con `CoreAlreadyExists` si otro core vivo tiene ese contenido
+    /// (directamente o vía alias). Como en `insert_atomic`, el contenido de un
+    /// core eliminado puede volver: su historial se conserva y el del core
+    /// actualizado continúa a continuación.
+    pub async fn update_core(&mut self, id: &str, update: CoreUpdate) -> Result<CoreDiff> {
+        let old_id = self.state.resolve(id).to_string();
+        let old_core = self
+            .state
+            .cores
+            .get(&old_id)
+            .ok_or_else(|| TelescopeDBError::CoreNotFound(id.to_string()))?;
+
+        let mut new_core = old_core.clone();
+        if let Some(tensor) = update.context_tensor {
+            new_core.coords = tensor.to_spherical_coords();
+            new_core.context_tensor = tensor;
+        }
+        if let Some(atomic_core) = update.atomic_core {
+            new_core.atomic_core = atomic_core;
+        }
+        if let Some(metadata) = update.metadata {
+            new_core.metadata = metadata;
+        }
+
+        let content_changed = new_core.atomic_core.content != old_core.atomic_core.content
+            || new_core.context_tensor.to_vec() != old_core.context_tensor.to_vec();
+        if content_changed {
+            new_core.id = FBCUCore::content_id(&new_core.atomic_core.content, &new_core.context_tensor);
+            let taken = self.state.cores.contains_key(&new_core.id)
+                || self.state.aliases.get(&new_core.id).is_some_and(|target| {
+                    *target != old_id && self.state.cores.contains_key(target)
+                });
+            if new_core.id != old_id && taken {
+                return Err(TelescopeDBError::CoreAlreadyExists(new_core.id));
+            }
+        }
+
+        let diff = self.forensics.compute_diff(old_core, &new_core);
+        let old_coords = old_core.coords;
+        let renamed_from = (new_core.id != old_id).then(|| old_id.clone());
+
+        self.apply_op(WalOp::Update {
+            core: new_core,
+            diff: diff.clone(),
+            renamed_from,
+        })?;
+
+        // Reindexar con la nueva versión (un embedding vacío sólo retira la anterior)
+        let core = &self.state.cores[&diff.new_id];
+        self.spatial_index.remove(&old_id, old_coords);
+        self.spatial_index.insert(core.id.clone(), core.coords);
+        self.semantic_index.remove(&old_id);
+        self.semantic_index.insert(&core.id, &core.atomic_core.embedding);
+
+        self.maybe_compact()?;
+
+        Ok(diff)
+    }
+
+    /// Retira un core: deja de aparecer en queries pero su historial se conserva
+    ///
+    /// Retorna la última versión del core eliminado.
+    pub async fn delete_core(&mut self, id: &str) -> Result<FBCUCore> {
+        let id = self.state.resolve(id).to_string();
+        if !self.state.cores.contains_key(&id) {
+            return Err(TelescopeDBError::CoreNotFound(id));
+        }
+
+        let version = self.apply_op(WalOp::Delete { id: id.clone() })?;
+        self.metrics.total_cores -= 1;
+
+        self.spatial_index.remove(&id, version.core.coords);
+        self.semantic_index.remove(&id);
+
+        self.maybe_compact()?;
+
+        Ok(version.core)
+    }
+
+    /// ID actual de un core: `id` re-keyed por un `update_core` resuelve al
+    /// ID de su contenido actual; cualquier otro se retorna igual
+    pub fn resolve_id<'a>(&'a self, id: &'a str) -> &'a str {
+        self.state.resolve(id)
+    }
+
+    /// Obtiene la versión actual de un core
+    pub fn get_core(&self, id: &str) -> Option<&FBCUCore> {
+        self.state.cores.get(self.state.resolve(id))
+    }
+
+    /// Historial de versiones de un core (vacío si nunca existió)
+    ///
+    /// Incluye cores eliminados: la última entrada es el `Delete`.
+    pub fn history(&self, id: &str) -> &[CoreVersion] {
+        self.state
+            .history
+            .get(self.state.resolve(id))
+            .map(|versions| versions.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Exporta un core a QPX nativo (QuantumCore) con su historial y tensor 7D
+    ///
+    /// - Contenido atómico → blob FBCU (sin comprimir)
+    /// - Embedding → píxeles (cuantizado, para búsquedas sobre QPX)
+    /// - Anchors → tags, modelo de embedding → categoría
+    /// - Historial de versiones → sección Timeline
+    /// - `ContextTensor7D` → sección Context
+    ///
+    /// Funciona también con cores eliminados (se exporta la última versión).
+    pub fn export_core_qpx(&self, id: &str) -> Result<Vec<u8>> {
+        use sha2::Digest;
+
+        let history = self.history(id);
+        let core = self
+            .get_core(id)
+            .or_else(|| history.last().map(|version| &version.core))
+            .ok_or_else(|| TelescopeDBError::CoreNotFound(id.to_string()))?;
+
+        let atomic = &core.atomic_core;
+        let checksum: [u8; 32] = sha2::Sha256::digest(&atomic.content).into();
+
+        let quantum_core = crate::qpx::QPXQuantumCore {
+            fbcu_core: crate::fbcu::FBCUCore {
+                id: core.id.clone(),
+                compression_type: crate::fbcu::CompressionType::None,
+                compressed_data: atomic.content.clone(),
+                original_size: atomic.content.len(),
+                compression_ratio: 1.0,
+                metadata: crate::fbcu::FBCUMetadata {
+                    compressed_at: atomic.timestamp.to_rfc3339(),
+                    compression_time_ms: 0,
+                    original_hash: hex::encode(checksum),
+                    wavelet_level: None,
+                    fractal_level: None,
+                    dictionary_id: None,
+                    codec_selection: None,
+                },
+            },
+            pixels: crate::qpx::embedding_to_pixels(&atomic.embedding.vec, 255),
+            alpha: 255,
+            metadata: crate::qpx::TemplateMetadata {
+                concept_name: core.id.clone(),
+                category: atomic.embedding.model.clone(),
+                tags: atomic.anchors.clone(),
+                original_path: String::new(),
+                original_filename: None,
+                file_extension: None,
+            },
+            timestamp: atomic.timestamp,
+            checksum,
+            entanglements: Vec::new(),
+            branches: Vec::new(),
+            timeline: history
+                .iter()
+                .map(|version| crate::qpx::QPXTimelineEntry {
+                    version: version.version,
+                    kind: version.event_type.into(),
+                    timestamp: version.recorded_at,
+                })
+                .collect(),
+            context: Some(core.context_tensor.clone()),
+        };
+
+        Ok(crate::qpx::QPXEncoder::encode_quantum_core(&quantum_core)?)
+    }
+
+    /// Reconstruye un core exportado con `export_core_qpx` y su timeline
+    ///
+    /// ID, contenido, anchors, timestamp y tensor 7D (y por lo tanto las
+    /// coordenadas) se restauran exactos. El embedding sólo viaja cuantizado en
+    /// los píxeles, así que se retorna vacío (con su modelo); la metadata libre
+    /// del core no se exporta.
+    pub fn core_from_qpx(bytes: &[u8]) -> Result<(FBCUCore, Vec<crate::qpx::QPXTimelineEntry>)> {
+        let quantum_core = crate::qpx::QPXDecoder::decode_quantum_core(bytes)?;
+
+        let context_tensor = quantum_core.context.ok_or_else(|| {
+            TelescopeDBError::CTX7DConversionError("QPX record has no context section".to_string())
+        })?;
+
+        let core = FBCUCore {
+            id: quantum_core.metadata.concept_name,
+            atomic_core: AtomicCore {
+                embedding: Embedding::empty(quantum_core.metadata.category),
+                anchors: quantum_core.metadata.tags,
+                timestamp: quantum_core.timestamp,
+                content: quantum_core.fbcu_core.compressed_data,
+            },
+            coords: context_tensor.to_spherical_coords(),
+            context_tensor,
+            metadata: HashMap::new(),
+        };
+
+        Ok((core, quantum_core.timeline))
+    }
+
+    /// Análisis forense (timeline de inserts, updates y deletes)
+    pub fn forensics(&self) -> &MemoryForensics {
+        &self.forensics
+    }
+
+    /// Itera sobre los cores vivos (p.ej. para crear un snapshot)
+    pub fn cores(&self) -> impl Iterator<Item = &FBCUCore> {
+        self.state.cores.values()
+    }
+
+    /// Restaura el estado de un snapshot
+    ///
+    /// Retorna la comparación estado vivo → snapshot. Con `dry_run` no se
+    /// modifica nada. Si no, las diferencias se aplican sobre una copia del
+    /// estado (quedan en el historial y la timeline), la copia se persiste como
+    /// segmento nuevo y sólo entonces reemplaza al estado vivo: un fallo a
+    /// mitad de camino no deja una restauración parcial ni en memoria ni en
+    /// disco. Los índices se reconstruyen desde cero.
+    pub async fn restore_snapshot(
+        &mut self,
+        snapshots: &SnapshotManager,
+        snapshot_id: &str,
+        dry_run: bool,
+    ) -> Result<SnapshotComparison> {
+        let target = snapshots.load_snapshot_cores(snapshot_id)?;
+
+        // Cores re-keyed después del snapshot se comparan bajo su ID de entonces
+        let mut current = self.state.cores.clone();
+        for core in &target {
+            let live_id = self.state.resolve(&core.id);
+            if live_id != core.id {
+                if let Some(mut live) = current.remove(live_id) {
+                    live.id = core.id.clone();
+                    current.insert(core.id.clone(), live);
+                }
+            }
+        }
+        let current: Vec<FBCUCore> = current.into_values().collect();
+        let comparison =
+            SnapshotManager::compare_cores(CURRENT_STATE_ID, &current, snapshot_id, &target)?;
+
+        if dry_run {
+            return Ok(comparison);
+        }
+
+        let mut target: HashMap<String, FBCUCore> =
+            target.into_iter().map(|c| (c.id.clone(), c)).collect();
+
+        // Staging: el estado vivo no cambia hasta el swap
+        let mut staged = self.state.clone();
+        let restored_at = Utc::now();
+        let mut ops = Vec::new();
+        for id in &comparison.deleted_cores {
+            ops.push(WalOp::Delete { id: id.clone() });
+        }
+        for id in &comparison.added_cores {
+            if let Some(core) = target.remove(id) {
+                ops.push(WalOp::Insert(core));
+            }
+        }
+        for diff in &comparison.summary.modifications {
+            if let Some(core) = target.remove(&diff.new_id) {
+                let live_id = staged.resolve(&core.id).to_string();
+                ops.push(WalOp::Update {
+                    renamed_from: (live_id != core.id).then_some(live_id),
+                    core,
+                    diff: diff.clone(),
+                });
+            }
+        }
+        let versions = ops
+            .into_iter()
+            .map(|op| {
+                op.apply(&mut staged, restored_at).ok_or_else(|| {
+                    TelescopeDBError::StorageCorrupted("restore operation on missing core".to_string())
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // El segmento (tmp + rename) es el punto de commit de la restauración
+        self.storage.compact(&staged)?;
+
+        self.state = staged;
+        for version in &versions {
+            self.forensics.record_event(timeline_event(version));
+        }
+        let (spatial_index, semantic_index) = build_indexes(&self.state);
+        self.spatial_index = spatial_index;
+        self.semantic_index = semantic_index;
+        self.metrics.total_cores = self.state.cores.len();
+
+        tracing::info!(
+            "TelescopeDB restored snapshot {}: +{} -{} ~{}",
+            snapshot_id,
+            comparison.summary.total_added,
+            comparison.summary.total_deleted,
+            comparison.summary.total_modified
+        );
+
+        Ok(comparison)
+    }
+
+    /// Rollback al snapshot más reciente creado en o antes de `as_of`
+    pub async fn restore_as_of(
+        &mut self,
+        snapshots: &SnapshotManager,
+        as_of: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<SnapshotComparison> {
+        let snapshot = snapshots
+            .get_snapshot_as_of(as_of)
+            .ok_or_else(|| TelescopeDBError::SnapshotNotFound(format!("as of {}", as_of)))?;
+
+        self.restore_snapshot(snapshots, &snapshot.id, dry_run).await
     }
 
     /// Query contextual: buscar experiencias similares por coordenadas
+    ///
+    /// Retorna los `DEFAULT_CONTEXTUAL_TOP_K` cores más cercanos dentro de `radius`.
     pub async fn query_contextual(
         &self,
         coords: SphericalCoords,
         radius: f64,
     ) -> Result<Vec<FBCUCore>> {
+        Ok(self
+            .query_contextual_top_k(coords, radius, DEFAULT_CONTEXTUAL_TOP_K)
+            .await?
+            .into_iter()
+            .map(|(c, _)| c)
+            .collect())
+    }
+
+    /// Query contextual con `k` configurable: los `k` cores más cercanos dentro
+    /// de `radius`, ordenados por distancia (más cercano primero)
+    pub async fn query_contextual_top_k(
+        &self,
+        coords: SphericalCoords,
+        radius: f64,
+        k: usize,
+    ) -> Result<Vec<(FBCUCore, f64)>> {
         let start = std::time::Instant::now();
 
-        let mut results: Vec<(FBCUCore, f64)> = self
-            .cores
-            .values()
-            .filter_map(|core| {
-                let distance = coords.distance(&core.coords);
-                if distance <= radius {
-                    Some((core.clone(), distance))
-                } else {
-                    None
-                }
-            })
+        // Búsqueda best-first en el octree (sub-lineal)
+        let results: Vec<(FBCUCore, f64)> = self
+            .spatial_index
+            .nearest(coords, k, radius)
+            .into_iter()
+            .filter_map(|(id, distance)| self.state.cores.get(&id).map(|c| (c.clone(), distance)))
             .collect();
 
-        // Ordenar por distancia (más cercano primero)
-        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-
         let elapsed = start.elapsed().as_millis() as f64;
         tracing::debug!("Query contextual completed in {}ms", elapsed);
 
-        // Retornar top-10
-        Ok(results.into_iter().take(10).map(|(c, _)| c).collect())
+        Ok(results)
     }
 
     /// Query semántico: buscar por similaridad de embeddings
+    ///
+    /// Retorna hasta `DEFAULT_SEMANTIC_TOP_K` cores con similitud
+    /// ≥ `DEFAULT_SEMANTIC_THRESHOLD`.
     pub async fn query_semantic(&self, query_embedding: &Embedding) -> Result<Vec<(FBCUCore, f64)>> {
+        self.query_semantic_top_k(
+            query_embedding,
+            DEFAULT_SEMANTIC_TOP_K,
+            DEFAULT_SEMANTIC_THRESHOLD,
+        )
+        .await
+    }
+
+    /// Query semántico con `k` y umbral configurables
+    ///
+    /// El umbral se aplica antes de truncar a `k`. Resultados ordenados por
+    /// similitud (mayor primero).
+    pub async fn query_semantic_top_k(
+        &self,
+        query_embedding: &Embedding,
+        k: usize,
+        threshold: f64,
+    ) -> Result<Vec<(FBCUCore, f64)>> {
         let start = std::time::Instant::now();
 
-        let mut results: Vec<(FBCUCore, f64)> = self
-            .cores
-            .values()
-            .map(|core| {
-                let similarity = query_embedding.cosine_similarity(&core.atomic_core.embedding);
-                (core.clone(), similarity)
+        // Búsqueda ANN en el grafo HNSW
+        let results: Vec<(FBCUCore, f64)> = self
+            .semantic_index
+            .search(query_embedding, k, threshold)
+            .into_iter()
+            .filter_map(|(id, similarity)| {
+                self.state.cores.get(&id).map(|c| (c.clone(), similarity))
             })
             .collect();
 
-        // Ordenar por similaridad (mayor primero)
-        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
         let elapsed = start.elapsed().as_millis() as f64;
         tracing::debug!("Query semantic completed in {}ms", elapsed);
 
-        // Retornar top-10 con similarity > 0.7
-        Ok(results
-            .into_iter()
-            .take(10)
-            .filter(|(_, sim)| *sim > 0.7)
-            .collect())
+        Ok(results)
     }
 
     /// Obtiene métricas actuales
@@ -419,12 +941,84 @@ impl TelescopeDB {
 
     /// Obtiene número total de cores almacenados
     pub fn len(&self) -> usize {
-        self.cores.len()
+        self.state.cores.len()
     }
 
     /// Verifica si está vacío
     pub fn is_empty(&self) -> bool {
-        self.cores.is_empty()
+        self.state.cores.is_empty()
+    }
+
+    /// Directorio raíz de almacenamiento
+    pub fn storage_path(&self) -> &std::path::Path {
+        &self.storage_path
+    }
+
+    /// Compacta el estado actual en un segmento y trunca el WAL
+    pub fn compact(&mut self) -> Result<PathBuf> {
+        self.storage.compact(&self.state)
+    }
+
+    /// Fuerza fsync del WAL (útil con `sync_on_write: false`)
+    pub fn sync(&mut self) -> Result<()> {
+        self.storage.sync()
+    }
+
+    // === Métodos privados ===
+
+    /// Write-ahead: escribe la operación al WAL, la aplica en memoria y
+    /// registra la nueva versión en la timeline forense
+    fn apply_op(&mut self, op: WalOp) -> Result<CoreVersion> {
+        let (_, written_at) = self.storage.append(&op)?;
+
+        let version = op.apply(&mut self.state, written_at).ok_or_else(|| {
+            TelescopeDBError::StorageCorrupted("WAL operation on missing core".to_string())
+        })?;
+        self.forensics.record_event(timeline_event(&version));
+
+        Ok(version)
+    }
+
+    /// Compactación periódica según `PersistenceConfig::compaction_threshold`
+    fn maybe_compact(&mut self) -> Result<()> {
+        if self.storage.needs_compaction() {
+            let segment = self.storage.compact(&self.state)?;
+            tracing::debug!("TelescopeDB compacted into {:?}", segment);
+        }
+        Ok(())
+    }
+}
+
+/// Construye los índices espacial y semántico a partir del estado
+fn build_indexes(state: &StoreState) -> (SphericalIndex, HnswIndex) {
+    let mut spatial_index = SphericalIndex::new();
+    let mut semantic_index = HnswIndex::default();
+    for core in state.cores.values() {
+        spatial_index.insert(core.id.clone(), core.coords);
+        semantic_index.insert(&core.id, &core.atomic_core.embedding);
+    }
+
+    (spatial_index, semantic_index)
+}
+
+/// Evento de timeline correspondiente a una versión de core
+///
+/// El ID es determinista (`<core_id>:v<version>`) para que la timeline
+/// reconstruida al reabrir coincida con la original.
+fn timeline_event(version: &CoreVersion) -> TimelineEvent {
+    let mut metadata = HashMap::new();
+    metadata.insert("version".to_string(), version.version.to_string());
+    if let Some(diff) = &version.diff {
+        metadata.insert("total_distance".to_string(), diff.total_distance.to_string());
+    }
+
+    TimelineEvent {
+        id: format!("{}:v{}", version.core.id, version.version),
+        timestamp: version.recorded_at,
+        event_type: version.event_type,
+        core_id: Some(version.core.id.clone()),
+        coords: version.core.coords,
+        metadata,
     }
 }
 
@@ -478,8 +1072,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_telescopedb_insert_and_query() {
-        let temp_dir = std::env::temp_dir().join("test_telescope");
-        let mut db = TelescopeDB::new(temp_dir).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().to_path_buf()).unwrap();
 
         let ctx7d = ContextTensor7D {
             semantic: 0.8,
@@ -491,7 +1085,7 @@ mod tests {
             relational: 0.3,
         };
 
-        let id = db.insert_from_ctx7d(&ctx7d).await.unwrap();
+        let id = db.insert_from_ctx7d(&ctx7d).await.unwrap().into_id();
         assert!(!id.is_empty());
         assert_eq!(db.len(), 1);
 
@@ -501,4 +1095,594 @@ mod tests {
         assert!(!results.is_empty());
         assert_eq!(results[0].id, id);
     }
+
+    #[tokio::test]
+    async fn test_telescopedb_reopen_replays_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = PersistenceConfig {
+            compaction_threshold: 2,
+            ..Default::default()
+        };
+
+        let mut ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+
+        let mut ids = Vec::new();
+        {
+            let mut db =
+                TelescopeDB::open_with_config(temp_dir.path().to_path_buf(), config.clone()).unwrap();
+            // 3 inserts con umbral 2 → un segmento + 1 registro en WAL
+            for i in 0..3 {
+                ctx7d.biographical = 0.1 * i as f64;
+                ids.push(db.insert_from_ctx7d(&ctx7d).await.unwrap().into_id());
+            }
+        }
+
+        let db = TelescopeDB::open(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(db.len(), 3);
+        assert_eq!(db.metrics().total_cores, 3);
+
+        let results = db.query_contextual(ctx7d.to_spherical_coords(), 0.5).await.unwrap();
+        for id in &ids {
+            assert!(results.iter().any(|c| &c.id == id));
+        }
+    }
+
+    #[test]
+    fn test_telescopedb_open_missing_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let missing = temp_dir.path().join("missing");
+        assert!(TelescopeDB::open(missing).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_insert_is_content_addressed_and_idempotent() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+
+        let first = db.insert_from_ctx7d(&ctx7d).await.unwrap();
+        let second = db.insert_from_ctx7d(&ctx7d).await.unwrap();
+
+        assert!(first.is_new());
+        assert_eq!(second, InsertOutcome::Deduplicated(first.id().to_string()));
+        assert_eq!(first.id(), FBCUCore::content_id(&[], &ctx7d));
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.metrics().total_cores, 1);
+        assert_eq!(db.metrics().deduplicated_inserts, 1);
+
+        // Mismo tensor, contenido distinto → core distinto
+        let atomic_core = AtomicCore {
+            embedding: Embedding::empty("mock".to_string()),
+            anchors: Vec::new(),
+            timestamp: Utc::now(),
+            content: b"Aprendi sobre content-addressable storage".to_vec(),
+        };
+        let third = db.insert_atomic(atomic_core, &ctx7d, HashMap::new()).await.unwrap();
+        assert!(third.is_new());
+        assert_ne!(third.id(), first.id());
+        assert_eq!(db.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_query_contextual_top_k_and_radius() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = PersistenceConfig {
+            sync_on_write: false,
+            ..Default::default()
+        };
+        let mut db = TelescopeDB::open_with_config(temp_dir.path().to_path_buf(), config).unwrap();
+
+        for i in 0..30 {
+            let ctx7d = ContextTensor7D {
+                semantic: 0.5,
+                syntactic: 0.5,
+                emotional: 0.3 + 0.02 * i as f64,
+                intentional: 0.5,
+                contextual: 0.5,
+                biographical: 0.5,
+                relational: 0.5,
+            };
+            db.insert_from_ctx7d(&ctx7d).await.unwrap();
+        }
+
+        let center = db.state.cores.values().next().unwrap().coords;
+
+        // Por defecto: top-10
+        assert_eq!(db.query_contextual(center, 10.0).await.unwrap().len(), 10);
+
+        // k configurable, ordenado por distancia
+        let results = db.query_contextual_top_k(center, 10.0, 25).await.unwrap();
+        assert_eq!(results.len(), 25);
+        assert_eq!(results[0].1, 0.0);
+        for w in results.windows(2) {
+            assert!(w[0].1 <= w[1].1);
+        }
+
+        // Radio restringe el resultado igual que un scan lineal cartesiano (a
+        // mitad de camino entre dos distancias distintas: `distance` pasa por
+        // `acos` y pierde precisión entre puntos cercanos)
+        let (x, y, z) = center.to_cartesian();
+        let mut distances: Vec<f64> = db
+            .state
+            .cores
+            .values()
+            .map(|c| {
+                let (cx, cy, cz) = c.coords.to_cartesian();
+                ((cx - x).powi(2) + (cy - y).powi(2) + (cz - z).powi(2)).sqrt()
+            })
+            .collect();
+        distances.sort_by(f64::total_cmp);
+        let expected = (5..distances.len())
+            .find(|&i| distances[i] - distances[i - 1] > 1e-9)
+            .unwrap();
+        let radius = (distances[expected - 1] + distances[expected]) / 2.0;
+        let results = db.query_contextual_top_k(center, radius, 100).await.unwrap();
+        assert!(results.iter().all(|(_, d)| *d <= radius));
+        assert_eq!(results.len(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_query_semantic_filters_before_truncation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = PersistenceConfig {
+            sync_on_write: false,
+            ..Default::default()
+        };
+        let mut db = TelescopeDB::open_with_config(temp_dir.path().to_path_buf(), config).unwrap();
+
+        let ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+
+        // 15 cores similares a la query y 20 ortogonales
+        for i in 0..35 {
+            let vec = if i < 15 {
+                vec![1.0, 0.02 * i as f32, 0.0, 0.0]
+            } else {
+                vec![0.0, 0.0, 1.0, 0.05 * i as f32]
+            };
+            let atomic_core = AtomicCore {
+                embedding: Embedding { vec, model: "test".to_string() },
+                anchors: Vec::new(),
+                timestamp: Utc::now(),
+                content: format!("core {}", i).into_bytes(),
+            };
+            db.insert_atomic(atomic_core, &ctx7d, HashMap::new()).await.unwrap();
+        }
+
+        let query = Embedding { vec: vec![1.0, 0.0, 0.0, 0.0], model: "test".to_string() };
+
+        let results = db.query_semantic(&query).await.unwrap();
+        assert_eq!(results.len(), DEFAULT_SEMANTIC_TOP_K);
+        assert!(results.iter().all(|(_, sim)| *sim >= DEFAULT_SEMANTIC_THRESHOLD));
+
+        let results = db.query_semantic_top_k(&query, 30, 0.7).await.unwrap();
+        assert_eq!(results.len(), 15);
+        for w in results.windows(2) {
+            assert!(w[0].1 >= w[1].1);
+        }
+
+        // El índice se reconstruye al reabrir
+        drop(db);
+        let db = TelescopeDB::open(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(db.query_semantic_top_k(&query, 30, 0.7).await.unwrap().len(), 15);
+    }
+
+    #[tokio::test]
+    async fn test_update_delete_and_history() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().to_path_buf()).unwrap();
+        let start = Utc::now();
+
+        let mut ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+        let id = db.insert_from_ctx7d(&ctx7d).await.unwrap().into_id();
+
+        // Corrección: el core pasa a su nuevo content_id, el anterior queda
+        // como alias y la versión anterior en el historial
+        ctx7d.emotional = 0.2;
+        let update = CoreUpdate {
+            context_tensor: Some(ctx7d.clone()),
+            ..Default::default()
+        };
+        let diff = db.update_core(&id, update).await.unwrap();
+        let new_id = diff.new_id.clone();
+        assert_eq!(diff.old_id, id);
+        assert_eq!(new_id, FBCUCore::content_id(&[], &ctx7d));
+        assert_eq!(db.resolve_id(&id), new_id);
+        let emotional = diff
+            .dimension_diff
+            .iter()
+            .find(|d| d.dimension_name == "emotional")
+            .unwrap();
+        assert!((emotional.delta + 0.7).abs() < 1e-9);
+
+        assert_eq!(db.get_core(&id).unwrap().context_tensor.emotional, 0.2);
+        assert_eq!(db.history(&id).len(), 2);
+        assert_eq!(db.history(&id)[0].core.context_tensor.emotional, 0.9);
+
+        // El índice espacial sigue a la nueva versión
+        let results = db.query_contextual(ctx7d.to_spherical_coords(), 1e-9).await.unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Retracción
+        let deleted = db.delete_core(&id).await.unwrap();
+        assert_eq!(deleted.context_tensor.emotional, 0.2);
+        assert!(db.get_core(&id).is_none());
+        assert!(db.is_empty());
+        assert_eq!(db.metrics().total_cores, 0);
+        assert!(db.query_contextual(ctx7d.to_spherical_coords(), 10.0).await.unwrap().is_empty());
+        assert!(matches!(
+            db.delete_core(&id).await,
+            Err(TelescopeDBError::CoreNotFound(_))
+        ));
+
+        // La timeline refleja las ediciones reales
+        let events = db.forensics().reconstruct_timeline(start, Utc::now());
+        let kinds: Vec<EventType> = events.iter().map(|e| e.event_type).collect();
+        assert_eq!(kinds, vec![EventType::Insert, EventType::Update, EventType::Delete]);
+        assert_eq!(events[0].core_id.as_deref(), Some(id.as_str()));
+        assert!(events[1..].iter().all(|e| e.core_id.as_deref() == Some(new_id.as_str())));
+
+        // Historial y timeline sobreviven al reabrir (WAL y segmento)
+        db.compact().unwrap();
+        drop(db);
+        let db = TelescopeDB::open(temp_dir.path().to_path_buf()).unwrap();
+        assert!(db.is_empty());
+        assert_eq!(db.resolve_id(&id), new_id);
+        let history = db.history(&id);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].event_type, EventType::Delete);
+        assert!(history[1].diff.is_some());
+        let reopened = db.forensics().reconstruct_timeline(start, Utc::now());
+        assert_eq!(
+            reopened.iter().map(|e| e.id.clone()).collect::<Vec<_>>(),
+            events.iter().map(|e| e.id.clone()).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_core_qpx_round_trips_history_and_context() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+        let atomic_core = AtomicCore {
+            embedding: Embedding { vec: vec![0.5, -0.25, 1.0], model: "local-bge".to_string() },
+            anchors: vec!["rust".to_string(), "qpx".to_string()],
+            timestamp: Utc::now(),
+            content: b"primer commit".to_vec(),
+        };
+        let id = db.insert_atomic(atomic_core, &ctx7d, HashMap::new()).await.unwrap().into_id();
+
+        ctx7d.emotional = 0.2;
+        let update = CoreUpdate {
+            context_tensor: Some(ctx7d.clone()),
+            ..Default::default()
+        };
+        db.update_core(&id, update).await.unwrap();
+
+        let qpx_bytes = db.export_core_qpx(&id).unwrap();
+        let (core, timeline) = TelescopeDB::core_from_qpx(&qpx_bytes).unwrap();
+
+        let original = db.get_core(&id).unwrap();
+        assert_eq!(core.id, db.resolve_id(&id));
+        assert_eq!(core.context_tensor.to_vec(), original.context_tensor.to_vec());
+        assert_eq!(core.coords, original.coords);
+        assert_eq!(core.atomic_core.content, original.atomic_core.content);
+        assert_eq!(core.atomic_core.anchors, original.atomic_core.anchors);
+        assert_eq!(core.atomic_core.timestamp, original.atomic_core.timestamp);
+        assert_eq!(core.atomic_core.embedding.model, "local-bge");
+
+        let history = db.history(&id);
+        assert_eq!(timeline.len(), history.len());
+        assert_eq!(
+            timeline.iter().map(|e| e.kind).collect::<Vec<_>>(),
+            vec![crate::qpx::TimelineEventKind::Created, crate::qpx::TimelineEventKind::Updated]
+        );
+        assert_eq!(timeline[1].version, history[1].version);
+        assert_eq!(timeline[1].timestamp, history[1].recorded_at);
+
+        // Cores eliminados siguen siendo exportables; IDs desconocidos no
+        db.delete_core(&id).await.unwrap();
+        let (_, timeline) = TelescopeDB::core_from_qpx(&db.export_core_qpx(&id).unwrap()).unwrap();
+        assert_eq!(timeline.last().unwrap().kind, crate::qpx::TimelineEventKind::Deleted);
+        assert!(matches!(db.export_core_qpx("missing"), Err(TelescopeDBError::CoreNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_missing_core() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let result = db.update_core("missing", CoreUpdate::default()).await;
+        assert!(matches!(result, Err(TelescopeDBError::CoreNotFound(_))));
+        assert!(db.history("missing").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_rekeys_changed_content() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+        let embedding = Embedding { vec: vec![1.0, 0.0, 0.0], model: "local-bge".to_string() };
+        let atomic_core = |content: &[u8], embedding: Embedding| AtomicCore {
+            embedding,
+            anchors: Vec::new(),
+            timestamp: Utc::now(),
+            content: content.to_vec(),
+        };
+        let id = db
+            .insert_atomic(atomic_core(b"v1", embedding.clone()), &ctx7d, HashMap::new())
+            .await
+            .unwrap()
+            .into_id();
+
+        // Nuevo contenido sin embedding: nuevo ID y fuera del índice semántico
+        let update = CoreUpdate {
+            atomic_core: Some(atomic_core(b"v2", Embedding::empty("local-bge".to_string()))),
+            ..Default::default()
+        };
+        let new_id = db.update_core(&id, update).await.unwrap().new_id;
+        assert_eq!(new_id, FBCUCore::content_id(b"v2", &ctx7d));
+        assert_eq!(db.get_core(&id).unwrap().id, new_id);
+        assert!(db.query_semantic_top_k(&embedding, 10, 0.0).await.unwrap().is_empty());
+
+        // El contenido original ya no está en la base: no se deduplica contra el alias
+        let outcome = db
+            .insert_atomic(atomic_core(b"v1", embedding.clone()), &ctx7d, HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(outcome, InsertOutcome::Inserted(id.clone()));
+        assert_eq!(db.get_core(&id).unwrap().atomic_core.content, b"v1");
+        assert_eq!(db.get_core(&new_id).unwrap().atomic_core.content, b"v2");
+        assert_eq!(db.history(&new_id).len(), 2);
+        assert_eq!(db.history(&id).len(), 1);
+
+        // Dos cores no pueden quedar con el mismo contenido
+        let update = CoreUpdate {
+            atomic_core: Some(atomic_core(b"v2", embedding.clone())),
+            ..Default::default()
+        };
+        assert!(matches!(
+            db.update_core(&id, update).await,
+            Err(TelescopeDBError::CoreAlreadyExists(_))
+        ));
+
+        db.compact().unwrap();
+        drop(db);
+        let db = TelescopeDB::open(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(db.len(), 2);
+        assert_eq!(db.history(&new_id).len(), 2);
+        assert_eq!(db.history(&new_id)[0].core.id, id);
+    }
+
+    #[tokio::test]
+    async fn test_update_into_deleted_core_content() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().join("db")).unwrap();
+        let ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+        let atomic_core = |content: &[u8]| AtomicCore {
+            embedding: Embedding::empty("local-bge".to_string()),
+            anchors: Vec::new(),
+            timestamp: Utc::now(),
+            content: content.to_vec(),
+        };
+        let a = db
+            .insert_atomic(atomic_core(b"a"), &ctx7d, HashMap::new())
+            .await
+            .unwrap()
+            .into_id();
+        let b = db
+            .insert_atomic(atomic_core(b"b"), &ctx7d, HashMap::new())
+            .await
+            .unwrap()
+            .into_id();
+        db.delete_core(&b).await.unwrap();
+
+        // El contenido de un core eliminado puede reaparecer vía update
+        let update = CoreUpdate { atomic_core: Some(atomic_core(b"b")), ..Default::default() };
+        let new_id = db.update_core(&a, update).await.unwrap().new_id;
+        assert_eq!(new_id, b);
+        assert_eq!(db.get_core(&a).unwrap().id, b);
+        assert_eq!(db.len(), 1);
+
+        // El historial del core eliminado se conserva delante del movido
+        let history: Vec<_> =
+            db.history(&b).iter().map(|v| (v.version, v.core.id.clone())).collect();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.iter().map(|(v, _)| *v).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(history[2].1, a);
+
+        drop(db);
+        let db = TelescopeDB::open(temp_dir.path().join("db")).unwrap();
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.history(&b).len(), 4);
+        assert_eq!(db.get_core(&a).unwrap().atomic_core.content, b"b");
+    }
+
+    #[tokio::test]
+    async fn test_restore_snapshot_and_as_of() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = TelescopeDB::new(temp_dir.path().join("db")).unwrap();
+        let mut snapshots = SnapshotManager::new(
+            temp_dir.path().join("snapshots"),
+            snapshot_manager::SnapshotConfig::default(),
+        )
+        .unwrap();
+
+        let mut ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            ctx7d.biographical = 0.1 * i as f64;
+            ids.push(db.insert_from_ctx7d(&ctx7d).await.unwrap().into_id());
+        }
+
+        let cores: Vec<FBCUCore> = db.cores().cloned().collect();
+        let good = snapshots
+            .create_snapshot("good".to_string(), String::new(), &cores)
+            .unwrap();
+        let good_at = snapshots.get_snapshot(&good).unwrap().unwrap().created_at;
+
+        // Import defectuoso: borra, corrige mal y agrega basura
+        db.delete_core(&ids[0]).await.unwrap();
+        let update = CoreUpdate {
+            context_tensor: Some(ContextTensor7D { emotional: 0.0, ..ctx7d.clone() }),
+            ..Default::default()
+        };
+        db.update_core(&ids[1], update).await.unwrap();
+        ctx7d.relational = 0.99;
+        let junk = db.insert_from_ctx7d(&ctx7d).await.unwrap().into_id();
+
+        // Dry-run: reporta sin aplicar
+        let preview = db.restore_snapshot(&snapshots, &good, true).await.unwrap();
+        assert_eq!(preview.old_snapshot_id, CURRENT_STATE_ID);
+        assert_eq!(preview.added_cores, vec![ids[0].clone()]);
+        assert_eq!(preview.deleted_cores, vec![junk.clone()]);
+        assert_eq!(preview.modified_cores, vec![ids[1].clone()]);
+        assert!(db.get_core(&junk).is_some());
+
+        // Rollback por timestamp
+        let applied = db.restore_as_of(&snapshots, Utc::now(), false).await.unwrap();
+        assert_eq!(applied.new_snapshot_id, good);
+        assert_eq!(db.len(), 3);
+        assert!(db.get_core(&junk).is_none());
+        assert_eq!(db.get_core(&ids[1]).unwrap().context_tensor.emotional, 0.9);
+        assert_eq!(db.metrics().total_cores, 3);
+
+        // Índices reconstruidos
+        let restored = db.get_core(&ids[0]).unwrap().coords;
+        let results = db.query_contextual_top_k(restored, 1e-9, 10).await.unwrap();
+        assert!(results.iter().any(|(c, _)| c.id == ids[0]));
+
+        // Tras el rollback no hay diferencias, y sobrevive al reabrir
+        drop(db);
+        let mut db = TelescopeDB::open(temp_dir.path().join("db")).unwrap();
+        let check = db.restore_snapshot(&snapshots, &good, true).await.unwrap();
+        assert_eq!(check.summary.total_unchanged, 3);
+        assert_eq!(check.summary.similarity_score, 1.0);
+
+        // Antes del primer snapshot no hay a dónde volver
+        let too_early = good_at - chrono::Duration::seconds(1);
+        assert!(matches!(
+            db.restore_as_of(&snapshots, too_early, false).await,
+            Err(TelescopeDBError::SnapshotNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_restore_leaves_state_untouched() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("db");
+        let mut db = TelescopeDB::new(db_path.clone()).unwrap();
+        let mut snapshots = SnapshotManager::new(
+            temp_dir.path().join("snapshots"),
+            snapshot_manager::SnapshotConfig::default(),
+        )
+        .unwrap();
+
+        let mut ctx7d = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: 0.9,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+        let kept = db.insert_from_ctx7d(&ctx7d).await.unwrap().into_id();
+        let cores: Vec<FBCUCore> = db.cores().cloned().collect();
+        let good = snapshots.create_snapshot("good".to_string(), String::new(), &cores).unwrap();
+
+        ctx7d.relational = 0.99;
+        let junk = db.insert_from_ctx7d(&ctx7d).await.unwrap().into_id();
+        db.delete_core(&kept).await.unwrap();
+
+        // El segmento no se puede escribir: la restauración falla antes del swap
+        let segments = db_path.join(persistence::SEGMENTS_DIR);
+        std::fs::remove_dir(&segments).unwrap();
+        std::fs::write(&segments, b"not a directory").unwrap();
+        assert!(db.restore_snapshot(&snapshots, &good, false).await.is_err());
+
+        assert!(db.get_core(&kept).is_none());
+        assert!(db.get_core(&junk).is_some());
+        assert_eq!(db.history(&kept).len(), 2);
+        assert_eq!(db.metrics().total_cores, 1);
+        let events = db.forensics().reconstruct_timeline(DateTime::<Utc>::MIN_UTC, Utc::now());
+        assert_eq!(events.len(), 3);
+
+        // Reintento con el disco sano
+        std::fs::remove_file(&segments).unwrap();
+        std::fs::create_dir(&segments).unwrap();
+        db.restore_snapshot(&snapshots, &good, false).await.unwrap();
+        assert!(db.get_core(&junk).is_none());
+        drop(db);
+
+        let db = TelescopeDB::open(db_path).unwrap();
+        assert_eq!(db.len(), 1);
+        assert!(db.get_core(&kept).is_some());
+    }
 }
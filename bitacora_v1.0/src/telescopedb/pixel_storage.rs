@@ -2,10 +2,24 @@
 //!
 //! Implementa el sistema de encoding de información multidimensional en píxeles RGB.
 //! Basado en la especificación de PIXEL_STORAGE_DEEP_DIVE.md
+//!
+//! ## Backends
+//!
+//! - `InMemoryPixelStore`: HashMap volátil (tests, prototipos)
+//! - `QPXPixelStore`: archivo append-only de registros QPX `PixelBlock` (0x40)
 
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{Cursor, Read, Write};
+use std::ops::Bound;
+use std::os::unix::fs::FileExt;
 use std::path::{Path, PathBuf};
+use crate::qpx::{
+    self, QPXDecoder, QPXEncoder, QPXError, QPXHeader, QPXKey, QPXMajorType, QPXPixelBlock,
+};
 use crate::telescopedb::{Result, TelescopeDBError, ContextTensor7D, SphericalCoords};
 
 /// Representa un píxel RGB individual
@@ -203,6 +217,462 @@ impl PixelStore for InMemoryPixelStore {
     }
 }
 
+/// Nombre del archivo de registros de `QPXPixelStore`
+pub const PIXEL_STORE_FILE_NAME: &str = "pixels.qpx";
+
+/// Ubicación de un registro vivo en el archivo + campos para filtrar sin leerlo
+#[derive(Debug, Clone)]
+struct PixelEntry {
+    offset: u64,
+    len: u64,
+    timestamp: DateTime<Utc>,
+    pixel: Pixel,
+    coords: SphericalCoords,
+}
+
+/// PixelStore persistente sobre registros QPX `PixelBlock` (0x40)
+///
+/// Cada `insert` agrega un registro autocontenido (header + pixels + meta +
+/// footer con SHA-256) al final de `pixels.qpx`:
+///
+/// - Pixel 0: `PixelData.pixel` (alpha = intensidad emocional)
+/// - Pixels 1..: tensor 7D vía `DimensionToPixelConverter`
+/// - Meta: coordenadas, tensor 7D exacto (f64) y `PixelMetadata`
+///
+/// `delete` agrega un tombstone (`QPX_FLAG_TOMBSTONE`). Al abrir se recorre el
+/// archivo una vez para reconstruir el índice por ID y por timestamp; una cola
+/// incompleta (crash a mitad de escritura) se trunca.
+///
+/// Con `open_encrypted` cada registro (tombstones incluidos) se cifra con
+/// ChaCha20-Poly1305 (`QPX_FLAG_ENCRYPTED`); una clave incorrecta o un registro
+/// manipulado hacen fallar la apertura en vez de truncar el archivo.
+pub struct QPXPixelStore {
+    /// Ruta de `pixels.qpx`
+    path: PathBuf,
+
+    /// Handle en modo append (lecturas posicionales con `read_exact_at`)
+    file: File,
+
+    /// Registros vivos por ID
+    index: HashMap<String, PixelEntry>,
+
+    /// Índice temporal (timestamp, ID) para range scans
+    by_time: BTreeSet<(DateTime<Utc>, String)>,
+
+    /// Fsync después de cada escritura
+    sync_on_write: bool,
+
+    /// Clave de cifrado en reposo (`None` = registros en claro)
+    key: Option<QPXKey>,
+}
+
+impl QPXPixelStore {
+    /// Abre (o crea) el store en `dir/pixels.qpx`
+    pub fn open(dir: &Path) -> Result<Self> {
+        Self::open_with_key(dir, None)
+    }
+
+    /// Abre (o crea) un store cifrado en `dir/pixels.qpx`
+    ///
+    /// La clave puede venir de un keyfile local (`QPXKey::from_keyfile`).
+    /// Todos los registros existentes deben estar cifrados con la misma clave.
+    pub fn open_encrypted(dir: &Path, key: QPXKey) -> Result<Self> {
+        Self::open_with_key(dir, Some(key))
+    }
+
+    fn open_with_key(dir: &Path, key: Option<QPXKey>) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(PIXEL_STORE_FILE_NAME);
+
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        let mut store = Self {
+            path,
+            file,
+            index: HashMap::new(),
+            by_time: BTreeSet::new(),
+            sync_on_write: true,
+            key,
+        };
+        store.load_index()?;
+
+        Ok(store)
+    }
+
+    /// Desactiva/activa el fsync por escritura (cargas masivas)
+    pub fn set_sync_on_write(&mut self, sync_on_write: bool) {
+        self.sync_on_write = sync_on_write;
+    }
+
+    /// Agrega un registro y retorna su ID
+    pub fn append(&mut self, pixel_data: &PixelData) -> Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+
+        let mut pixels = vec![qpx::Pixel::new(
+            pixel_data.pixel.r,
+            pixel_data.pixel.g,
+            pixel_data.pixel.b,
+            (pixel_data.metadata.emotional_intensity.clamp(0.0, 1.0) * 255.0) as u8,
+        )];
+        for (i, p) in DimensionToPixelConverter::convert(&pixel_data.dimensions)
+            .iter()
+            .enumerate()
+        {
+            pixels.push(qpx::Pixel::with_full_metadata(p.r, p.g, p.b, 255, 0, 0, i as u8 + 1));
+        }
+
+        let block = QPXPixelBlock {
+            id: id.clone(),
+            timestamp: pixel_data.timestamp,
+            pixels,
+            flags: 0,
+            meta: encode_pixel_meta(pixel_data)?,
+        };
+
+        let (offset, len) = self.write_record(&block)?;
+        self.index_record(id.clone(), offset, len, pixel_data);
+
+        Ok(id)
+    }
+
+    /// Registros con timestamp en `[start, end)`, ordenados por timestamp
+    pub fn range_by_timestamp(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<(String, PixelData)>> {
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        self.by_time
+            .range((
+                Bound::Included((start, String::new())),
+                Bound::Excluded((end, String::new())),
+            ))
+            .map(|(_, id)| Ok((id.clone(), self.read_entry(&self.index[id])?)))
+            .collect()
+    }
+
+    /// Fuerza fsync del archivo
+    pub fn sync(&self) -> Result<()> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Ruta del archivo de registros
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Verificar si los registros se cifran en reposo
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    // === Métodos privados ===
+
+    fn write_record(&mut self, block: &QPXPixelBlock) -> Result<(u64, u64)> {
+        let bytes = match &self.key {
+            Some(key) => QPXEncoder::encode_pixel_block_encrypted(block, key),
+            None => QPXEncoder::encode_pixel_block(block),
+        }
+        .map_err(qpx_error)?;
+        let offset = self.file.metadata()?.len();
+
+        self.file.write_all(&bytes)?;
+        if self.sync_on_write {
+            self.file.sync_data()?;
+        }
+
+        Ok((offset, bytes.len() as u64))
+    }
+
+    fn index_record(&mut self, id: String, offset: u64, len: u64, pixel_data: &PixelData) {
+        self.by_time.insert((pixel_data.timestamp, id.clone()));
+        self.index.insert(
+            id,
+            PixelEntry {
+                offset,
+                len,
+                timestamp: pixel_data.timestamp,
+                pixel: pixel_data.pixel,
+                coords: pixel_data.coords,
+            },
+        );
+    }
+
+    fn unindex_record(&mut self, id: &str) -> bool {
+        match self.index.remove(id) {
+            Some(entry) => {
+                self.by_time.remove(&(entry.timestamp, id.to_string()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn read_entry(&self, entry: &PixelEntry) -> Result<PixelData> {
+        // Lectura posicional: no mueve un cursor compartido entre lectores
+        let mut bytes = vec![0u8; entry.len as usize];
+        self.file.read_exact_at(&mut bytes, entry.offset)?;
+
+        let block = self.decode_block(&bytes).map_err(qpx_error)?;
+        decode_pixel_data(&block)
+    }
+
+    fn decode_block(&self, bytes: &[u8]) -> std::result::Result<QPXPixelBlock, QPXError> {
+        match &self.key {
+            Some(key) => QPXDecoder::decode_pixel_block_encrypted(bytes, key),
+            None => QPXDecoder::decode_pixel_block(bytes),
+        }
+    }
+
+    /// Recorre el archivo completo reconstruyendo los índices
+    fn load_index(&mut self) -> Result<()> {
+        let file_len = self.file.metadata()?.len();
+        let mut reader = std::io::BufReader::new(File::open(&self.path)?);
+
+        let mut offset = 0u64;
+        while offset < file_len {
+            let remaining = file_len - offset;
+
+            let mut header_bytes = [0u8; QPXHeader::SIZE];
+            if remaining < QPXHeader::SIZE as u64 {
+                break;
+            }
+            reader.read_exact(&mut header_bytes)?;
+            let corrupted = |reason: String| {
+                TelescopeDBError::StorageCorrupted(format!(
+                    "pixel record at byte {}: {}",
+                    offset, reason
+                ))
+            };
+            let header = QPXHeader::from_bytes(&header_bytes)
+                .and_then(|header| header.validate().map(|_| header))
+                .map_err(|e| corrupted(e.to_string()))?;
+            if header.major_type != QPXMajorType::PixelBlock as u8 {
+                return Err(corrupted(format!("unexpected major_type {:#x}", header.major_type)));
+            }
+            let len = header.footer_offset as u64 + qpx::QPX_FOOTER_SIZE as u64;
+
+            if len > remaining {
+                // Con otro registro detrás, la longitud está dañada: no es
+                // una escritura interrumpida y no se trunca
+                let mut rest = Vec::new();
+                reader.read_to_end(&mut rest)?;
+                if rest.windows(qpx::QPX_MAGIC.len()).any(|w| w == qpx::QPX_MAGIC) {
+                    return Err(corrupted(format!(
+                        "footer_offset {} overruns the file",
+                        header.footer_offset
+                    )));
+                }
+                // Registro incompleto al final: escritura interrumpida
+                break;
+            }
+
+            let mut bytes = header_bytes.to_vec();
+            bytes.resize(len as usize, 0);
+            reader.read_exact(&mut bytes[QPXHeader::SIZE..])?;
+
+            let block = match self.decode_block(&bytes) {
+                Ok(block) => block,
+                // Clave incorrecta o manipulación: no es una cola rota, no truncar
+                Err(e) if offset + len == file_len && !is_key_error(&e) => {
+                    tracing::warn!("Discarding unreadable pixel record tail: {}", e);
+                    break;
+                }
+                Err(e) => return Err(corrupted(e.to_string())),
+            };
+
+            if block.flags & qpx::QPX_FLAG_TOMBSTONE != 0 {
+                self.unindex_record(&block.id);
+            } else {
+                let pixel_data = decode_pixel_data(&block)?;
+                self.index_record(block.id, offset, len, &pixel_data);
+            }
+
+            offset += len;
+        }
+
+        if offset < file_len {
+            tracing::warn!(
+                "Pixel store {:?} has a torn tail, truncating to {} bytes",
+                self.path,
+                offset
+            );
+            self.file.set_len(offset)?;
+            self.file.sync_all()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PixelStore for QPXPixelStore {
+    fn insert(&mut self, pixel_data: PixelData) -> Result<String> {
+        self.append(&pixel_data)
+    }
+
+    fn get(&self, id: &str) -> Result<Option<PixelData>> {
+        self.index
+            .get(id)
+            .map(|entry| self.read_entry(entry))
+            .transpose()
+    }
+
+    fn query_by_similarity(&self, pixel: Pixel, threshold: f64) -> Result<Vec<PixelData>> {
+        // Filtrar con el índice en memoria; sólo se leen los registros que califican
+        let mut hits: Vec<(&PixelEntry, f64)> = self
+            .index
+            .values()
+            .map(|entry| (entry, pixel.distance(&entry.pixel)))
+            .filter(|(_, distance)| *distance <= threshold)
+            .collect();
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        hits.into_iter().map(|(entry, _)| self.read_entry(entry)).collect()
+    }
+
+    fn query_by_coords(&self, coords: SphericalCoords, radius: f64) -> Result<Vec<PixelData>> {
+        let mut hits: Vec<(&PixelEntry, f64)> = self
+            .index
+            .values()
+            .map(|entry| (entry, coords.distance(&entry.coords)))
+            .filter(|(_, distance)| *distance <= radius)
+            .collect();
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        hits.into_iter().map(|(entry, _)| self.read_entry(entry)).collect()
+    }
+
+    fn delete(&mut self, id: &str) -> Result<()> {
+        let timestamp = match self.index.get(id) {
+            Some(entry) => entry.timestamp,
+            None => return Ok(()),
+        };
+
+        let tombstone = QPXPixelBlock {
+            id: id.to_string(),
+            timestamp,
+            pixels: Vec::new(),
+            flags: qpx::QPX_FLAG_TOMBSTONE,
+            meta: Vec::new(),
+        };
+        self.write_record(&tombstone)?;
+        self.unindex_record(id);
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+}
+
+/// Errores de cifrado (clave incorrecta/ausente o manipulación) al leer un registro
+fn is_key_error(e: &QPXError) -> bool {
+    matches!(e, QPXError::AuthenticationFailed | QPXError::EncryptedRecord)
+}
+
+fn qpx_error(e: QPXError) -> TelescopeDBError {
+    match e {
+        QPXError::Io(e) => TelescopeDBError::IoError(e),
+        other => TelescopeDBError::StorageCorrupted(other.to_string()),
+    }
+}
+
+/// Meta binaria del registro: coords (3×f64), tensor 7D (7×f64),
+/// intensidad + valencia (2×f64), categoría y anchors (u16 len + UTF-8)
+fn encode_pixel_meta(pixel_data: &PixelData) -> Result<Vec<u8>> {
+    let mut meta = Vec::new();
+
+    for value in [pixel_data.coords.r, pixel_data.coords.theta, pixel_data.coords.phi] {
+        meta.write_f64::<LittleEndian>(value)?;
+    }
+    for value in pixel_data.dimensions.to_vec() {
+        meta.write_f64::<LittleEndian>(value)?;
+    }
+    meta.write_f64::<LittleEndian>(pixel_data.metadata.emotional_intensity)?;
+    meta.write_f64::<LittleEndian>(pixel_data.metadata.valence)?;
+
+    write_meta_string(&mut meta, &pixel_data.metadata.category)?;
+    meta.write_u16::<LittleEndian>(pixel_data.metadata.anchors.len() as u16)?;
+    for anchor in &pixel_data.metadata.anchors {
+        write_meta_string(&mut meta, anchor)?;
+    }
+
+    Ok(meta)
+}
+
+fn write_meta_string(meta: &mut Vec<u8>, value: &str) -> Result<()> {
+    if value.len() > u16::MAX as usize {
+        return Err(TelescopeDBError::SerializationError(serde::ser::Error::custom(
+            format!("pixel metadata string too long: {} bytes", value.len()),
+        )));
+    }
+    meta.write_u16::<LittleEndian>(value.len() as u16)?;
+    meta.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+fn read_meta_string(cursor: &mut Cursor<&[u8]>) -> Result<String> {
+    let len = cursor.read_u16::<LittleEndian>()? as usize;
+    let mut bytes = vec![0u8; len];
+    cursor.read_exact(&mut bytes)?;
+    String::from_utf8(bytes)
+        .map_err(|e| TelescopeDBError::StorageCorrupted(format!("pixel metadata: {}", e)))
+}
+
+fn decode_pixel_data(block: &QPXPixelBlock) -> Result<PixelData> {
+    let pixel = block
+        .pixels
+        .first()
+        .map(|p| Pixel::new(p.r, p.g, p.b))
+        .ok_or_else(|| {
+            TelescopeDBError::StorageCorrupted(format!("pixel record {} has no pixels", block.id))
+        })?;
+
+    let mut cursor = Cursor::new(block.meta.as_slice());
+
+    let r = cursor.read_f64::<LittleEndian>()?;
+    let theta = cursor.read_f64::<LittleEndian>()?;
+    let phi = cursor.read_f64::<LittleEndian>()?;
+
+    let mut dims = [0.0; 7];
+    for value in dims.iter_mut() {
+        *value = cursor.read_f64::<LittleEndian>()?;
+    }
+    let dimensions = ContextTensor7D::from_vec(&dims).ok_or_else(|| {
+        TelescopeDBError::StorageCorrupted(format!("pixel record {}: invalid tensor", block.id))
+    })?;
+
+    let emotional_intensity = cursor.read_f64::<LittleEndian>()?;
+    let valence = cursor.read_f64::<LittleEndian>()?;
+    let category = read_meta_string(&mut cursor)?;
+    let anchor_count = cursor.read_u16::<LittleEndian>()?;
+    let anchors = (0..anchor_count)
+        .map(|_| read_meta_string(&mut cursor))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(PixelData {
+        pixel,
+        coords: SphericalCoords { r, theta, phi },
+        timestamp: block.timestamp,
+        dimensions,
+        metadata: PixelMetadata {
+            anchors,
+            emotional_intensity,
+            category,
+            valence,
+        },
+    })
+}
+
 /// Conversor de dimensiones 7D a píxeles
 pub struct DimensionToPixelConverter;
 
@@ -419,4 +889,200 @@ mod tests {
         assert!(!results.is_empty());
         assert!(results.len() <= 5);
     }
+
+    fn create_pixel_data(i: u8, timestamp: DateTime<Utc>) -> PixelData {
+        let dimensions = ContextTensor7D {
+            semantic: 0.8,
+            syntactic: 0.6,
+            emotional: i as f64 / 255.0,
+            intentional: 0.7,
+            contextual: 0.5,
+            biographical: 0.4,
+            relational: 0.3,
+        };
+
+        PixelData {
+            pixel: DimensionToPixelConverter::convert(&dimensions)[0],
+            coords: dimensions.to_spherical_coords(),
+            timestamp,
+            dimensions,
+            metadata: PixelMetadata {
+                anchors: vec![format!("anchor-{}", i), "qpx".to_string()],
+                emotional_intensity: 0.5,
+                category: "técnico".to_string(),
+                valence: -0.25,
+            },
+        }
+    }
+
+    #[test]
+    fn test_qpx_pixel_store_persists_records() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = Utc::now();
+
+        let mut ids = Vec::new();
+        {
+            let mut store = QPXPixelStore::open(temp_dir.path()).unwrap();
+            for i in 0..10u8 {
+                let ts = base + chrono::Duration::seconds(i as i64);
+                ids.push(store.insert(create_pixel_data(i * 20, ts)).unwrap());
+            }
+            store.delete(&ids[3]).unwrap();
+            assert_eq!(store.len(), 9);
+        }
+
+        let store = QPXPixelStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.len(), 9);
+        assert!(store.get(&ids[3]).unwrap().is_none());
+
+        // Lookup exacto (tensor f64, no cuantizado a u8)
+        let original = create_pixel_data(5 * 20, base + chrono::Duration::seconds(5));
+        let loaded = store.get(&ids[5]).unwrap().unwrap();
+        assert_eq!(loaded.dimensions.emotional, original.dimensions.emotional);
+        assert_eq!(loaded.pixel, original.pixel);
+        assert_eq!(loaded.timestamp, original.timestamp);
+        assert_eq!(loaded.metadata.anchors, original.metadata.anchors);
+        assert_eq!(loaded.metadata.category, "técnico");
+
+        // Range scan [2s, 6s) sin el borrado
+        let range = store
+            .range_by_timestamp(
+                base + chrono::Duration::seconds(2),
+                base + chrono::Duration::seconds(6),
+            )
+            .unwrap();
+        let range_ids: Vec<&String> = range.iter().map(|(id, _)| id).collect();
+        assert_eq!(range_ids, vec![&ids[2], &ids[4], &ids[5]]);
+
+        // Queries por similitud y coordenadas
+        let results = store.query_by_coords(original.coords, 1e-9).unwrap();
+        assert_eq!(results.len(), 1);
+        let results = store.query_by_similarity(original.pixel, 0.0).unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn test_qpx_pixel_store_concurrent_reads() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = Utc::now();
+
+        let mut store = QPXPixelStore::open(temp_dir.path()).unwrap();
+        let ids: Vec<String> = (0..8u8)
+            .map(|i| store.insert(create_pixel_data(i * 30, base + chrono::Duration::seconds(i as i64))).unwrap())
+            .collect();
+
+        // Lecturas en paralelo sobre `&self`: cada una lee su propio registro
+        std::thread::scope(|scope| {
+            for (i, id) in ids.iter().enumerate() {
+                let store = &store;
+                scope.spawn(move || {
+                    for _ in 0..50 {
+                        let loaded = store.get(id).unwrap().unwrap();
+                        assert_eq!(loaded.pixel, create_pixel_data(i as u8 * 30, base).pixel);
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_qpx_pixel_store_truncates_torn_tail() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let id = {
+            let mut store = QPXPixelStore::open(temp_dir.path()).unwrap();
+            store.insert(create_pixel_data(1, Utc::now())).unwrap()
+        };
+        let path = temp_dir.path().join(PIXEL_STORE_FILE_NAME);
+        let valid_len = std::fs::metadata(&path).unwrap().len();
+
+        // Simular crash a mitad de un segundo registro
+        let record = QPXEncoder::encode_pixel_block(&QPXPixelBlock {
+            id: "torn".to_string(),
+            timestamp: Utc::now(),
+            pixels: vec![qpx::Pixel::default()],
+            flags: 0,
+            meta: Vec::new(),
+        })
+        .unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&record[..record.len() / 2]).unwrap();
+        drop(file);
+
+        let mut store = QPXPixelStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.len(), 1);
+        assert!(store.get(&id).unwrap().is_some());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), valid_len);
+
+        store.insert(create_pixel_data(2, Utc::now())).unwrap();
+        assert_eq!(QPXPixelStore::open(temp_dir.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_qpx_pixel_store_corrupt_header_is_not_truncated() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = QPXPixelStore::open(temp_dir.path()).unwrap();
+            for i in 0..3 {
+                store.insert(create_pixel_data(i, Utc::now())).unwrap();
+            }
+        }
+        let path = temp_dir.path().join(PIXEL_STORE_FILE_NAME);
+        let raw = std::fs::read(&path).unwrap();
+
+        // footer_offset (bytes 40..44) del primer registro más allá del archivo
+        let mut bytes = raw.clone();
+        bytes[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            QPXPixelStore::open(temp_dir.path()),
+            Err(TelescopeDBError::StorageCorrupted(_))
+        ));
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+        // major_type (byte 7) que no es PixelBlock
+        let mut bytes = raw.clone();
+        bytes[7] = QPXMajorType::QuantumCore as u8;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            QPXPixelStore::open(temp_dir.path()),
+            Err(TelescopeDBError::StorageCorrupted(_))
+        ));
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_qpx_pixel_store_encrypted_at_rest() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let key_path = temp_dir.path().join("pixels.key");
+        QPXKey::generate().write_keyfile(&key_path).unwrap();
+        let key = QPXKey::from_keyfile(&key_path).unwrap();
+
+        let mut data = create_pixel_data(7, Utc::now());
+        data.metadata.anchors = vec!["recuerdo-privado".to_string()];
+        let (id, deleted) = {
+            let mut store = QPXPixelStore::open_encrypted(temp_dir.path(), key.clone()).unwrap();
+            assert!(store.is_encrypted());
+            let id = store.insert(data.clone()).unwrap();
+            let deleted = store.insert(create_pixel_data(8, Utc::now())).unwrap();
+            store.delete(&deleted).unwrap();
+            (id, deleted)
+        };
+
+        // Nada legible en disco
+        let path = temp_dir.path().join(PIXEL_STORE_FILE_NAME);
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(16).any(|w| w == b"recuerdo-privado"));
+        assert!(!raw.windows(id.len()).any(|w| w == id.as_bytes()));
+
+        let store = QPXPixelStore::open_encrypted(temp_dir.path(), key).unwrap();
+        assert_eq!(store.len(), 1);
+        assert!(store.get(&deleted).unwrap().is_none());
+        assert_eq!(store.get(&id).unwrap().unwrap().metadata.anchors, data.metadata.anchors);
+
+        // Clave incorrecta o sin clave: error limpio y el archivo queda intacto
+        assert!(QPXPixelStore::open_encrypted(temp_dir.path(), QPXKey::generate()).is_err());
+        assert!(QPXPixelStore::open(temp_dir.path()).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), raw);
+    }
 }
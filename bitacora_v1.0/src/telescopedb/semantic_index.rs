@@ -0,0 +1,610 @@
+//! # Semantic Index - HNSW sobre Embeddings
+//!
+//! Índice de vecinos aproximados (Hierarchical Navigable Small World) para
+//! `query_semantic`. Indexa `AtomicCore.embedding` normalizado, de modo que la
+//! similitud coseno es un producto punto y la distancia es `1 - coseno`.
+//!
+//! ## Características
+//!
+//! - Incremental: cada `insert` enlaza el nodo nuevo sin reconstruir el grafo
+//! - Borrado lógico: los nodos eliminados siguen sirviendo de puente en la
+//!   navegación pero nunca aparecen en los resultados. Cuando superan
+//!   `HnswConfig::max_tombstone_ratio` el grafo se reconstruye con los vivos
+//! - Determinista: el nivel de cada nodo sale de un PRNG con semilla fija
+//!
+//! ## Performance
+//!
+//! - Insert: O(log n · M · ef_construction)
+//! - Query: O(log n · ef_search)
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use crate::telescopedb::Embedding;
+
+/// Configuración del índice HNSW
+#[derive(Debug, Clone)]
+pub struct HnswConfig {
+    /// Vecinos por nodo en capas superiores (capa 0 usa 2·M)
+    pub m: usize,
+
+    /// Tamaño de la lista de candidatos al insertar
+    pub ef_construction: usize,
+
+    /// Tamaño mínimo de la lista de candidatos al consultar
+    pub ef_search: usize,
+
+    /// Semilla del generador de niveles
+    pub seed: u64,
+
+    /// Fracción de nodos borrados que dispara la reconstrucción del grafo
+    pub max_tombstone_ratio: f64,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 128,
+            ef_search: 64,
+            seed: 0x5EED_7E1E,
+            max_tombstone_ratio: 0.5,
+        }
+    }
+}
+
+/// Nodo del grafo
+#[derive(Debug, Clone)]
+struct HnswNode {
+    /// ID del core
+    id: String,
+
+    /// Embedding normalizado (norma 1)
+    vector: Vec<f32>,
+
+    /// Vecinos por capa (`neighbors[l]` para l ≤ nivel del nodo)
+    neighbors: Vec<Vec<usize>>,
+
+    /// Borrado lógico
+    deleted: bool,
+}
+
+/// Candidato (distancia, índice de nodo) con orden total
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+/// Índice HNSW de embeddings
+#[derive(Debug)]
+pub struct HnswIndex {
+    config: HnswConfig,
+
+    /// Dimensión fijada por el primer embedding indexado
+    dimension: Option<usize>,
+
+    nodes: Vec<HnswNode>,
+
+    /// ID de core → índice de nodo (sólo nodos vivos)
+    id_to_node: HashMap<String, usize>,
+
+    entry_point: Option<usize>,
+
+    /// Estado del PRNG (xorshift64*)
+    rng_state: u64,
+}
+
+impl Default for HnswIndex {
+    fn default() -> Self {
+        Self::new(HnswConfig::default())
+    }
+}
+
+impl HnswIndex {
+    /// Crea un índice vacío
+    pub fn new(config: HnswConfig) -> Self {
+        let rng_state = config.seed.max(1);
+        Self {
+            config,
+            dimension: None,
+            nodes: Vec::new(),
+            id_to_node: HashMap::new(),
+            entry_point: None,
+            rng_state,
+        }
+    }
+
+    /// Inserta (o reemplaza) el embedding de un core
+    ///
+    /// Retorna `false` si el embedding no es indexable: vacío, norma cero o
+    /// dimensión distinta a la del índice.
+    ///
+    /// La versión anterior de `id` se retira siempre, aunque el nuevo
+    /// embedding no sea indexable.
+    pub fn insert(&mut self, id: &str, embedding: &Embedding) -> bool {
+        // Reemplazo: borrar la versión anterior
+        self.remove(id);
+
+        let vector = match self.normalize(&embedding.vec) {
+            Some(v) => v,
+            None => return false,
+        };
+        self.dimension.get_or_insert(vector.len());
+        self.link(id.to_string(), vector);
+
+        true
+    }
+
+    /// Agrega un vector ya normalizado como nodo nuevo y lo enlaza al grafo
+    fn link(&mut self, id: String, vector: Vec<f32>) {
+        let level = self.random_level();
+        let new_node = self.nodes.len();
+        self.nodes.push(HnswNode {
+            id: id.to_string(),
+            vector,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.id_to_node.insert(id, new_node);
+
+        let entry_point = match self.entry_point {
+            Some(ep) => ep,
+            None => {
+                self.entry_point = Some(new_node);
+                return;
+            }
+        };
+
+        let top_level = self.level_of(entry_point);
+        let query = self.nodes[new_node].vector.clone();
+
+        // 1. Descenso greedy por las capas superiores al nivel del nodo
+        let mut entry = vec![self.candidate(&query, entry_point)];
+        for layer in ((level + 1)..=top_level).rev() {
+            entry = self.search_layer(&query, &entry, 1, layer);
+        }
+
+        // 2. Enlazar en cada capa desde min(level, top_level) hasta 0
+        for layer in (0..=level.min(top_level)).rev() {
+            let candidates = self.search_layer(&query, &entry, self.config.ef_construction, layer);
+            let max_links = self.max_links(layer);
+            let selected = self.select_neighbors(&candidates, max_links);
+
+            self.nodes[new_node].neighbors[layer] = selected.iter().map(|c| c.node).collect();
+
+            for neighbor in &selected {
+                self.nodes[neighbor.node].neighbors[layer].push(new_node);
+                if self.nodes[neighbor.node].neighbors[layer].len() > max_links {
+                    self.shrink_links(neighbor.node, layer, max_links);
+                }
+            }
+
+            entry = candidates;
+        }
+
+        if level > top_level {
+            self.entry_point = Some(new_node);
+        }
+    }
+
+    /// Elimina un core del índice (borrado lógico)
+    ///
+    /// Si los borrados superan `max_tombstone_ratio`, reconstruye el grafo.
+    /// Al retirar el último nodo vivo el índice vuelve a estar vacío y acepta
+    /// embeddings de cualquier dimensión.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(node) = self.id_to_node.remove(id) else {
+            return false;
+        };
+        self.nodes[node].deleted = true;
+
+        if self.id_to_node.is_empty() {
+            self.nodes.clear();
+            self.entry_point = None;
+            self.dimension = None;
+            return true;
+        }
+
+        let tombstones = self.nodes.len() - self.id_to_node.len();
+        if tombstones as f64 > self.config.max_tombstone_ratio * self.nodes.len() as f64 {
+            self.rebuild();
+        }
+        true
+    }
+
+    /// Número de nodos borrados que siguen en el grafo
+    pub fn tombstones(&self) -> usize {
+        self.nodes.len() - self.id_to_node.len()
+    }
+
+    /// Busca los `k` cores más similares con similitud coseno ≥ `threshold`
+    ///
+    /// El umbral se aplica antes de truncar a `k`, así que nunca se descartan
+    /// coincidencias válidas por culpa de candidatos por debajo del umbral.
+    pub fn search(&self, query: &Embedding, k: usize, threshold: f64) -> Vec<(String, f64)> {
+        let query = match self.normalize(&query.vec) {
+            Some(q) => q,
+            None => return Vec::new(),
+        };
+        let entry_point = match self.entry_point {
+            Some(ep) if k > 0 => ep,
+            _ => return Vec::new(),
+        };
+
+        let mut entry = vec![self.candidate(&query, entry_point)];
+        for layer in (1..=self.level_of(entry_point)).rev() {
+            entry = self.search_layer(&query, &entry, 1, layer);
+        }
+
+        // Compensar nodos borrados que ocupan lugares en la lista de candidatos
+        // (acotados por `max_tombstone_ratio`)
+        let ef = self.config.ef_search.max(k + self.tombstones()).min(self.nodes.len());
+        let candidates = self.search_layer(&query, &entry, ef, 0);
+
+        let mut results: Vec<(String, f64)> = candidates
+            .into_iter()
+            .filter(|c| !self.nodes[c.node].deleted)
+            .map(|c| (self.nodes[c.node].id.clone(), (1.0 - c.distance) as f64))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .collect();
+
+        results.truncate(k);
+        results
+    }
+
+    /// Número de embeddings vivos
+    pub fn len(&self) -> usize {
+        self.id_to_node.len()
+    }
+
+    /// Verifica si está vacío
+    pub fn is_empty(&self) -> bool {
+        self.id_to_node.is_empty()
+    }
+
+    /// Dimensión de los embeddings indexados
+    pub fn dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    // === Métodos privados ===
+
+    /// Reconstruye el grafo sólo con los nodos vivos, en orden de inserción
+    fn rebuild(&mut self) {
+        let mut live: Vec<(usize, String)> = self.id_to_node.drain().map(|(id, node)| (node, id)).collect();
+        live.sort_unstable_by_key(|(node, _)| *node);
+
+        let mut old_nodes = std::mem::take(&mut self.nodes);
+        self.entry_point = None;
+        for (node, id) in live {
+            let vector = std::mem::take(&mut old_nodes[node].vector);
+            self.link(id, vector);
+        }
+    }
+
+    fn normalize(&self, vec: &[f32]) -> Option<Vec<f32>> {
+        if vec.is_empty() {
+            return None;
+        }
+        if let Some(dim) = self.dimension {
+            if vec.len() != dim {
+                return None;
+            }
+        }
+
+        let norm = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 || !norm.is_finite() {
+            return None;
+        }
+
+        Some(vec.iter().map(|x| x / norm).collect())
+    }
+
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>()
+    }
+
+    fn candidate(&self, query: &[f32], node: usize) -> Candidate {
+        Candidate {
+            distance: self.distance(query, &self.nodes[node].vector),
+            node,
+        }
+    }
+
+    fn level_of(&self, node: usize) -> usize {
+        self.nodes[node].neighbors.len() - 1
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.config.m * 2
+        } else {
+            self.config.m
+        }
+    }
+
+    /// Nivel aleatorio con distribución exponencial (mL = 1/ln M)
+    fn random_level(&mut self) -> usize {
+        // xorshift64*
+        self.rng_state ^= self.rng_state >> 12;
+        self.rng_state ^= self.rng_state << 25;
+        self.rng_state ^= self.rng_state >> 27;
+        let bits = self.rng_state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+
+        let uniform = ((bits >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let ml = 1.0 / (self.config.m.max(2) as f64).ln();
+        ((-uniform.ln() * ml).floor() as usize).min(16)
+    }
+
+    /// Búsqueda best-first en una capa. Retorna hasta `ef` candidatos ordenados.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry: &[Candidate],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entry.iter().map(|c| c.node).collect();
+        // Min-heap de candidatos por explorar
+        let mut to_visit: BinaryHeap<std::cmp::Reverse<Candidate>> =
+            entry.iter().copied().map(std::cmp::Reverse).collect();
+        // Max-heap con los mejores `ef` encontrados
+        let mut found: BinaryHeap<Candidate> = entry.iter().copied().collect();
+        while found.len() > ef {
+            found.pop();
+        }
+
+        while let Some(std::cmp::Reverse(current)) = to_visit.pop() {
+            if let Some(worst) = found.peek() {
+                if found.len() >= ef && current.distance > worst.distance {
+                    break;
+                }
+            }
+
+            let neighbors = match self.nodes[current.node].neighbors.get(layer) {
+                Some(n) => n,
+                None => continue,
+            };
+
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+
+                let candidate = self.candidate(query, neighbor);
+                let admit = found.len() < ef
+                    || found.peek().map(|w| candidate.distance < w.distance).unwrap_or(true);
+
+                if admit {
+                    to_visit.push(std::cmp::Reverse(candidate));
+                    found.push(candidate);
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+
+        found.into_sorted_vec()
+    }
+
+    /// Heurística de selección de vecinos (diversidad), completada con los
+    /// descartados más cercanos si faltan enlaces
+    fn select_neighbors(&self, candidates: &[Candidate], max_links: usize) -> Vec<Candidate> {
+        let mut selected: Vec<Candidate> = Vec::with_capacity(max_links);
+        let mut pruned = Vec::new();
+
+        for &candidate in candidates {
+            if selected.len() >= max_links {
+                break;
+            }
+
+            let diverse = selected.iter().all(|s| {
+                self.distance(&self.nodes[candidate.node].vector, &self.nodes[s.node].vector)
+                    > candidate.distance
+            });
+
+            if diverse {
+                selected.push(candidate);
+            } else {
+                pruned.push(candidate);
+            }
+        }
+
+        for candidate in pruned {
+            if selected.len() >= max_links {
+                break;
+            }
+            selected.push(candidate);
+        }
+
+        selected
+    }
+
+    fn shrink_links(&mut self, node: usize, layer: usize, max_links: usize) {
+        let base = self.nodes[node].vector.clone();
+        let mut candidates: Vec<Candidate> = self.nodes[node].neighbors[layer]
+            .iter()
+            .map(|&n| Candidate {
+                distance: self.distance(&base, &self.nodes[n].vector),
+                node: n,
+            })
+            .collect();
+        candidates.sort();
+
+        let selected = self.select_neighbors(&candidates, max_links);
+        self.nodes[node].neighbors[layer] = selected.into_iter().map(|c| c.node).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_embeddings(n: usize, dim: usize, seed: u64) -> Vec<Embedding> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 40) as f32 / (1u64 << 24) as f32) * 2.0 - 1.0
+        };
+
+        (0..n)
+            .map(|_| Embedding {
+                vec: (0..dim).map(|_| next()).collect(),
+                model: "test".to_string(),
+            })
+            .collect()
+    }
+
+    fn brute_force(data: &[Embedding], query: &Embedding, k: usize) -> Vec<String> {
+        let mut scored: Vec<(String, f64)> = data
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (format!("core-{}", i), query.cosine_similarity(e)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.into_iter().take(k).map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_hnsw_recall_against_brute_force() {
+        let data = random_embeddings(1000, 16, 42);
+        let mut index = HnswIndex::new(HnswConfig {
+            ef_construction: 64,
+            ..Default::default()
+        });
+        for (i, e) in data.iter().enumerate() {
+            assert!(index.insert(&format!("core-{}", i), e));
+        }
+        assert_eq!(index.len(), 1000);
+
+        let queries = random_embeddings(50, 16, 7);
+        let mut hits = 0;
+        for q in &queries {
+            let expected: HashSet<String> = brute_force(&data, q, 10).into_iter().collect();
+            let found = index.search(q, 10, -1.0);
+            assert_eq!(found.len(), 10);
+            hits += found.iter().filter(|(id, _)| expected.contains(id)).count();
+        }
+
+        let recall = hits as f64 / (queries.len() * 10) as f64;
+        assert!(recall >= 0.9, "recall@10 too low: {}", recall);
+    }
+
+    #[test]
+    fn test_threshold_applied_before_truncation() {
+        let mut index = HnswIndex::default();
+        let base = Embedding { vec: vec![1.0, 0.0, 0.0], model: "test".to_string() };
+
+        // 12 vectores muy similares + 30 ortogonales
+        for i in 0..12 {
+            let e = Embedding { vec: vec![1.0, 0.01 * i as f32, 0.0], model: "test".to_string() };
+            index.insert(&format!("similar-{}", i), &e);
+        }
+        for i in 0..30 {
+            let e = Embedding { vec: vec![0.0, 1.0, 0.1 * i as f32], model: "test".to_string() };
+            index.insert(&format!("other-{}", i), &e);
+        }
+
+        let results = index.search(&base, 20, 0.7);
+        assert_eq!(results.len(), 12);
+        assert!(results.iter().all(|(id, sim)| id.starts_with("similar") && *sim >= 0.7));
+        for w in results.windows(2) {
+            assert!(w[0].1 >= w[1].1);
+        }
+    }
+
+    #[test]
+    fn test_remove_and_rejects_invalid_embeddings() {
+        let mut index = HnswIndex::default();
+        let a = Embedding { vec: vec![1.0, 0.0], model: "test".to_string() };
+        let b = Embedding { vec: vec![0.9, 0.1], model: "test".to_string() };
+
+        assert!(index.insert("a", &a));
+        assert!(index.insert("b", &b));
+        assert!(!index.insert("empty", &Embedding::empty("test".to_string())));
+        assert!(!index.insert("zero", &Embedding { vec: vec![0.0, 0.0], model: "t".into() }));
+        assert!(!index.insert("dim", &Embedding { vec: vec![1.0, 0.0, 0.0], model: "t".into() }));
+
+        assert!(index.remove("a"));
+        assert_eq!(index.len(), 1);
+
+        let results = index.search(&a, 5, 0.0);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "b");
+    }
+
+    #[test]
+    fn test_invalid_replacement_removes_previous_vector() {
+        let mut index = HnswIndex::default();
+        let a = Embedding { vec: vec![1.0, 0.0], model: "test".to_string() };
+
+        assert!(index.insert("a", &a));
+        assert!(!index.insert("a", &Embedding::empty("test".to_string())));
+        assert!(index.is_empty());
+        assert!(index.search(&a, 5, -1.0).is_empty());
+    }
+
+    #[test]
+    fn test_emptied_index_accepts_new_dimension() {
+        let mut index = HnswIndex::default();
+        let two = Embedding { vec: vec![1.0, 0.0], model: "test".to_string() };
+        let three = Embedding { vec: vec![0.0, 1.0, 0.0], model: "test".to_string() };
+
+        assert!(index.insert("a", &two));
+        assert!(!index.insert("b", &three));
+        assert!(index.remove("a"));
+        assert_eq!(index.dimension(), None);
+        assert_eq!(index.tombstones(), 0);
+
+        assert!(index.insert("b", &three));
+        assert_eq!(index.dimension(), Some(3));
+        assert_eq!(index.search(&three, 1, 0.9)[0].0, "b");
+    }
+
+    #[test]
+    fn test_tombstones_trigger_rebuild() {
+        let data = random_embeddings(60, 8, 3);
+        let mut index = HnswIndex::new(HnswConfig {
+            ef_construction: 16,
+            ..Default::default()
+        });
+        for (i, e) in data.iter().enumerate() {
+            index.insert(&format!("core-{}", i), e);
+        }
+
+        // Reemplazos repetidos: los borrados nunca superan la mitad del grafo
+        for round in 0..3 {
+            for (i, e) in data.iter().enumerate().skip(round % 2) {
+                index.insert(&format!("core-{}", i), e);
+                assert!(index.tombstones() <= index.len());
+            }
+        }
+        assert_eq!(index.len(), 60);
+
+        for i in 0..45 {
+            index.remove(&format!("core-{}", i));
+        }
+        assert!(index.tombstones() <= index.len());
+
+        let found = index.search(&data[59], 1, -1.0);
+        assert_eq!(found[0].0, "core-59");
+        assert_eq!(index.search(&data[0], 100, -1.0).len(), 15);
+    }
+}
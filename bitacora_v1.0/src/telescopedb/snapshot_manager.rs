@@ -2,11 +2,28 @@
 //!
 //! Sistema de creación, recuperación, comparación y compresión de snapshots.
 //! Integración con pixel_storage para almacenamiento visual.
+//!
+//! ## Formato en disco
+//!
+//! ```text
+//! storage_path/
+//! ├── <id>.snapshot          → cores serializados (JSON) comprimidos
+//! └── <id>.metadata.json     → `Snapshot` (algoritmo, ratio, checksum)
+//! ```
+//!
+//! El checksum SHA-256 se calcula sobre el JSON sin comprimir y se verifica
+//! al cargar, así que un `.snapshot` truncado o alterado nunca se deserializa.
 
 use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use crate::fbcu::{self, FBCUConfig, FBCUEngine};
+use crate::telescopedb::memory_forensics::{CoreDiff, ForensicsConfig, MemoryForensics};
 use crate::telescopedb::{Result, TelescopeDBError, FBCUCore};
 
 /// Snapshot de TelescopeDB en un momento específico
@@ -67,6 +84,22 @@ pub struct CompressionInfo {
 
     /// Tiempo de compresión (ms)
     pub compression_time_ms: u64,
+
+    /// Ratio real alcanzado (bytes sin comprimir / bytes en disco)
+    #[serde(default = "default_ratio")]
+    pub ratio: f64,
+
+    /// SHA-256 del payload sin comprimir (verificación al cargar)
+    #[serde(default)]
+    pub checksum: Option<String>,
+
+    /// Codec interno elegido por el FBCUEngine (FBCU y FBCUGzip)
+    #[serde(default)]
+    pub fbcu_type: Option<fbcu::CompressionType>,
+}
+
+fn default_ratio() -> f64 {
+    1.0
 }
 
 /// Algoritmos de compresión soportados
@@ -121,6 +154,10 @@ pub struct ComparisonSummary {
     pub total_modified: usize,
     pub total_unchanged: usize,
     pub similarity_score: f64, // 0.0 = totalmente diferente, 1.0 = idéntico
+
+    /// Diff por core modificado (coordenadas + deltas por dimensión)
+    #[serde(default)]
+    pub modifications: Vec<CoreDiff>,
 }
 
 /// Gestor de snapshots
@@ -168,14 +205,21 @@ impl Default for SnapshotConfig {
 
 impl SnapshotManager {
     /// Crea un nuevo gestor de snapshots
+    ///
+    /// Carga la metadata de los snapshots ya existentes en `storage_path`, de modo
+    /// que `max_snapshots` también aplica a los creados en sesiones anteriores.
+    /// Abrir nunca borra nada: la poda ocurre sólo en `create_snapshot`.
     pub fn new(storage_path: PathBuf, config: SnapshotConfig) -> Result<Self> {
         std::fs::create_dir_all(&storage_path)?;
 
-        Ok(Self {
+        let mut manager = Self {
             storage_path,
             snapshots: HashMap::new(),
             config,
-        })
+        };
+        manager.load_existing_snapshots()?;
+
+        Ok(manager)
     }
 
     /// Crea un nuevo snapshot
@@ -187,10 +231,12 @@ impl SnapshotManager {
     ) -> Result<String> {
         let id = uuid::Uuid::new_v4().to_string();
 
-        // Calcular tamaños
-        let uncompressed_size = self.calculate_uncompressed_size(cores);
-        let (compressed_data, compressed_size, compression_time_ms) =
-            self.compress_cores(cores)?;
+        // Serializar y comprimir
+        let serialized = serde_json::to_vec(cores)?;
+        let uncompressed_size = serialized.len() as u64;
+        let checksum = fbcu::sha256_hex(&serialized);
+        let (compressed_data, fbcu_type, compression_time_ms) = self.compress_cores(&serialized)?;
+        let compressed_size = compressed_data.len() as u64;
 
         let compression_ratio = if compressed_size > 0 {
             uncompressed_size as f64 / compressed_size as f64
@@ -216,6 +262,9 @@ impl SnapshotManager {
                 algorithm: self.config.default_compression,
                 level: self.config.compression_level,
                 compression_time_ms,
+                ratio: compression_ratio,
+                checksum: Some(checksum),
+                fbcu_type,
             },
         };
 
@@ -236,54 +285,122 @@ impl SnapshotManager {
         Ok(self.snapshots.get(id).cloned())
     }
 
-    /// Elimina un snapshot
+    /// Carga los cores de un snapshot desde disco
+    ///
+    /// Descomprime según el algoritmo registrado y verifica el checksum antes
+    /// de deserializar. Un archivo alterado retorna `StorageCorrupted`.
+    pub fn load_snapshot_cores(&self, id: &str) -> Result<Vec<FBCUCore>> {
+        let snapshot = self
+            .snapshots
+            .get(id)
+            .ok_or_else(|| TelescopeDBError::SnapshotNotFound(id.to_string()))?;
+
+        let data = std::fs::read(self.snapshot_path(id))?;
+        if data.len() as u64 != snapshot.metadata.compressed_size {
+            return Err(TelescopeDBError::StorageCorrupted(format!(
+                "snapshot {}: expected {} bytes on disk, found {}",
+                id,
+                snapshot.metadata.compressed_size,
+                data.len()
+            )));
+        }
+
+        let serialized = self.decompress_cores(snapshot, &data)?;
+
+        if let Some(expected) = &snapshot.compression.checksum {
+            let got = fbcu::sha256_hex(&serialized);
+            if &got != expected {
+                return Err(TelescopeDBError::StorageCorrupted(format!(
+                    "snapshot {}: checksum mismatch (expected {}, got {})",
+                    id, expected, got
+                )));
+            }
+        }
+
+        Ok(serde_json::from_slice(&serialized)?)
+    }
+
+    /// Elimina un snapshot (datos y metadata)
     pub fn delete_snapshot(&mut self, id: &str) -> Result<()> {
         self.snapshots.remove(id);
 
         // Eliminar de disco
-        let snapshot_path = self.storage_path.join(format!("{}.snapshot", id));
-        if snapshot_path.exists() {
-            std::fs::remove_file(snapshot_path)?;
+        for path in [self.snapshot_path(id), self.metadata_path(id)] {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
         }
 
         Ok(())
     }
 
     /// Compara dos snapshots
+    ///
+    /// Los cores presentes en ambos se comparan por contenido: los que difieren
+    /// se reportan como modificados junto con su `CoreDiff`.
     pub fn compare_snapshots(&self, old_id: &str, new_id: &str) -> Result<SnapshotComparison> {
-        let old_snapshot = self
-            .snapshots
-            .get(old_id)
-            .ok_or_else(|| TelescopeDBError::CoreNotFound(old_id.to_string()))?;
+        if !self.snapshots.contains_key(old_id) {
+            return Err(TelescopeDBError::SnapshotNotFound(old_id.to_string()));
+        }
+        if !self.snapshots.contains_key(new_id) {
+            return Err(TelescopeDBError::SnapshotNotFound(new_id.to_string()));
+        }
 
-        let new_snapshot = self
-            .snapshots
-            .get(new_id)
-            .ok_or_else(|| TelescopeDBError::CoreNotFound(new_id.to_string()))?;
+        let old_cores = self.load_snapshot_cores(old_id)?;
+        let new_cores = self.load_snapshot_cores(new_id)?;
 
-        let old_cores: std::collections::HashSet<_> =
-            old_snapshot.core_ids.iter().collect();
-        let new_cores: std::collections::HashSet<_> =
-            new_snapshot.core_ids.iter().collect();
+        Self::compare_cores(old_id, &old_cores, new_id, &new_cores)
+    }
 
-        let added_cores: Vec<String> = new_cores
-            .difference(&old_cores)
-            .map(|s| s.to_string())
+    /// Compara dos conjuntos de cores arbitrarios (p.ej. estado vivo vs snapshot)
+    ///
+    /// `old_id` / `new_id` sólo se usan para etiquetar la comparación.
+    pub fn compare_cores(
+        old_id: &str,
+        old_cores: &[FBCUCore],
+        new_id: &str,
+        new_cores: &[FBCUCore],
+    ) -> Result<SnapshotComparison> {
+        let old_cores: HashMap<&str, &FBCUCore> =
+            old_cores.iter().map(|c| (c.id.as_str(), c)).collect();
+        let new_cores: HashMap<&str, &FBCUCore> =
+            new_cores.iter().map(|c| (c.id.as_str(), c)).collect();
+
+        let mut added_cores: Vec<String> = new_cores
+            .keys()
+            .filter(|id| !old_cores.contains_key(*id))
+            .map(|id| id.to_string())
             .collect();
+        added_cores.sort();
 
-        let deleted_cores: Vec<String> = old_cores
-            .difference(&new_cores)
-            .map(|s| s.to_string())
+        let mut deleted_cores: Vec<String> = old_cores
+            .keys()
+            .filter(|id| !new_cores.contains_key(*id))
+            .map(|id| id.to_string())
             .collect();
-
-        let common_cores: Vec<String> = old_cores
-            .intersection(&new_cores)
-            .map(|s| s.to_string())
+        deleted_cores.sort();
+
+        // Detectar modificaciones reales comparando contenido
+        let forensics = MemoryForensics::new(ForensicsConfig::default());
+        let mut common_cores: Vec<&str> = old_cores
+            .keys()
+            .filter(|id| new_cores.contains_key(*id))
+            .copied()
             .collect();
-
-        // TODO: Detectar modificaciones reales comparando contenido
-        let modified_cores = Vec::new();
-        let unchanged_cores = common_cores;
+        common_cores.sort();
+
+        let mut modified_cores = Vec::new();
+        let mut unchanged_cores = Vec::new();
+        let mut modifications = Vec::new();
+        for id in common_cores {
+            let (old_core, new_core) = (old_cores[id], new_cores[id]);
+            if cores_equal(old_core, new_core)? {
+                unchanged_cores.push(id.to_string());
+            } else {
+                modified_cores.push(id.to_string());
+                modifications.push(forensics.compute_diff(old_core, new_core));
+            }
+        }
 
         let total_added = added_cores.len();
         let total_deleted = deleted_cores.len();
@@ -291,7 +408,7 @@ impl SnapshotManager {
         let total_unchanged = unchanged_cores.len();
 
         // Calcular similitud
-        let total_cores = (old_snapshot.core_ids.len() + new_snapshot.core_ids.len()) as f64 / 2.0;
+        let total_cores = (old_cores.len() + new_cores.len()) as f64 / 2.0;
         let similarity_score = if total_cores > 0.0 {
             total_unchanged as f64 / total_cores
         } else {
@@ -312,6 +429,7 @@ impl SnapshotManager {
                 total_modified,
                 total_unchanged,
                 similarity_score,
+                modifications,
             },
         })
     }
@@ -328,56 +446,122 @@ impl SnapshotManager {
         self.list_snapshots().into_iter().next()
     }
 
+    /// Obtiene el snapshot más reciente creado en o antes de `as_of`
+    pub fn get_snapshot_as_of(&self, as_of: DateTime<Utc>) -> Option<Snapshot> {
+        self.list_snapshots()
+            .into_iter()
+            .find(|snapshot| snapshot.created_at <= as_of)
+    }
+
     // === Métodos privados ===
 
-    fn calculate_uncompressed_size(&self, cores: &[FBCUCore]) -> u64 {
-        // Estimación simple: serializar a JSON y medir tamaño
-        cores
-            .iter()
-            .map(|core| {
-                serde_json::to_string(core)
-                    .map(|s| s.len() as u64)
-                    .unwrap_or(0)
-            })
-            .sum()
+    fn snapshot_path(&self, id: &str) -> PathBuf {
+        self.storage_path.join(format!("{}.snapshot", id))
     }
 
-    fn compress_cores(&self, cores: &[FBCUCore]) -> Result<(Vec<u8>, u64, u64)> {
-        let start = std::time::Instant::now();
+    fn metadata_path(&self, id: &str) -> PathBuf {
+        self.storage_path.join(format!("{}.metadata.json", id))
+    }
 
-        // Serializar cores
-        let serialized = serde_json::to_vec(cores)?;
+    /// Comprime el payload serializado. Retorna (datos, codec FBCU, tiempo ms).
+    fn compress_cores(
+        &self,
+        serialized: &[u8],
+    ) -> Result<(Vec<u8>, Option<fbcu::CompressionType>, u64)> {
+        let start = std::time::Instant::now();
 
         // Aplicar compresión según algoritmo
-        let compressed = match self.config.default_compression {
-            CompressionAlgorithm::None => serialized.clone(),
-            CompressionAlgorithm::Gzip => {
-                // TODO: Implementar GZIP
-                serialized.clone()
-            }
+        let (compressed, fbcu_type) = match self.config.default_compression {
+            CompressionAlgorithm::None => (serialized.to_vec(), None),
+            CompressionAlgorithm::Gzip => (self.gzip(serialized)?, None),
             CompressionAlgorithm::FBCU => {
-                // TODO: Implementar FBCU compression
-                serialized.clone()
+                let core = fbcu_engine()?.compress(serialized).map_err(compression_error)?;
+                (core.compressed_data, Some(core.compression_type))
             }
             CompressionAlgorithm::FBCUGzip => {
-                // TODO: Implementar FBCU + GZIP
-                serialized.clone()
+                let core = fbcu_engine()?.compress(serialized).map_err(compression_error)?;
+                (self.gzip(&core.compressed_data)?, Some(core.compression_type))
             }
         };
 
         let elapsed = start.elapsed().as_millis() as u64;
-        let size = compressed.len() as u64;
 
-        Ok((compressed, size, elapsed))
+        Ok((compressed, fbcu_type, elapsed))
+    }
+
+    /// Inverso de `compress_cores` según la `CompressionInfo` del snapshot
+    fn decompress_cores(&self, snapshot: &Snapshot, data: &[u8]) -> Result<Vec<u8>> {
+        let info = &snapshot.compression;
+
+        let fbcu_payload = match info.algorithm {
+            CompressionAlgorithm::None => return Ok(data.to_vec()),
+            CompressionAlgorithm::Gzip => return gunzip(data),
+            CompressionAlgorithm::FBCU => data.to_vec(),
+            CompressionAlgorithm::FBCUGzip => gunzip(data)?,
+        };
+
+        // Reconstruir el core FBCU: `decompress` verifica el hash del original
+        let original_hash = info.checksum.clone().ok_or_else(|| {
+            TelescopeDBError::StorageCorrupted(format!("snapshot {}: missing checksum", snapshot.id))
+        })?;
+        let core = fbcu::FBCUCore {
+            id: original_hash.clone(),
+            compression_type: info.fbcu_type.unwrap_or(fbcu::CompressionType::None),
+            compressed_data: fbcu_payload,
+            original_size: snapshot.metadata.uncompressed_size as usize,
+            compression_ratio: info.ratio,
+            metadata: fbcu::FBCUMetadata {
+                compressed_at: snapshot.created_at.to_rfc3339(),
+                compression_time_ms: info.compression_time_ms,
+                original_hash,
+                wavelet_level: None,
+                fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
+            },
+        };
+
+        fbcu_engine()?.decompress(&core).map_err(|e| {
+            TelescopeDBError::StorageCorrupted(format!("snapshot {}: {}", snapshot.id, e))
+        })
+    }
+
+    fn gzip(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let level = Compression::new(u32::from(self.config.compression_level.min(9)));
+        let mut encoder = GzEncoder::new(Vec::new(), level);
+        encoder.write_all(data)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Carga la metadata de snapshots persistidos en sesiones anteriores
+    fn load_existing_snapshots(&mut self) -> Result<()> {
+        for entry in std::fs::read_dir(&self.storage_path)? {
+            let path = entry?.path();
+            let is_metadata = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.ends_with(".metadata.json"))
+                .unwrap_or(false);
+            if !is_metadata {
+                continue;
+            }
+
+            match serde_json::from_slice::<Snapshot>(&std::fs::read(&path)?) {
+                Ok(snapshot) => {
+                    self.snapshots.insert(snapshot.id.clone(), snapshot);
+                }
+                Err(e) => tracing::warn!("Skipping unreadable snapshot metadata {:?}: {}", path, e),
+            }
+        }
+
+        Ok(())
     }
 
     fn save_snapshot(&self, snapshot: &Snapshot, data: &[u8]) -> Result<()> {
-        let snapshot_path = self.storage_path.join(format!("{}.snapshot", snapshot.id));
+        let snapshot_path = self.snapshot_path(&snapshot.id);
 
         // Guardar metadata
-        let metadata_path = self
-            .storage_path
-            .join(format!("{}.metadata.json", snapshot.id));
+        let metadata_path = self.metadata_path(&snapshot.id);
         let metadata_json = serde_json::to_string_pretty(snapshot)?;
         std::fs::write(metadata_path, metadata_json)?;
 
@@ -406,6 +590,40 @@ impl SnapshotManager {
     }
 }
 
+/// Igualdad de contenido entre dos versiones del mismo core
+///
+/// Igual que en `FBCUCore::content_id`, el timestamp no participa: re-crear la
+/// misma experiencia no es una modificación. `FBCUCore` no implementa
+/// `PartialEq`, así que se compara su representación serializada.
+fn cores_equal(a: &FBCUCore, b: &FBCUCore) -> Result<bool> {
+    let mut a = serde_json::to_value(a)?;
+    let mut b = serde_json::to_value(b)?;
+    for value in [&mut a, &mut b] {
+        if let Some(atomic_core) = value.get_mut("atomic_core").and_then(|v| v.as_object_mut()) {
+            atomic_core.remove("timestamp");
+        }
+    }
+    Ok(a == b)
+}
+
+/// Motor FBCU para un snapshot (sin estado compartido entre operaciones)
+fn fbcu_engine() -> Result<FBCUEngine> {
+    FBCUEngine::new(FBCUConfig::default()).map_err(compression_error)
+}
+
+fn compression_error(e: fbcu::FBCUError) -> TelescopeDBError {
+    TelescopeDBError::CompressionError(e.to_string())
+}
+
+fn gunzip(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = GzDecoder::new(data);
+    let mut decompressed = Vec::new();
+    decoder
+        .read_to_end(&mut decompressed)
+        .map_err(|e| TelescopeDBError::StorageCorrupted(format!("GZIP stream: {}", e)))?;
+    Ok(decompressed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,15 +654,17 @@ mod tests {
 
     #[test]
     fn test_snapshot_manager_creation() {
-        let temp_dir = std::env::temp_dir().join("test_snapshots");
-        let manager = SnapshotManager::new(temp_dir, SnapshotConfig::default());
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager =
+            SnapshotManager::new(temp_dir.path().to_path_buf(), SnapshotConfig::default());
         assert!(manager.is_ok());
     }
 
     #[test]
     fn test_create_snapshot() {
-        let temp_dir = std::env::temp_dir().join("test_snapshots_create");
-        let mut manager = SnapshotManager::new(temp_dir, SnapshotConfig::default()).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manager =
+            SnapshotManager::new(temp_dir.path().to_path_buf(), SnapshotConfig::default()).unwrap();
 
         let cores = vec![
             create_test_core("core-1".to_string()),
@@ -471,8 +691,9 @@ mod tests {
 
     #[test]
     fn test_compare_snapshots() {
-        let temp_dir = std::env::temp_dir().join("test_snapshots_compare");
-        let mut manager = SnapshotManager::new(temp_dir, SnapshotConfig::default()).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manager =
+            SnapshotManager::new(temp_dir.path().to_path_buf(), SnapshotConfig::default()).unwrap();
 
         let cores1 = vec![
             create_test_core("core-1".to_string()),
@@ -499,12 +720,18 @@ mod tests {
         assert_eq!(comparison.summary.total_added, 1); // core-3
         assert_eq!(comparison.summary.total_deleted, 1); // core-1
         assert_eq!(comparison.summary.total_unchanged, 1); // core-2
+
+        assert!(matches!(
+            manager.compare_snapshots(&snapshot1_id, "missing"),
+            Err(TelescopeDBError::SnapshotNotFound(id)) if id == "missing"
+        ));
     }
 
     #[test]
     fn test_list_snapshots() {
-        let temp_dir = std::env::temp_dir().join("test_snapshots_list");
-        let mut manager = SnapshotManager::new(temp_dir, SnapshotConfig::default()).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manager =
+            SnapshotManager::new(temp_dir.path().to_path_buf(), SnapshotConfig::default()).unwrap();
 
         let cores = vec![create_test_core("core-1".to_string())];
 
@@ -522,8 +749,9 @@ mod tests {
 
     #[test]
     fn test_delete_snapshot() {
-        let temp_dir = std::env::temp_dir().join("test_snapshots_delete");
-        let mut manager = SnapshotManager::new(temp_dir, SnapshotConfig::default()).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manager =
+            SnapshotManager::new(temp_dir.path().to_path_buf(), SnapshotConfig::default()).unwrap();
 
         let cores = vec![create_test_core("core-1".to_string())];
 
@@ -540,12 +768,12 @@ mod tests {
 
     #[test]
     fn test_prune_old_snapshots() {
-        let temp_dir = std::env::temp_dir().join("test_snapshots_prune");
+        let temp_dir = tempfile::tempdir().unwrap();
         let config = SnapshotConfig {
             max_snapshots: 2,
             ..Default::default()
         };
-        let mut manager = SnapshotManager::new(temp_dir, config).unwrap();
+        let mut manager = SnapshotManager::new(temp_dir.path().to_path_buf(), config).unwrap();
 
         let cores = vec![create_test_core("core-1".to_string())];
 
@@ -569,4 +797,167 @@ mod tests {
         // Debería haber solo 2 snapshots (el más antiguo fue eliminado)
         assert_eq!(manager.list_snapshots().len(), 2);
     }
+
+    fn create_varied_cores(n: usize) -> Vec<FBCUCore> {
+        (0..n)
+            .map(|i| {
+                let mut core = create_test_core(format!("core-{}", i));
+                core.atomic_core.content = format!("memoria {} sobre compresión", i).into_bytes();
+                core.context_tensor.semantic = i as f64 / n as f64;
+                core
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compression_algorithms_round_trip() {
+        let cores = create_varied_cores(50);
+
+        for algorithm in [
+            CompressionAlgorithm::None,
+            CompressionAlgorithm::Gzip,
+            CompressionAlgorithm::FBCU,
+            CompressionAlgorithm::FBCUGzip,
+        ] {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let config = SnapshotConfig {
+                default_compression: algorithm,
+                ..Default::default()
+            };
+            let mut manager = SnapshotManager::new(temp_dir.path().to_path_buf(), config).unwrap();
+
+            let id = manager
+                .create_snapshot("Test".to_string(), "Test".to_string(), &cores)
+                .unwrap();
+            let snapshot = manager.get_snapshot(&id).unwrap().unwrap();
+
+            // El ratio registrado es el real
+            let on_disk = std::fs::metadata(temp_dir.path().join(format!("{}.snapshot", id)))
+                .unwrap()
+                .len();
+            assert_eq!(snapshot.metadata.compressed_size, on_disk);
+            assert_eq!(
+                snapshot.compression.ratio,
+                snapshot.metadata.uncompressed_size as f64 / on_disk as f64
+            );
+            if algorithm == CompressionAlgorithm::None {
+                assert_eq!(snapshot.compression.ratio, 1.0);
+            } else {
+                assert!(snapshot.compression.ratio > 2.0, "{:?}", algorithm);
+            }
+
+            let loaded = manager.load_snapshot_cores(&id).unwrap();
+            assert_eq!(loaded.len(), cores.len());
+            assert_eq!(loaded[7].atomic_core.content, cores[7].atomic_core.content);
+        }
+    }
+
+    #[test]
+    fn test_load_detects_tampering() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = SnapshotConfig {
+            default_compression: CompressionAlgorithm::None,
+            ..Default::default()
+        };
+        let mut manager = SnapshotManager::new(temp_dir.path().to_path_buf(), config).unwrap();
+
+        let id = manager
+            .create_snapshot("Test".to_string(), "Test".to_string(), &create_varied_cores(5))
+            .unwrap();
+
+        // Mismo tamaño, contenido alterado
+        let path = temp_dir.path().join(format!("{}.snapshot", id));
+        let mut data = std::fs::read(&path).unwrap();
+        let pos = data.iter().position(|&b| b == b'1').unwrap();
+        data[pos] = b'2';
+        std::fs::write(&path, &data).unwrap();
+
+        assert!(matches!(
+            manager.load_snapshot_cores(&id),
+            Err(TelescopeDBError::StorageCorrupted(_))
+        ));
+    }
+
+    #[test]
+    fn test_existing_snapshots_are_reloaded_and_pruned_on_create() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cores = create_varied_cores(3);
+
+        {
+            let mut manager =
+                SnapshotManager::new(temp_dir.path().to_path_buf(), SnapshotConfig::default())
+                    .unwrap();
+            for i in 0..3 {
+                manager
+                    .create_snapshot(format!("Snapshot {}", i), String::new(), &cores)
+                    .unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+        }
+
+        let config = SnapshotConfig {
+            max_snapshots: 1,
+            ..Default::default()
+        };
+        let mut manager = SnapshotManager::new(temp_dir.path().to_path_buf(), config).unwrap();
+
+        // Abrir con un límite menor no borra nada
+        assert_eq!(manager.list_snapshots().len(), 3);
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 6);
+
+        manager
+            .create_snapshot("Snapshot 3".to_string(), String::new(), &cores)
+            .unwrap();
+        let snapshots = manager.list_snapshots();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].name, "Snapshot 3");
+        assert_eq!(manager.load_snapshot_cores(&snapshots[0].id).unwrap().len(), 3);
+
+        // Datos y metadata de los snapshots podados se eliminan de disco
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_compare_snapshots_detects_modifications() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manager =
+            SnapshotManager::new(temp_dir.path().to_path_buf(), SnapshotConfig::default()).unwrap();
+
+        let cores1 = create_varied_cores(3);
+        let mut cores2 = cores1.clone();
+
+        // core-1: corrección emocional (cambia tensor y coordenadas)
+        cores2[1].context_tensor.emotional = 0.2;
+        cores2[1].coords = cores2[1].context_tensor.to_spherical_coords();
+        // core-2: sólo cambia la metadata
+        cores2[2].metadata.insert("source".to_string(), "manual".to_string());
+
+        let old_id = manager
+            .create_snapshot("Before".to_string(), String::new(), &cores1)
+            .unwrap();
+        let new_id = manager
+            .create_snapshot("After".to_string(), String::new(), &cores2)
+            .unwrap();
+
+        let comparison = manager.compare_snapshots(&old_id, &new_id).unwrap();
+        assert_eq!(comparison.modified_cores, vec!["core-1", "core-2"]);
+        assert_eq!(comparison.unchanged_cores, vec!["core-0"]);
+        assert_eq!(comparison.summary.total_modified, 2);
+        assert_eq!(comparison.summary.modifications.len(), 2);
+
+        let diff = &comparison.summary.modifications[0];
+        assert_eq!(diff.old_id, "core-1");
+        let emotional = diff
+            .dimension_diff
+            .iter()
+            .find(|d| d.dimension_name == "emotional")
+            .unwrap();
+        assert_eq!(emotional.old_value, 0.9);
+        assert_eq!(emotional.new_value, 0.2);
+        assert!(diff.coord_diff.euclidean_distance > 0.0);
+
+        // Sólo metadata: diff sin deltas dimensionales
+        let diff = &comparison.summary.modifications[1];
+        assert_eq!(diff.total_distance, 0.0);
+    }
 }
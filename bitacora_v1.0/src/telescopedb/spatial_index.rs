@@ -0,0 +1,519 @@
+//! # Spatial Index - Octree sobre Coordenadas Cartesianas
+//!
+//! Índice espacial de TelescopeDB para `query_contextual`. Cada core se indexa
+//! por `SphericalCoords::to_cartesian()`: la distancia euclidiana entre puntos
+//! cartesianos es exactamente `SphericalCoords::distance` (ley de cosenos), así
+//! que la poda por bounding box no pierde resultados.
+//!
+//! A diferencia del `Octree` de VoxelDB (espacio fijo [0,1]³), aquí `r` no está
+//! acotado: la raíz es un cubo centrado en el origen que se duplica (con
+//! reconstrucción) cuando llega un punto fuera de sus límites.
+//!
+//! ## Performance
+//!
+//! - Insert / remove: O(log n)
+//! - Query por radio: O(log n + k) donde k = resultados
+//! - k vecinos más cercanos: búsqueda best-first con poda por distancia mínima
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use crate::telescopedb::SphericalCoords;
+
+/// Máximo de puntos en una hoja antes de subdividir
+const LEAF_CAPACITY: usize = 16;
+
+/// Profundidad máxima (evita recursión infinita con puntos duplicados)
+const MAX_DEPTH: usize = 20;
+
+/// Semi-lado inicial del cubo raíz (tensores normalizados → r ≤ 1)
+const INITIAL_HALF_EXTENT: f64 = 1.0;
+
+/// Caja alineada a ejes en espacio cartesiano
+#[derive(Debug, Clone, Copy)]
+struct Aabb {
+    min: [f64; 3],
+    max: [f64; 3],
+}
+
+impl Aabb {
+    /// Cubo centrado en el origen
+    fn centered_cube(half_extent: f64) -> Self {
+        Self {
+            min: [-half_extent; 3],
+            max: [half_extent; 3],
+        }
+    }
+
+    fn contains(&self, p: &[f64; 3]) -> bool {
+        (0..3).all(|i| p[i] >= self.min[i] && p[i] <= self.max[i])
+    }
+
+    /// Distancia² mínima desde `p` a cualquier punto de la caja
+    fn min_distance_sq(&self, p: &[f64; 3]) -> f64 {
+        (0..3)
+            .map(|i| {
+                let d = if p[i] < self.min[i] {
+                    self.min[i] - p[i]
+                } else if p[i] > self.max[i] {
+                    p[i] - self.max[i]
+                } else {
+                    0.0
+                };
+                d * d
+            })
+            .sum()
+    }
+
+    /// Octante de `p`: bit 0 = x, bit 1 = y, bit 2 = z
+    fn octant(&self, p: &[f64; 3]) -> usize {
+        (0..3).fold(0, |acc, i| {
+            let mid = (self.min[i] + self.max[i]) / 2.0;
+            if p[i] >= mid {
+                acc | (1 << i)
+            } else {
+                acc
+            }
+        })
+    }
+
+    fn child(&self, octant: usize) -> Self {
+        let mut min = self.min;
+        let mut max = self.max;
+        for i in 0..3 {
+            let mid = (self.min[i] + self.max[i]) / 2.0;
+            if octant & (1 << i) != 0 {
+                min[i] = mid;
+            } else {
+                max[i] = mid;
+            }
+        }
+        Self { min, max }
+    }
+}
+
+/// Punto indexado
+#[derive(Debug, Clone)]
+struct Entry {
+    id: String,
+    point: [f64; 3],
+}
+
+/// Nodo del octree
+#[derive(Debug)]
+struct Node {
+    bounds: Aabb,
+    depth: usize,
+    items: Vec<Entry>,
+    children: Option<Box<[Node; 8]>>,
+}
+
+impl Node {
+    fn new_leaf(bounds: Aabb, depth: usize) -> Self {
+        Self {
+            bounds,
+            depth,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+
+    fn insert(&mut self, entry: Entry) {
+        match self.children {
+            Some(ref mut children) => {
+                let octant = self.bounds.octant(&entry.point);
+                children[octant].insert(entry);
+            }
+            None => {
+                self.items.push(entry);
+                if self.items.len() > LEAF_CAPACITY && self.depth < MAX_DEPTH {
+                    self.split();
+                }
+            }
+        }
+    }
+
+    /// Convierte la hoja en nodo interno moviendo sus puntos a los hijos
+    fn split(&mut self) {
+        let bounds = self.bounds;
+        let depth = self.depth + 1;
+        let mut children: Box<[Node; 8]> =
+            Box::new(std::array::from_fn(|i| Node::new_leaf(bounds.child(i), depth)));
+
+        for entry in std::mem::take(&mut self.items) {
+            let octant = bounds.octant(&entry.point);
+            children[octant].insert(entry);
+        }
+
+        self.children = Some(children);
+    }
+
+    fn remove(&mut self, id: &str, point: &[f64; 3]) -> bool {
+        match self.children {
+            Some(ref mut children) => {
+                let octant = self.bounds.octant(point);
+                children[octant].remove(id, point)
+            }
+            None => {
+                let before = self.items.len();
+                self.items.retain(|e| e.id != id);
+                self.items.len() != before
+            }
+        }
+    }
+
+    fn collect_within(&self, center: &[f64; 3], radius_sq: f64, out: &mut Vec<(String, f64)>) {
+        // PODA: la caja completa está fuera del radio
+        if self.bounds.min_distance_sq(center) > radius_sq {
+            return;
+        }
+
+        match self.children {
+            Some(ref children) => {
+                for child in children.iter() {
+                    child.collect_within(center, radius_sq, out);
+                }
+            }
+            None => {
+                for entry in &self.items {
+                    let d2 = distance_sq(center, &entry.point);
+                    if d2 <= radius_sq {
+                        out.push((entry.id.clone(), d2.sqrt()));
+                    }
+                }
+            }
+        }
+    }
+
+    fn drain_into(&mut self, out: &mut Vec<Entry>) {
+        out.append(&mut self.items);
+        if let Some(ref mut children) = self.children {
+            for child in children.iter_mut() {
+                child.drain_into(out);
+            }
+        }
+    }
+}
+
+/// f64 con orden total para colas de prioridad
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrdF64(f64);
+
+impl Eq for OrdF64 {}
+
+impl PartialOrd for OrdF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrdF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Nodo pendiente en la búsqueda best-first (min-heap por distancia)
+struct PendingNode<'a> {
+    min_distance_sq: OrdF64,
+    node: &'a Node,
+}
+
+impl PartialEq for PendingNode<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.min_distance_sq == other.min_distance_sq
+    }
+}
+
+impl Eq for PendingNode<'_> {}
+
+impl PartialOrd for PendingNode<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingNode<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Invertido: BinaryHeap es max-heap
+        other.min_distance_sq.cmp(&self.min_distance_sq)
+    }
+}
+
+/// Índice espacial esférico (octree sobre `to_cartesian`)
+#[derive(Debug)]
+pub struct SphericalIndex {
+    root: Node,
+    len: usize,
+}
+
+impl Default for SphericalIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SphericalIndex {
+    /// Crea un índice vacío
+    pub fn new() -> Self {
+        Self {
+            root: Node::new_leaf(Aabb::centered_cube(INITIAL_HALF_EXTENT), 0),
+            len: 0,
+        }
+    }
+
+    /// Inserta un core en sus coordenadas esféricas
+    ///
+    /// Coordenadas no finitas se ignoran (nunca caerían dentro de un radio).
+    pub fn insert(&mut self, id: String, coords: SphericalCoords) {
+        let point = cartesian(&coords);
+        if !point.iter().all(|v| v.is_finite()) {
+            tracing::warn!("SphericalIndex: ignoring non-finite coords for {}", id);
+            return;
+        }
+
+        if !self.root.bounds.contains(&point) {
+            self.grow_to_fit(&point);
+        }
+
+        self.root.insert(Entry { id, point });
+        self.len += 1;
+    }
+
+    /// Elimina un core (se requieren las coordenadas con las que se insertó)
+    pub fn remove(&mut self, id: &str, coords: SphericalCoords) -> bool {
+        let point = cartesian(&coords);
+        if !self.root.bounds.contains(&point) {
+            return false;
+        }
+
+        let removed = self.root.remove(id, &point);
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Todos los cores dentro de `radius`, ordenados por distancia ascendente
+    pub fn within_radius(&self, center: SphericalCoords, radius: f64) -> Vec<(String, f64)> {
+        let mut results = Vec::new();
+        if radius < 0.0 || radius.is_nan() {
+            return results;
+        }
+
+        self.root
+            .collect_within(&cartesian(&center), radius * radius, &mut results);
+        sort_by_distance(&mut results);
+        results
+    }
+
+    /// Los `k` cores más cercanos dentro de `max_radius` (usar `f64::INFINITY` sin límite)
+    pub fn nearest(&self, center: SphericalCoords, k: usize, max_radius: f64) -> Vec<(String, f64)> {
+        if k == 0 || self.len == 0 || max_radius < 0.0 || max_radius.is_nan() {
+            return Vec::new();
+        }
+
+        let query = cartesian(&center);
+        let max_radius_sq = max_radius * max_radius;
+
+        // Max-heap con los k mejores candidatos (peor arriba)
+        let mut best: BinaryHeap<(OrdF64, String)> = BinaryHeap::with_capacity(k + 1);
+        let mut pending = BinaryHeap::new();
+        pending.push(PendingNode {
+            min_distance_sq: OrdF64(self.root.bounds.min_distance_sq(&query)),
+            node: &self.root,
+        });
+
+        while let Some(PendingNode { min_distance_sq, node }) = pending.pop() {
+            let bound = match best.peek() {
+                Some((worst, _)) if best.len() == k => worst.0.min(max_radius_sq),
+                _ => max_radius_sq,
+            };
+            // Ningún nodo restante puede mejorar el resultado
+            if min_distance_sq.0 > bound {
+                break;
+            }
+
+            match node.children {
+                Some(ref children) => {
+                    for child in children.iter() {
+                        let d2 = child.bounds.min_distance_sq(&query);
+                        if d2 <= bound {
+                            pending.push(PendingNode {
+                                min_distance_sq: OrdF64(d2),
+                                node: child,
+                            });
+                        }
+                    }
+                }
+                None => {
+                    for entry in &node.items {
+                        let d2 = distance_sq(&query, &entry.point);
+                        if d2 > max_radius_sq {
+                            continue;
+                        }
+                        if best.len() < k {
+                            best.push((OrdF64(d2), entry.id.clone()));
+                        } else if let Some((worst, _)) = best.peek() {
+                            if d2 < worst.0 {
+                                best.pop();
+                                best.push((OrdF64(d2), entry.id.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut results: Vec<(String, f64)> = best
+            .into_iter()
+            .map(|(d2, id)| (id, d2.0.sqrt()))
+            .collect();
+        sort_by_distance(&mut results);
+        results
+    }
+
+    /// Número de cores indexados
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Verifica si está vacío
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Reconstruye la raíz duplicando su tamaño hasta contener `point`
+    fn grow_to_fit(&mut self, point: &[f64; 3]) {
+        let mut half_extent = self.root.bounds.max[0];
+        while !Aabb::centered_cube(half_extent).contains(point) {
+            half_extent *= 2.0;
+        }
+
+        let mut entries = Vec::with_capacity(self.len);
+        self.root.drain_into(&mut entries);
+
+        self.root = Node::new_leaf(Aabb::centered_cube(half_extent), 0);
+        for entry in entries {
+            self.root.insert(entry);
+        }
+    }
+}
+
+fn cartesian(coords: &SphericalCoords) -> [f64; 3] {
+    let (x, y, z) = coords.to_cartesian();
+    [x, y, z]
+}
+
+fn distance_sq(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
+
+/// Orden determinista: distancia ascendente, desempate por ID
+fn sort_by_distance(results: &mut [(String, f64)]) {
+    results.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generador determinista de coordenadas (LCG)
+    fn sample_coords(n: usize, seed: u64) -> Vec<SphericalCoords> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        (0..n)
+            .map(|_| SphericalCoords {
+                r: next() * 1.5,
+                theta: next() * 2.0 * std::f64::consts::PI,
+                phi: next() * std::f64::consts::PI,
+            })
+            .collect()
+    }
+
+    fn linear_within(
+        points: &[SphericalCoords],
+        center: SphericalCoords,
+        radius: f64,
+    ) -> Vec<String> {
+        let c = cartesian(&center);
+        let mut ids: Vec<String> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| distance_sq(&c, &cartesian(p)) <= radius * radius)
+            .map(|(i, _)| format!("core-{}", i))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_within_radius_matches_linear_scan() {
+        let points = sample_coords(2000, 42);
+        let mut index = SphericalIndex::new();
+        for (i, p) in points.iter().enumerate() {
+            index.insert(format!("core-{}", i), *p);
+        }
+        assert_eq!(index.len(), 2000);
+
+        for center in sample_coords(20, 7) {
+            let results = index.within_radius(center, 0.3);
+            let mut ids: Vec<String> = results.iter().map(|(id, _)| id.clone()).collect();
+            ids.sort();
+            assert_eq!(ids, linear_within(&points, center, 0.3));
+
+            // Distancias consistentes con SphericalCoords::distance y ordenadas
+            for w in results.windows(2) {
+                assert!(w[0].1 <= w[1].1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_matches_sorted_scan() {
+        let points = sample_coords(3000, 1);
+        let mut index = SphericalIndex::new();
+        for (i, p) in points.iter().enumerate() {
+            index.insert(format!("core-{}", i), *p);
+        }
+
+        for center in sample_coords(10, 99) {
+            let c = cartesian(&center);
+            let mut expected: Vec<(String, f64)> = points
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (format!("core-{}", i), distance_sq(&c, &cartesian(p)).sqrt()))
+                .collect();
+            sort_by_distance(&mut expected);
+            expected.truncate(5);
+
+            assert_eq!(index.nearest(center, 5, f64::INFINITY), expected);
+
+            // Con radio máximo sólo se retornan los que caen dentro
+            let limited = index.nearest(center, 5, 0.05);
+            assert!(limited.iter().all(|(_, d)| *d <= 0.05));
+        }
+    }
+
+    #[test]
+    fn test_grow_and_remove() {
+        let mut index = SphericalIndex::new();
+        let near = SphericalCoords { r: 0.5, theta: 1.0, phi: 1.0 };
+        let far = SphericalCoords { r: 10.0, theta: 1.0, phi: 1.0 };
+
+        index.insert("near".to_string(), near);
+        index.insert("far".to_string(), far);
+        assert_eq!(index.len(), 2);
+
+        let results = index.nearest(far, 1, f64::INFINITY);
+        assert_eq!(results[0].0, "far");
+
+        assert!(index.remove("far", far));
+        assert!(!index.remove("far", far));
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.nearest(far, 1, f64::INFINITY)[0].0, "near");
+    }
+}
@@ -0,0 +1,172 @@
+//! # Index Snapshot - Arranque Incremental de VoxelDB
+//!
+//! Serializa en un único archivo CBOR el estado en memoria de VoxelDB
+//! (templates, Octree, índices por categoría y nombre) junto con un manifiesto
+//! de los `.json` de los que proviene y los embeddings del índice semántico.
+//! Al arrancar se carga con una sola lectura y solo se vuelven a decodificar
+//! los archivos cuyo checksum cambió.
+//!
+//! ## Detección de cambios
+//!
+//! 1. Tamaño y mtime iguales al manifiesto y mtime anterior al snapshot →
+//!    archivo sin cambios (sin leerlo)
+//! 2. Si difieren, se calcula SHA-256: igual → solo se refresca el manifiesto
+//! 3. Checksum distinto, archivo nuevo o eliminado → se re-decodifica / des-indexa
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::voxeldb::{Octree, Result, SemanticIndex, TemplateCategory, TemplateEntry, VoxelDBError};
+
+/// Nombre del snapshot dentro del directorio de VoxelDB
+pub const INDEX_SNAPSHOT_FILE_NAME: &str = "voxel_index.snapshot";
+
+/// Versión del formato; un snapshot de otra versión se ignora y se reconstruye
+///
+/// - v1: templates, Octree, índices y manifiesto
+/// - v2: + índice semántico
+pub const INDEX_SNAPSHOT_VERSION: u32 = 2;
+
+/// Huella de un archivo de template en el momento de indexarlo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    /// Tamaño en bytes
+    pub len: u64,
+
+    /// mtime en nanosegundos desde epoch (0 si el FS no lo soporta)
+    pub modified_nanos: u64,
+
+    /// SHA-256 hex del contenido
+    pub checksum: String,
+}
+
+impl FileFingerprint {
+    /// Huella de `bytes` leídos desde `path`
+    pub fn from_bytes(path: &Path, bytes: &[u8]) -> Result<Self> {
+        let (len, modified_nanos) = file_stat(path)?;
+        Ok(Self {
+            len,
+            modified_nanos,
+            checksum: sha256_hex(bytes),
+        })
+    }
+
+    /// Verifica si tamaño y mtime del archivo coinciden (sin leerlo)
+    ///
+    /// Un mtime igual o posterior a `saved_at_nanos` no es concluyente (el
+    /// archivo pudo reescribirse dentro de la misma resolución del reloj),
+    /// así que en ese caso hay que comparar checksums.
+    pub fn matches_stat(&self, path: &Path, saved_at_nanos: u64) -> Result<bool> {
+        let (len, modified_nanos) = file_stat(path)?;
+        Ok(len == self.len
+            && modified_nanos == self.modified_nanos
+            && modified_nanos < saved_at_nanos)
+    }
+}
+
+/// Estado serializado de VoxelDB
+#[derive(Serialize, Deserialize)]
+pub struct IndexSnapshot {
+    /// Versión del formato
+    pub version: u32,
+
+    /// Momento de escritura del snapshot (nanosegundos desde epoch)
+    pub saved_at_nanos: u64,
+
+    /// Templates en memoria (ID → TemplateEntry)
+    pub templates: HashMap<String, TemplateEntry>,
+
+    /// Índice espacial
+    pub spatial_index: Octree<String>,
+
+    /// Índice por categoría
+    pub category_index: HashMap<TemplateCategory, Vec<String>>,
+
+    /// Índice por nombre
+    pub name_index: HashMap<String, String>,
+
+    /// Manifiesto: nombre de archivo `.json` → huella
+    pub manifest: HashMap<String, FileFingerprint>,
+
+    /// Embeddings de los templates con QPX
+    pub semantic_index: SemanticIndex,
+}
+
+impl IndexSnapshot {
+    /// Cargar snapshot desde `dir`; `None` si falta, está corrupto o es de otra versión
+    pub fn load(dir: &Path) -> Option<Self> {
+        let bytes = std::fs::read(dir.join(INDEX_SNAPSHOT_FILE_NAME)).ok()?;
+
+        match serde_cbor::from_slice::<IndexSnapshot>(&bytes) {
+            Ok(snapshot) if snapshot.version == INDEX_SNAPSHOT_VERSION => Some(snapshot),
+            Ok(snapshot) => {
+                tracing::info!(
+                    "Ignoring VoxelDB index snapshot v{} (expected v{})",
+                    snapshot.version,
+                    INDEX_SNAPSHOT_VERSION
+                );
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable VoxelDB index snapshot: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Vista prestada de `IndexSnapshot` para serializar sin clonar el estado
+///
+/// Mismos campos y orden que `IndexSnapshot`, que es quien la deserializa.
+#[derive(Serialize)]
+pub(crate) struct IndexSnapshotRef<'a> {
+    pub version: u32,
+    pub saved_at_nanos: u64,
+    pub templates: &'a HashMap<String, TemplateEntry>,
+    pub spatial_index: &'a Octree<String>,
+    pub category_index: &'a HashMap<TemplateCategory, Vec<String>>,
+    pub name_index: &'a HashMap<String, String>,
+    pub manifest: &'a HashMap<String, FileFingerprint>,
+    pub semantic_index: &'a SemanticIndex,
+}
+
+impl IndexSnapshotRef<'_> {
+    /// Escribir snapshot en `dir` (archivo temporal + rename atómico)
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let bytes = serde_cbor::to_vec(self)
+            .map_err(|e| VoxelDBError::SerializationError(e.to_string()))?;
+
+        let path = dir.join(INDEX_SNAPSHOT_FILE_NAME);
+        let tmp_path = path.with_extension("snapshot.tmp");
+        std::fs::write(&tmp_path, bytes)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+/// Nanosegundos desde epoch del reloj del sistema
+pub fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// SHA-256 hex de `bytes`
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn file_stat(path: &Path) -> Result<(u64, u64)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified_nanos = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    Ok((metadata.len(), modified_nanos))
+}
@@ -42,20 +42,31 @@
 //! }
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
 // QPX Native Format
-use crate::qpx::{QPXEncoder, QPXDecoder, QPXQuantumCore, TemplateMetadata as QPXTemplateMetadata, Pixel};
+use crate::qpx::{QPXEncoder, QPXDecoder, QPXQuantumCore, TemplateMetadata as QPXTemplateMetadata, Pixel, pixels_to_embedding};
+use crate::qpx::{QPXCoreSummary, QPXReader, ReadSeek, EntanglementKind, QPXEntanglement};
+use crate::qpx::{QPXTimelineEntry, TimelineEventKind};
+use crate::qpx::{QPXError, upgrade_file, upgrade_record};
 use crate::fbcu::{FBCUCore, CompressionType, FBCUMetadata};
 
 // Re-exports públicos
-pub use crate::voxeldb::octree::{Octree, OctreeNode};
+pub use crate::voxeldb::octree::{BoundingBox, Octree, OctreeNode};
+pub use crate::voxeldb::index_snapshot::{FileFingerprint, IndexSnapshot, INDEX_SNAPSHOT_FILE_NAME};
+pub use crate::voxeldb::pack_store::{PackEntry, QPXPackStore, QPX_PACK_FILE_NAME, QPX_PACK_INDEX_FILE_NAME};
+pub use crate::voxeldb::semantic::{embed_text, SemanticIndex, SemanticQuery, TEMPLATE_EMBEDDING_DIM};
 
 // Submódulos
+pub mod index_snapshot;
+
+use crate::voxeldb::index_snapshot::IndexSnapshotRef;
 pub mod octree;
+pub mod pack_store;
+pub mod semantic;
 
 /// Errores específicos de VoxelDB
 #[derive(Debug, thiserror::Error)]
@@ -235,6 +246,7 @@ impl TemplateEntry {
             y: 0.5, // Complejidad media por defecto
             z: 0.5, // Efectividad inicial promedio
         };
+        let now = Utc::now();
         
         Self {
             id: Self::generate_id(&name, &content),
@@ -246,8 +258,8 @@ impl TemplateEntry {
             telescope_refs: Vec::new(),
             effectiveness: EffectivenessMetrics::default(),
             metadata: TemplateMetadata {
-                created_at: Utc::now(),
-                updated_at: Utc::now(),
+                created_at: now,
+                updated_at: now,
                 version: "1.0".to_string(),
                 author: "bitacora".to_string(),
             },
@@ -398,6 +410,15 @@ pub struct VoxelDB {
     
     /// Índice por nombre
     name_index: HashMap<String, String>, // name → id
+    
+    /// Pack QPX (si está activo, reemplaza los `.qpxf` por template)
+    qpx_pack: Option<QPXPackStore>,
+    
+    /// Huellas de los `.json` indexados (para el snapshot incremental)
+    manifest: HashMap<String, FileFingerprint>,
+    
+    /// Índice vectorial de embeddings QPX (para query_semantic)
+    semantic_index: SemanticIndex,
 }
 
 impl VoxelDB {
@@ -411,15 +432,97 @@ impl VoxelDB {
         // Crear índice espacial Octree (resolución 100)
         let spatial_index = Octree::new(100);
         
+        // Abrir pack QPX si este directorio ya usa el layout empaquetado
+        let qpx_pack = if QPXPackStore::exists_in(&storage_path) {
+            Some(QPXPackStore::open(&storage_path)?)
+        } else {
+            None
+        };
+        
         Ok(Self {
             storage_path,
             templates: HashMap::new(),
             spatial_index,
             category_index: HashMap::new(),
             name_index: HashMap::new(),
+            qpx_pack,
+            manifest: HashMap::new(),
+            semantic_index: SemanticIndex::new(),
         })
     }
     
+    /// Activar almacenamiento QPX empaquetado (un solo archivo de registros + índice)
+    ///
+    /// Migra los `.qpxf` existentes en `templates/YYYY/MM/` al pack y los borra.
+    /// Es idempotente: con el pack ya activo solo migra archivos rezagados.
+    /// Retorna el número de archivos migrados.
+    pub fn enable_packed_storage(&mut self) -> Result<usize> {
+        if self.qpx_pack.is_none() {
+            self.qpx_pack = Some(QPXPackStore::open(&self.storage_path)?);
+        }
+        
+        let files = self.list_template_qpx_files()?;
+        if let Some(pack) = self.qpx_pack.as_mut() {
+            // Sin fsync por registro: un solo sync al final de la migración
+            pack.set_sync_on_write(false);
+            let result = append_qpx_files(pack, &files);
+            pack.set_sync_on_write(true);
+            result?;
+        }
+        
+        // Borrar los archivos solo después de que el pack esté en disco
+        for (_, path) in &files {
+            std::fs::remove_file(path)?;
+        }
+        let paths: Vec<PathBuf> = files.into_iter().map(|(_, path)| path).collect();
+        remove_empty_partition_dirs(&paths)?;
+        
+        Ok(paths.len())
+    }
+    
+    /// Verificar si el almacenamiento QPX empaquetado está activo
+    pub fn is_packed_storage(&self) -> bool {
+        self.qpx_pack.is_some()
+    }
+    
+    /// Compactar el pack QPX eliminando registros borrados o reemplazados
+    ///
+    /// Retorna los bytes recuperados (0 si el pack no está activo).
+    pub fn compact_qpx_pack(&mut self) -> Result<u64> {
+        match self.qpx_pack.as_mut() {
+            Some(pack) => pack.compact(),
+            None => Ok(0),
+        }
+    }
+
+    /// Reescribir a la versión QPX actual los registros escritos por versiones anteriores
+    ///
+    /// Cubre el pack y el layout por archivo. Retorna el número de registros
+    /// actualizados (0 si ya estaban todos al día).
+    pub fn upgrade_qpx_storage(&mut self) -> Result<usize> {
+        let to_voxel_error =
+            |e: QPXError| VoxelDBError::SerializationError(format!("QPX upgrade failed: {}", e));
+        let mut upgraded = 0;
+
+        if let Some(pack) = self.qpx_pack.as_mut() {
+            let ids: Vec<String> = pack.ids().map(str::to_string).collect();
+            for id in ids {
+                let Some(qpx_bytes) = pack.read(&id)? else { continue };
+                if let Some(new_bytes) = upgrade_record(&qpx_bytes).map_err(to_voxel_error)? {
+                    pack.append(&id, &new_bytes)?;
+                    upgraded += 1;
+                }
+            }
+        }
+        for (_, path) in self.list_template_qpx_files()? {
+            if upgrade_file(&path).map_err(to_voxel_error)? {
+                upgraded += 1;
+            }
+        }
+
+        Ok(upgraded)
+    }
+
     /// Insertar template en VoxelDB
     pub fn insert_template(&mut self, mut template: TemplateEntry) -> Result<String> {
         // Validar coordenadas
@@ -445,18 +548,8 @@ impl VoxelDB {
         // Guardar en disco
         self.save_template_to_disk(&template)?;
         
-        // Indexar en Octree
-        self.spatial_index.insert(template.coords, id.clone());
-        
-        // Indexar por categoría
-        self.category_index
-            .entry(template.category)
-            .or_insert_with(Vec::new)
-            .push(id.clone());
-        
-        // Indexar por nombre (solo primera ocurrencia para búsqueda rápida)
-        self.name_index.entry(template.name.clone())
-            .or_insert_with(|| id.clone());
+        // Indexar en Octree, por categoría y por nombre (solo primera ocurrencia)
+        self.index_template(&template);
         
         // Guardar en memoria
         self.templates.insert(id.clone(), template);
@@ -486,6 +579,100 @@ impl VoxelDB {
         Ok(results.into_iter().map(|(t, _)| t).collect())
     }
     
+    /// Query kNN: los `k` templates más cercanos a `coords` con su distancia
+    pub fn query_nearest(&self, coords: CubicCoords, k: usize) -> Result<Vec<(TemplateEntry, f64)>> {
+        Ok(self.spatial_index
+            .query_nearest(coords, k)
+            .into_iter()
+            .filter_map(|(id, distance)| {
+                self.templates.get(&id).map(|template| (template.clone(), distance))
+            })
+            .collect())
+    }
+    
+    /// Query por caja alineada a los ejes entre dos esquinas opuestas
+    ///
+    /// Cada resultado lleva su distancia al centro de la caja (más cercano primero).
+    pub fn query_box(&self, corner_a: CubicCoords, corner_b: CubicCoords) -> Result<Vec<(TemplateEntry, f64)>> {
+        let bounds = BoundingBox::from_corners(corner_a, corner_b);
+        Ok(self.spatial_index
+            .query_box(bounds)
+            .into_iter()
+            .filter_map(|(id, distance)| {
+                self.templates.get(&id).map(|template| (template.clone(), distance))
+            })
+            .collect())
+    }
+    
+    /// Query semántica: los `k` templates más similares (coseno) a un texto o embedding
+    ///
+    /// Usa los embeddings de 384D guardados en los píxeles QPX; templates sin
+    /// QPX no participan. Un embedding explícito de otra dimensión es un error.
+    pub fn query_semantic(
+        &self,
+        query: impl Into<SemanticQuery>,
+        k: usize,
+    ) -> Result<Vec<(TemplateEntry, f32)>> {
+        let embedding = query.into().to_embedding();
+        if embedding.len() != TEMPLATE_EMBEDDING_DIM {
+            return Err(VoxelDBError::ValidationFailed(format!(
+                "semantic query has {} dimensions, expected {}",
+                embedding.len(),
+                TEMPLATE_EMBEDDING_DIM
+            )));
+        }
+        if embedding.iter().all(|x| *x == 0.0) {
+            return Err(VoxelDBError::ValidationFailed(
+                "semantic query has no usable terms or is a zero vector".to_string(),
+            ));
+        }
+        
+        Ok(self.semantic_index
+            .ranked(&embedding)
+            .into_iter()
+            .filter_map(|(id, similarity)| {
+                self.templates.get(&id).map(|template| (template.clone(), similarity))
+            })
+            .take(k)
+            .collect())
+    }
+    
+    /// Poner al día el índice semántico con los QPX en disco
+    ///
+    /// Solo se decodifican los píxeles de templates aún sin embedding o en
+    /// `stale` (templates re-cargados); los embeddings de templates que ya no
+    /// existen se descartan. Retorna `true` si el índice cambió.
+    fn refresh_semantic_index(&mut self, mut stale: HashSet<String>) -> Result<bool> {
+        let before = self.semantic_index.len();
+        let templates = &self.templates;
+        self.semantic_index.retain(|id| templates.contains_key(id));
+        let mut changed = self.semantic_index.len() != before;
+        
+        let mut sources: Vec<String> = self.qpx_pack
+            .as_ref()
+            .map(|pack| pack.ids().map(str::to_string).collect())
+            .unwrap_or_default();
+        sources.extend(self.list_template_qpx_files()?.into_iter().map(|(id, _)| id));
+        
+        for id in sources {
+            let needs_index = !self.semantic_index.contains(&id) || stale.remove(&id);
+            if !self.templates.contains_key(&id) || !needs_index {
+                continue;
+            }
+            match self.load_template_qpx_pixels(&id) {
+                Ok(pixels) => {
+                    self.semantic_index.insert(&id, &pixels_to_embedding(&pixels));
+                    changed = true;
+                }
+                Err(e) => {
+                    eprintln!("Warning: Failed to index embedding for template {}: {}", id, e);
+                }
+            }
+        }
+        
+        Ok(changed)
+    }
+    
     /// Obtener template por ID
     pub fn get_by_id(&self, id: &str) -> Result<TemplateEntry> {
         self.templates
@@ -553,7 +740,11 @@ impl VoxelDB {
         // Guardar cambios en disco
         self.save_template_to_disk(&template_clone)?;
         
-        Ok(())
+        // Registrar la actualización en la timeline del QPX
+        let updated_at = template_clone.metadata.updated_at;
+        self.rewrite_template_qpx(&template_clone, |quantum_core| {
+            QPXTimelineEntry::push(&mut quantum_core.timeline, TimelineEventKind::Updated, updated_at);
+        })
     }
     
     /// Vincular template con entrada biográfica de TelescopeDB
@@ -576,6 +767,18 @@ impl VoxelDB {
         // Guardar cambios
         self.save_template_to_disk(&template_clone)?;
         
+        // Persistir el vínculo como entanglement en el QPX del template
+        let linked_at = template_clone.metadata.updated_at;
+        self.rewrite_template_qpx(&template_clone, |quantum_core| {
+            let already_linked = quantum_core.entanglements.iter().any(|e| {
+                e.kind == EntanglementKind::Telescope && e.target_id == telescope_id
+            });
+            if !already_linked {
+                quantum_core.entanglements.push(QPXEntanglement::new(telescope_id, EntanglementKind::Telescope));
+                QPXTimelineEntry::push(&mut quantum_core.timeline, TimelineEventKind::Linked, linked_at);
+            }
+        })?;
+        
         Ok(())
     }
     
@@ -593,6 +796,194 @@ impl VoxelDB {
         templates.into_iter().take(k).collect()
     }
     
+    /// Eliminar template: lo saca del Octree, de los índices y borra sus archivos
+    ///
+    /// Se eliminan tanto `{id}.json` como los `.qpxf` asociados, de modo que
+    /// `load_all_from_disk` no vuelva a cargarlo.
+    pub fn delete_template(&mut self, template_id: &str) -> Result<TemplateEntry> {
+        let template = self.templates
+            .remove(template_id)
+            .ok_or_else(|| VoxelDBError::TemplateNotFound(template_id.to_string()))?;
+        
+        self.unindex_template(&template);
+        
+        // Borrar QPX antes que el JSON: si falla a mitad, el GC recoge el resto
+        if let Some(pack) = self.qpx_pack.as_mut() {
+            pack.remove(&template.id)?;
+        }
+        self.semantic_index.remove(&template.id);
+        for (id, path) in self.list_template_qpx_files()? {
+            if id == template.id {
+                std::fs::remove_file(path)?;
+            }
+        }
+        
+        let file_name = format!("{}.json", template.id);
+        let json_path = self.storage_path.join(&file_name);
+        if json_path.exists() {
+            std::fs::remove_file(json_path)?;
+        }
+        self.manifest.remove(&file_name);
+        
+        Ok(template)
+    }
+    
+    /// Renombrar template manteniendo su ID (las referencias siguen siendo válidas)
+    ///
+    /// Actualiza el índice de nombres, el JSON y el `concept_name` del `.qpxf` si existe.
+    pub fn rename_template(&mut self, template_id: &str, new_name: &str) -> Result<()> {
+        if new_name.trim().is_empty() {
+            return Err(VoxelDBError::ValidationFailed("template name cannot be empty".to_string()));
+        }
+        
+        let template = self.templates
+            .get_mut(template_id)
+            .ok_or_else(|| VoxelDBError::TemplateNotFound(template_id.to_string()))?;
+        
+        if template.name == new_name {
+            return Ok(());
+        }
+        
+        let old_name = std::mem::replace(&mut template.name, new_name.to_string());
+        template.metadata.updated_at = Utc::now();
+        let template_clone = template.clone();
+        
+        // Índice por nombre: liberar el nombre anterior y registrar el nuevo
+        self.release_name(&old_name, template_id);
+        self.name_index
+            .entry(new_name.to_string())
+            .or_insert_with(|| template_id.to_string());
+        
+        self.save_template_to_disk(&template_clone)?;
+        
+        // Reescribir metadata del QPX (el nombre viaja en concept_name)
+        let renamed_at = template_clone.metadata.updated_at;
+        self.rewrite_template_qpx(&template_clone, |quantum_core| {
+            quantum_core.metadata.concept_name = new_name.to_string();
+            QPXTimelineEntry::push(&mut quantum_core.timeline, TimelineEventKind::Renamed, renamed_at);
+        })
+    }
+    
+    /// Decodificar, modificar y volver a escribir el QPX de un template
+    ///
+    /// Cubre el pack y el layout por archivo (en la ruta derivada de
+    /// `created_at`, sin recorrer `templates/`); sin QPX no hace nada.
+    fn rewrite_template_qpx(
+        &mut self,
+        template: &TemplateEntry,
+        update: impl Fn(&mut QPXQuantumCore),
+    ) -> Result<()> {
+        let rewrite = |qpx_bytes: &[u8]| -> Result<Vec<u8>> {
+            let mut quantum_core = QPXDecoder::decode_quantum_core(qpx_bytes)
+                .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)))?;
+            update(&mut quantum_core);
+            QPXEncoder::encode_quantum_core(&quantum_core)
+                .map_err(|e| VoxelDBError::SerializationError(format!("QPX encoding failed: {}", e)))
+        };
+        
+        if let Some(pack) = self.qpx_pack.as_mut() {
+            if let Some(qpx_bytes) = pack.read(&template.id)? {
+                pack.append(&template.id, &rewrite(&qpx_bytes)?)?;
+            }
+        }
+        let qpx_path = self.get_template_qpx_path(&template.id, template.metadata.created_at);
+        match std::fs::read(&qpx_path) {
+            Ok(qpx_bytes) => write_qpx_file(&qpx_path, &rewrite(&qpx_bytes)?)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        
+        Ok(())
+    }
+    
+    /// Garbage collection: borrar `.qpxf` cuyo ID no está referenciado por ningún template
+    ///
+    /// Los templates vivos son los `{id}.json` en disco (más los que haya en
+    /// memoria), así que es seguro llamarlo sobre un handle recién abierto sin
+    /// `load_all_from_disk`. También elimina los directorios de año/mes que
+    /// quedan vacíos y marca como borrados los registros huérfanos del pack
+    /// (ver `compact_qpx_pack`). Devuelve las rutas borradas.
+    pub fn gc_orphaned_qpx(&mut self) -> Result<Vec<PathBuf>> {
+        let live = self.live_template_ids()?;
+        let mut removed = Vec::new();
+        
+        for (id, path) in self.list_template_qpx_files()? {
+            if !live.contains(&id) {
+                std::fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        remove_empty_partition_dirs(&removed)?;
+        
+        if let Some(pack) = self.qpx_pack.as_mut() {
+            let orphans: Vec<String> = pack
+                .ids()
+                .filter(|id| !live.contains(*id))
+                .map(str::to_string)
+                .collect();
+            for id in orphans {
+                pack.remove(&id)?;
+            }
+        }
+        
+        // Sin QPX no hay embedding: los huérfanos salen también del índice semántico
+        self.semantic_index.retain(|id| live.contains(id));
+        
+        Ok(removed)
+    }
+    
+    /// IDs de los templates persistidos (`{id}.json`) o registrados en memoria
+    fn live_template_ids(&self) -> Result<HashSet<String>> {
+        let mut ids: HashSet<String> = self.templates.keys().cloned().collect();
+        for entry in std::fs::read_dir(&self.storage_path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                ids.insert(id.to_string());
+            }
+        }
+        Ok(ids)
+    }
+    
+    /// Sacar template del Octree y de los índices por categoría y nombre
+    fn unindex_template(&mut self, template: &TemplateEntry) {
+        self.spatial_index.remove(template.coords, &template.id);
+        
+        if let Some(ids) = self.category_index.get_mut(&template.category) {
+            ids.retain(|id| id != &template.id);
+            if ids.is_empty() {
+                self.category_index.remove(&template.category);
+            }
+        }
+        
+        self.release_name(&template.name, &template.id);
+    }
+    
+    /// Liberar `name` si apuntaba a `template_id`, reasignándolo a otro template
+    /// con el mismo nombre (los nombres pueden repetirse)
+    fn release_name(&mut self, name: &str, template_id: &str) {
+        if self.name_index.get(name).map(String::as_str) != Some(template_id) {
+            return;
+        }
+        
+        let replacement = self.templates
+            .values()
+            .filter(|t| t.name == name && t.id != template_id)
+            .map(|t| t.id.clone())
+            .min();
+        
+        match replacement {
+            Some(id) => {
+                self.name_index.insert(name.to_string(), id);
+            }
+            None => {
+                self.name_index.remove(name);
+            }
+        }
+    }
+    
     /// Obtener estadísticas de VoxelDB
     pub fn stats(&self) -> VoxelDBStats {
         VoxelDBStats {
@@ -621,23 +1012,16 @@ impl VoxelDB {
     
     // === Operaciones de persistencia ===
     
-    /// Guardar template en disco
-    fn save_template_to_disk(&self, template: &TemplateEntry) -> Result<()> {
-        let file_path = self.storage_path.join(format!("{}.json", template.id));
+    /// Guardar template en disco (y registrar su huella en el manifiesto)
+    fn save_template_to_disk(&mut self, template: &TemplateEntry) -> Result<()> {
+        let file_name = format!("{}.json", template.id);
+        let file_path = self.storage_path.join(&file_name);
         let json = serde_json::to_string_pretty(template)
             .map_err(|e| VoxelDBError::SerializationError(e.to_string()))?;
-        std::fs::write(file_path, json)?;
+        std::fs::write(&file_path, &json)?;
+        self.manifest.insert(file_name, FileFingerprint::from_bytes(&file_path, json.as_bytes())?);
         Ok(())
     }
-    
-    /// Cargar template desde disco
-    fn load_template_from_disk(&self, id: &str) -> Result<TemplateEntry> {
-        let file_path = self.storage_path.join(format!("{}.json", id));
-        let json = std::fs::read_to_string(file_path)?;
-        let template = serde_json::from_str(&json)
-            .map_err(|e| VoxelDBError::SerializationError(e.to_string()))?;
-        Ok(template)
-    }
 
     // ========================================================================
     // QPX NATIVE FORMAT METHODS (v1.5+)
@@ -660,7 +1044,9 @@ impl VoxelDB {
 
     /// Write template as QPX binary format
     /// 
-    /// Converts TemplateEntry → QPXQuantumCore → .qpxf binary file
+    /// Converts TemplateEntry → QPXQuantumCore → .qpxf binary file.
+    /// With packed storage enabled the record is appended to the pack instead
+    /// and the pack path is returned.
     /// 
     /// # Arguments
     /// * `template` - Template to encode
@@ -670,7 +1056,7 @@ impl VoxelDB {
     /// # Returns
     /// * Path where .qpxt file was written
     pub fn write_template_qpx(
-        &self,
+        &mut self,
         template: &TemplateEntry,
         fbcu_core: FBCUCore,
         pixels: Vec<Pixel>,
@@ -694,6 +1080,20 @@ impl VoxelDB {
             50  // Deprecated template (low effectiveness)
         };
 
+        // Index the embedding carried by the pixels for query_semantic
+        self.semantic_index.insert(&template.id, &pixels_to_embedding(&pixels));
+
+        // Temporal history: the previous record's timeline (updates, renames,
+        // links) is carried over; only changes newer than it are appended
+        let qpx_path = self.get_template_qpx_path(&template.id, template.metadata.created_at);
+        let mut timeline = self.previous_template_timeline(&template.id, &qpx_path)?;
+        if timeline.is_empty() {
+            QPXTimelineEntry::push(&mut timeline, TimelineEventKind::Created, template.metadata.created_at);
+        }
+        if timeline.last().is_some_and(|entry| template.metadata.updated_at > entry.timestamp) {
+            QPXTimelineEntry::push(&mut timeline, TimelineEventKind::Updated, template.metadata.updated_at);
+        }
+
         // Create quantum core
         let quantum_core = QPXQuantumCore {
             fbcu_core,
@@ -702,26 +1102,67 @@ impl VoxelDB {
             metadata: qpx_metadata,
             timestamp: template.metadata.created_at,
             checksum: self.compute_template_checksum(template),
+            entanglements: template.telescope_refs
+                .iter()
+                .map(|telescope_id| QPXEntanglement::new(telescope_id.as_str(), EntanglementKind::Telescope))
+                .collect(),
+            branches: Vec::new(),
+            timeline,
+            context: None,
         };
 
         // Encode to QPX binary
         let qpx_bytes = QPXEncoder::encode_quantum_core(&quantum_core)
             .map_err(|e| VoxelDBError::SerializationError(format!("QPX encoding failed: {}", e)))?;
 
-        // Generate path with year/month partitioning
-        let qpx_path = self.get_template_qpx_path(&template.id, template.metadata.created_at);
+        if let Some(pack) = self.qpx_pack.as_mut() {
+            pack.append(&template.id, &qpx_bytes)?;
+            return Ok(pack.path().to_path_buf());
+        }
 
-        // Create directory structure
+        // Create directory structure (year/month partitioning)
         if let Some(parent) = qpx_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
         // Write to disk
-        std::fs::write(&qpx_path, qpx_bytes)?;
+        write_qpx_file(&qpx_path, &qpx_bytes)?;
 
         Ok(qpx_path)
     }
 
+    /// Timeline of the record `write_template_qpx` is about to replace
+    ///
+    /// Looks only at the template's own pack record or `qpx_path`. An
+    /// unreadable record is replaced rather than blocking the write, so it
+    /// counts as having no history.
+    fn previous_template_timeline(
+        &self,
+        template_id: &str,
+        qpx_path: &Path,
+    ) -> Result<Vec<QPXTimelineEntry>> {
+        let source: Box<dyn ReadSeek> = match self.qpx_pack.as_ref() {
+            Some(pack) => match pack.read(template_id)? {
+                Some(qpx_bytes) => Box::new(std::io::Cursor::new(qpx_bytes)),
+                None => return Ok(Vec::new()),
+            },
+            None => match std::fs::File::open(qpx_path) {
+                Ok(file) => Box::new(std::io::BufReader::new(file)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e.into()),
+            },
+        };
+
+        match QPXReader::new(source).and_then(|mut reader| reader.read_timeline()) {
+            Ok(timeline) => Ok(timeline),
+            Err(QPXError::Io(e)) if e.kind() != std::io::ErrorKind::UnexpectedEof => Err(e.into()),
+            Err(e) => {
+                tracing::warn!("Replacing unreadable QPX record of template {}: {}", template_id, e);
+                Ok(Vec::new())
+            }
+        }
+    }
+
     /// Read template from QPX binary format
     /// 
     /// # Arguments
@@ -736,9 +1177,72 @@ impl VoxelDB {
             .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)))
     }
 
+    /// Load a template's QPX data by ID from whichever layout holds it
+    ///
+    /// Checks the pack first (when enabled), then the per-file layout.
+    pub fn load_template_qpx(&self, template_id: &str) -> Result<QPXQuantumCore> {
+        if let Some(pack) = &self.qpx_pack {
+            if let Some(qpx_bytes) = pack.read(template_id)? {
+                return QPXDecoder::decode_quantum_core(&qpx_bytes)
+                    .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)));
+            }
+        }
+
+        let qpx_path = self.find_template_qpx(template_id)?;
+        self.read_template_qpx(&qpx_path)
+    }
+
+    /// Open a lazy QPX reader for a template ID (header parsed, nothing else)
+    ///
+    /// Per-file templates are streamed from disk; packed ones are read from
+    /// the pack record.
+    pub fn open_template_qpx(&self, template_id: &str) -> Result<QPXReader<Box<dyn ReadSeek>>> {
+        let source: Box<dyn ReadSeek> = match self.qpx_pack.as_ref().map(|pack| pack.read(template_id)) {
+            Some(Ok(Some(qpx_bytes))) => Box::new(std::io::Cursor::new(qpx_bytes)),
+            Some(Err(e)) => return Err(e),
+            _ => {
+                let qpx_path = self.find_template_qpx(template_id)?;
+                Box::new(std::io::BufReader::new(std::fs::File::open(qpx_path)?))
+            }
+        };
+
+        QPXReader::new(source)
+            .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)))
+    }
+
+    /// Load a template's QPX metadata without decoding the FBCU blob
+    pub fn load_template_qpx_summary(&self, template_id: &str) -> Result<QPXCoreSummary> {
+        self.open_template_qpx(template_id)?
+            .read_summary()
+            .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)))
+    }
+
+    /// Load only a template's QPX entanglements (e.g. TelescopeDB links)
+    pub fn load_template_entanglements(&self, template_id: &str) -> Result<Vec<QPXEntanglement>> {
+        self.open_template_qpx(template_id)?
+            .read_entanglements()
+            .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)))
+    }
+
+    /// Load only a template's QPX timeline (creation, updates, renames, links)
+    pub fn load_template_timeline(&self, template_id: &str) -> Result<Vec<QPXTimelineEntry>> {
+        self.open_template_qpx(template_id)?
+            .read_timeline()
+            .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)))
+    }
+
+    /// Load only a template's QPX pixels (semantic embedding)
+    pub fn load_template_qpx_pixels(&self, template_id: &str) -> Result<Vec<Pixel>> {
+        self.open_template_qpx(template_id)?
+            .read_pixels()
+            .map_err(|e| VoxelDBError::SerializationError(format!("QPX decoding failed: {}", e)))
+    }
+
     /// Find QPX file for a template ID
     /// 
-    /// Searches in year/month directories for the template's .qpxt file
+    /// Searches in year/month directories for the template's .qpxt file.
+    /// Only covers the per-file layout; use `load_template_qpx` to also
+    /// read from packed storage.
     pub fn find_template_qpx(&self, template_id: &str) -> Result<PathBuf> {
         let templates_dir = self.storage_path.join("templates");
         
@@ -781,6 +1285,51 @@ impl VoxelDB {
         ))
     }
 
+    /// List every `.qpxf` under `templates/YYYY/MM/` as `(template_id, path)`
+    ///
+    /// The ID is parsed from the `{YYYYMM}_{id}.qpxf` filename.
+    fn list_template_qpx_files(&self) -> Result<Vec<(String, PathBuf)>> {
+        let templates_dir = self.storage_path.join("templates");
+        let mut files = Vec::new();
+
+        if !templates_dir.exists() {
+            return Ok(files);
+        }
+
+        for year_entry in std::fs::read_dir(&templates_dir)? {
+            let year_entry = year_entry?;
+            if !year_entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            for month_entry in std::fs::read_dir(year_entry.path())? {
+                let month_entry = month_entry?;
+                if !month_entry.file_type()?.is_dir() {
+                    continue;
+                }
+
+                for file_entry in std::fs::read_dir(month_entry.path())? {
+                    let path = file_entry?.path();
+                    if path.extension().and_then(|s| s.to_str()) != Some("qpxf") {
+                        continue;
+                    }
+
+                    let id = path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .and_then(|stem| stem.split_once('_'))
+                        .map(|(_, id)| id.to_string());
+
+                    if let Some(id) = id {
+                        files.push((id, path));
+                    }
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
     /// Compute SHA256 checksum for template content
     fn compute_template_checksum(&self, template: &TemplateEntry) -> [u8; 32] {
         use sha2::{Sha256, Digest};
@@ -795,41 +1344,172 @@ impl VoxelDB {
     }
     
     /// Cargar todos los templates desde disco
+    ///
+    /// Si existe un snapshot del índice (`voxel_index.snapshot`) se carga con una
+    /// sola lectura y solo se decodifican los `.json` nuevos o cuyo checksum
+    /// cambió; los que desaparecieron se des-indexan. Los embeddings también
+    /// vienen del snapshot: solo se abren los QPX de templates nuevos o
+    /// re-cargados. Al terminar se reescribe el snapshot si hubo cambios.
+    /// Retorna el número de templates en memoria.
     pub fn load_all_from_disk(&mut self) -> Result<usize> {
-        let mut count = 0;
+        let started_at = index_snapshot::now_nanos();
+        
+        // El snapshot solo reemplaza el estado si aún no hay nada en memoria
+        let mut saved_at = 0;
+        let mut changed = true;
+        if self.templates.is_empty() {
+            if let Some(snapshot) = IndexSnapshot::load(&self.storage_path) {
+                saved_at = snapshot.saved_at_nanos;
+                self.templates = snapshot.templates;
+                self.spatial_index = snapshot.spatial_index;
+                self.category_index = snapshot.category_index;
+                self.name_index = snapshot.name_index;
+                self.manifest = snapshot.manifest;
+                self.semantic_index = snapshot.semantic_index;
+                changed = false;
+            }
+        }
         
+        let mut seen = HashSet::new();
+        let mut reloaded = HashSet::new();
         for entry in std::fs::read_dir(&self.storage_path)? {
-            let entry = entry?;
-            let path = entry.path();
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(file_name) = path.file_name().and_then(|s| s.to_str()).map(str::to_string) else {
+                continue;
+            };
+            seen.insert(file_name.clone());
             
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    match self.load_template_from_disk(stem) {
-                        Ok(template) => {
-                            let id = template.id.clone();
-                            
-                            // Re-indexar
-                            self.spatial_index.insert(template.coords, id.clone());
-                            self.category_index
-                                .entry(template.category)
-                                .or_insert_with(Vec::new)
-                                .push(id.clone());
-                            self.name_index.insert(template.name.clone(), id.clone());
-                            
-                            // Guardar en memoria
-                            self.templates.insert(id, template);
-                            count += 1;
-                        }
-                        Err(e) => {
-                            eprintln!("Warning: Failed to load template {}: {}", stem, e);
-                        }
+            // Sin cambios según tamaño/mtime: ni siquiera se lee
+            if let Some(fingerprint) = self.manifest.get(&file_name) {
+                if fingerprint.matches_stat(&path, saved_at)? {
+                    continue;
+                }
+            }
+            
+            let bytes = std::fs::read(&path)?;
+            let fingerprint = FileFingerprint::from_bytes(&path, &bytes)?;
+            let same_content = self.manifest
+                .get(&file_name)
+                .is_some_and(|old| old.checksum == fingerprint.checksum);
+            
+            if same_content {
+                // Solo cambió el mtime: refrescar manifiesto sin decodificar
+                self.manifest.insert(file_name, fingerprint);
+                changed = true;
+                continue;
+            }
+            
+            match serde_json::from_slice::<TemplateEntry>(&bytes) {
+                Ok(template) => {
+                    if let Some(old) = self.templates.remove(&template.id) {
+                        self.unindex_template(&old);
                     }
+                    self.index_template(&template);
+                    reloaded.insert(template.id.clone());
+                    self.templates.insert(template.id.clone(), template);
+                    self.manifest.insert(file_name, fingerprint);
+                    changed = true;
+                }
+                Err(e) => {
+                    eprintln!("Warning: Failed to load template {}: {}", file_name, e);
                 }
             }
         }
         
-        Ok(count)
+        // Archivos del manifiesto que ya no están en disco
+        let vanished: Vec<String> = self.manifest
+            .keys()
+            .filter(|file_name| !seen.contains(*file_name))
+            .cloned()
+            .collect();
+        for file_name in vanished {
+            self.manifest.remove(&file_name);
+            let id = file_name.trim_end_matches(".json");
+            if let Some(old) = self.templates.remove(id) {
+                self.unindex_template(&old);
+            }
+            changed = true;
+        }
+        
+        changed |= self.refresh_semantic_index(reloaded)?;
+        
+        if changed {
+            self.write_index_snapshot(started_at)?;
+        }
+        
+        Ok(self.templates.len())
     }
+    
+    /// Persistir snapshot del índice (templates, Octree, índices y manifiesto)
+    ///
+    /// `load_all_from_disk` lo reescribe automáticamente cuando detecta cambios;
+    /// llamarlo tras una sesión de escrituras evita re-decodificarlas al arrancar.
+    pub fn save_index_snapshot(&self) -> Result<()> {
+        self.write_index_snapshot(index_snapshot::now_nanos())
+    }
+    
+    fn write_index_snapshot(&self, saved_at_nanos: u64) -> Result<()> {
+        IndexSnapshotRef {
+            version: index_snapshot::INDEX_SNAPSHOT_VERSION,
+            saved_at_nanos,
+            templates: &self.templates,
+            spatial_index: &self.spatial_index,
+            category_index: &self.category_index,
+            name_index: &self.name_index,
+            manifest: &self.manifest,
+            semantic_index: &self.semantic_index,
+        }
+        .save(&self.storage_path)
+    }
+    
+    /// Indexar template en Octree, categoría y nombre (no lo guarda en memoria)
+    fn index_template(&mut self, template: &TemplateEntry) {
+        self.spatial_index.insert(template.coords, template.id.clone());
+        self.category_index
+            .entry(template.category)
+            .or_default()
+            .push(template.id.clone());
+        self.name_index
+            .entry(template.name.clone())
+            .or_insert_with(|| template.id.clone());
+    }
+}
+
+/// Copiar `.qpxf` al pack y dejarlo persistido (registros + índice)
+fn append_qpx_files(pack: &mut QPXPackStore, files: &[(String, PathBuf)]) -> Result<()> {
+    for (id, path) in files {
+        pack.append(id, &std::fs::read(path)?)?;
+    }
+    pack.sync()?;
+    pack.flush_index()
+}
+
+/// Escribir un `.qpxf` vía archivo temporal + rename (nunca queda a medias)
+fn write_qpx_file(path: &Path, qpx_bytes: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("qpxf.tmp");
+    std::fs::write(&tmp_path, qpx_bytes)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Eliminar directorios `templates/YYYY/MM` (y el de año) que quedaron vacíos
+fn remove_empty_partition_dirs(removed: &[PathBuf]) -> Result<()> {
+    for path in removed {
+        let month_dir = path.parent();
+        let year_dir = month_dir.and_then(|p| p.parent());
+        for dir in [month_dir, year_dir].into_iter().flatten() {
+            let is_empty = std::fs::read_dir(dir)
+                .map(|mut entries| entries.next().is_none())
+                .unwrap_or(false);
+            if is_empty {
+                std::fs::remove_dir(dir)?;
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Estadísticas de VoxelDB
@@ -921,7 +1601,7 @@ mod tests {
         use tempfile::tempdir;
         
         let temp_dir = tempdir().unwrap();
-        let voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
         
         // Create test template
         let template = TemplateEntry::new(
@@ -943,6 +1623,8 @@ mod tests {
                 original_hash: "test_hash".into(),
                 wavelet_level: Some(3),
                 fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
             },
         };
 
@@ -973,7 +1655,7 @@ mod tests {
         use tempfile::tempdir;
         
         let temp_dir = tempdir().unwrap();
-        let voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
         
         // High effectiveness template
         let mut template_high = TemplateEntry::new(
@@ -995,6 +1677,8 @@ mod tests {
                 original_hash: "hash".into(),
                 wavelet_level: None,
                 fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
             },
         };
 
@@ -1010,7 +1694,7 @@ mod tests {
         use tempfile::tempdir;
         
         let temp_dir = tempdir().unwrap();
-        let voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
         
         // Create and write template
         let template = TemplateEntry::new(
@@ -1031,6 +1715,8 @@ mod tests {
                 original_hash: "xyz".into(),
                 wavelet_level: Some(2),
                 fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
             },
         };
 
@@ -1043,4 +1729,598 @@ mod tests {
         assert!(found_path.exists());
         assert!(found_path.to_string_lossy().contains(&template.id));
     }
+
+    fn sample_fbcu(id: &str) -> FBCUCore {
+        FBCUCore {
+            id: id.into(),
+            compression_type: CompressionType::Hybrid,
+            compressed_data: vec![1, 2, 3],
+            original_size: 100,
+            compression_ratio: 0.03,
+            metadata: FBCUMetadata {
+                compressed_at: Utc::now().to_rfc3339(),
+                compression_time_ms: 1,
+                original_hash: "hash".into(),
+                wavelet_level: None,
+                fractal_level: None,
+                dictionary_id: None,
+                codec_selection: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_delete_template_removes_indexes_and_files() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let template = TemplateEntry::new(
+            "to_delete".to_string(),
+            TemplateCategory::Technical,
+            "content".to_string(),
+        );
+        let qpx_path = voxel
+            .write_template_qpx(&template, sample_fbcu("del"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+        let id = voxel.insert_template(template.clone()).unwrap();
+
+        let deleted = voxel.delete_template(&id).unwrap();
+        assert_eq!(deleted.name, "to_delete");
+        assert!(voxel.get_by_id(&id).is_err());
+        assert!(voxel.get_by_name("to_delete").is_err());
+        assert!(voxel.query_by_category(TemplateCategory::Technical).is_err());
+        assert!(voxel.query_spatial(template.coords, 0.1).unwrap().is_empty());
+        assert!(!qpx_path.exists());
+        assert!(!temp_dir.path().join(format!("{}.json", id)).exists());
+        assert!(matches!(voxel.delete_template(&id), Err(VoxelDBError::TemplateNotFound(_))));
+
+        // Recargar desde disco no resucita el template
+        let mut reloaded = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(reloaded.load_all_from_disk().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_delete_template_reassigns_shared_name() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let first = voxel.insert_template(TemplateEntry::new(
+            "shared".to_string(),
+            TemplateCategory::Creative,
+            "one".to_string(),
+        )).unwrap();
+        let second = voxel.insert_template(TemplateEntry::new(
+            "shared".to_string(),
+            TemplateCategory::Creative,
+            "two".to_string(),
+        )).unwrap();
+
+        voxel.delete_template(&first).unwrap();
+        assert_eq!(voxel.get_by_name("shared").unwrap().id, second);
+        assert_eq!(voxel.query_by_category(TemplateCategory::Creative).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rename_template_updates_index_and_qpx() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let template = TemplateEntry::new(
+            "old_name".to_string(),
+            TemplateCategory::Meta,
+            "content".to_string(),
+        );
+        let qpx_path = voxel
+            .write_template_qpx(&template, sample_fbcu("ren"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+        let id = voxel.insert_template(template).unwrap();
+
+        voxel.rename_template(&id, "new_name").unwrap();
+
+        assert!(voxel.get_by_name("old_name").is_err());
+        assert_eq!(voxel.get_by_name("new_name").unwrap().id, id);
+        assert_eq!(voxel.read_template_qpx(&qpx_path).unwrap().metadata.concept_name, "new_name");
+        assert!(voxel.rename_template(&id, "  ").is_err());
+
+        let mut reloaded = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        reloaded.load_all_from_disk().unwrap();
+        assert_eq!(reloaded.get_by_id(&id).unwrap().name, "new_name");
+    }
+
+    #[test]
+    fn test_gc_orphaned_qpx() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let kept = TemplateEntry::new("kept".to_string(), TemplateCategory::Technical, "a".to_string());
+        let kept_path = voxel
+            .write_template_qpx(&kept, sample_fbcu("kept"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+        voxel.insert_template(kept).unwrap();
+
+        // Huérfano: QPX escrito pero sin template registrado, en otro mes
+        let mut orphan = TemplateEntry::new("orphan".to_string(), TemplateCategory::Technical, "b".to_string());
+        orphan.metadata.created_at = DateTime::parse_from_rfc3339("2020-01-15T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let orphan_path = voxel
+            .write_template_qpx(&orphan, sample_fbcu("orphan"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+
+        let removed = voxel.gc_orphaned_qpx().unwrap();
+        assert_eq!(removed, vec![orphan_path.clone()]);
+        assert!(kept_path.exists());
+        assert!(!orphan_path.exists());
+        assert!(!temp_dir.path().join("templates").join("2020").exists());
+
+        assert!(voxel.gc_orphaned_qpx().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gc_orphaned_qpx_on_fresh_handle() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        let kept = TemplateEntry::new("kept".to_string(), TemplateCategory::Technical, "a".to_string());
+        let kept_path = voxel
+            .write_template_qpx(&kept, sample_fbcu("kept"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+        voxel.insert_template(kept).unwrap();
+        let orphan = TemplateEntry::new("orphan".to_string(), TemplateCategory::Technical, "b".to_string());
+        let orphan_path = voxel
+            .write_template_qpx(&orphan, sample_fbcu("orphan"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+        drop(voxel);
+
+        // Sin `load_all_from_disk`: los templates en disco siguen vivos
+        let mut reopened = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(reopened.gc_orphaned_qpx().unwrap(), vec![orphan_path]);
+        assert!(kept_path.exists());
+    }
+
+    #[test]
+    fn test_packed_storage_migration_and_reopen() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        // Layout por archivo antes de migrar
+        let legacy = TemplateEntry::new("legacy".to_string(), TemplateCategory::Technical, "a".to_string());
+        let legacy_path = voxel
+            .write_template_qpx(&legacy, sample_fbcu("legacy"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+        voxel.insert_template(legacy.clone()).unwrap();
+
+        assert_eq!(voxel.enable_packed_storage().unwrap(), 1);
+        assert!(voxel.is_packed_storage());
+        assert!(!legacy_path.exists());
+        assert!(voxel.find_template_qpx(&legacy.id).is_err());
+
+        // Escrituras nuevas van al pack
+        let packed = TemplateEntry::new("packed".to_string(), TemplateCategory::Creative, "b".to_string());
+        let pack_path = voxel
+            .write_template_qpx(&packed, sample_fbcu("packed"), vec![Pixel::new(4, 5, 6, 128); 8])
+            .unwrap();
+        voxel.insert_template(packed.clone()).unwrap();
+        assert!(pack_path.ends_with(QPX_PACK_FILE_NAME));
+        assert!(voxel.find_template_qpx(&packed.id).is_err());
+        drop(voxel);
+
+        // Reabrir detecta el pack automáticamente
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        voxel.load_all_from_disk().unwrap();
+        assert!(voxel.is_packed_storage());
+        assert_eq!(voxel.load_template_qpx(&legacy.id).unwrap().metadata.concept_name, "legacy");
+        assert_eq!(voxel.load_template_qpx(&packed.id).unwrap().metadata.concept_name, "packed");
+
+        voxel.rename_template(&packed.id, "packed_renamed").unwrap();
+        assert_eq!(
+            voxel.load_template_qpx(&packed.id).unwrap().metadata.concept_name,
+            "packed_renamed"
+        );
+
+        voxel.delete_template(&legacy.id).unwrap();
+        assert!(voxel.load_template_qpx(&legacy.id).is_err());
+        assert!(voxel.compact_qpx_pack().unwrap() > 0);
+        assert_eq!(voxel.load_template_qpx(&packed.id).unwrap().pixels.len(), 8);
+    }
+
+    #[test]
+    fn test_template_qpx_summary_and_pixels_in_both_layouts() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let per_file = TemplateEntry::new("per_file".to_string(), TemplateCategory::Technical, "a".to_string());
+        voxel
+            .write_template_qpx(&per_file, sample_fbcu("per_file"), vec![Pixel::new(1, 2, 3, 128); 4])
+            .unwrap();
+
+        let summary = voxel.load_template_qpx_summary(&per_file.id).unwrap();
+        assert_eq!(summary.metadata.concept_name, "per_file");
+        assert_eq!(summary.pixel_count, 4);
+        assert_eq!(voxel.load_template_qpx_pixels(&per_file.id).unwrap(), vec![Pixel::new(1, 2, 3, 128); 4]);
+
+        voxel.enable_packed_storage().unwrap();
+        let summary = voxel.load_template_qpx_summary(&per_file.id).unwrap();
+        assert_eq!(summary.metadata.concept_name, "per_file");
+        assert_eq!(voxel.load_template_qpx_pixels(&per_file.id).unwrap().len(), 4);
+        assert!(voxel.load_template_qpx_summary("missing").is_err());
+    }
+
+    #[test]
+    fn test_link_to_telescope_persists_entanglement_in_qpx() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut template = TemplateEntry::new("linked".to_string(), TemplateCategory::Technical, "a".to_string());
+        template.telescope_refs.push("telescope_a".to_string());
+        voxel
+            .write_template_qpx(&template, sample_fbcu("linked"), vec![Pixel::new(1, 2, 3, 128); 4])
+            .unwrap();
+        voxel.insert_template(template.clone()).unwrap();
+
+        voxel.link_to_telescope(&template.id, "telescope_b").unwrap();
+        voxel.link_to_telescope(&template.id, "telescope_b").unwrap();
+
+        let targets: Vec<String> = voxel
+            .load_template_entanglements(&template.id)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.kind == EntanglementKind::Telescope)
+            .map(|e| e.target_id)
+            .collect();
+        assert_eq!(targets, vec!["telescope_a".to_string(), "telescope_b".to_string()]);
+
+        // El vínculo nuevo queda en la timeline (el repetido no)
+        let kinds: Vec<TimelineEventKind> = voxel
+            .load_template_timeline(&template.id)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.kind)
+            .collect();
+        assert_eq!(kinds, vec![TimelineEventKind::Created, TimelineEventKind::Linked]);
+
+        voxel.rename_template(&template.id, "linked_renamed").unwrap();
+        let timeline = voxel.load_template_timeline(&template.id).unwrap();
+        assert_eq!(timeline.last().unwrap().kind, TimelineEventKind::Renamed);
+        assert_eq!(timeline.last().unwrap().version, 3);
+
+        // El resto del QPX queda intacto
+        let quantum_core = voxel.load_template_qpx(&template.id).unwrap();
+        assert_eq!(quantum_core.metadata.concept_name, "linked_renamed");
+        assert_eq!(quantum_core.pixels.len(), 4);
+    }
+
+    #[test]
+    fn test_rewriting_template_qpx_keeps_full_history() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let template = TemplateEntry::new("history".to_string(), TemplateCategory::Technical, "h".to_string());
+        voxel
+            .write_template_qpx(&template, sample_fbcu("history"), vec![Pixel::new(1, 2, 3, 128); 4])
+            .unwrap();
+        voxel.insert_template(template.clone()).unwrap();
+        voxel.link_to_telescope(&template.id, "telescope_a").unwrap();
+        voxel.rename_template(&template.id, "history_renamed").unwrap();
+        voxel.update_effectiveness(&template.id, 0.9, true, 1, 1).unwrap();
+        voxel.update_effectiveness(&template.id, 0.8, true, 2, 0).unwrap();
+
+        let expected = vec![
+            TimelineEventKind::Created,
+            TimelineEventKind::Linked,
+            TimelineEventKind::Renamed,
+            TimelineEventKind::Updated,
+            TimelineEventKind::Updated,
+        ];
+        let kinds = |voxel: &VoxelDB| -> Vec<TimelineEventKind> {
+            voxel.load_template_timeline(&template.id).unwrap().into_iter().map(|e| e.kind).collect()
+        };
+        assert_eq!(kinds(&voxel), expected);
+
+        // Volver a escribir el QPX conserva la historia (y no repite la última actualización)
+        let current = voxel.templates[&template.id].clone();
+        voxel
+            .write_template_qpx(&current, sample_fbcu("history"), vec![Pixel::new(1, 2, 3, 128); 4])
+            .unwrap();
+        assert_eq!(kinds(&voxel), expected);
+
+        let timeline = voxel.load_template_timeline(&template.id).unwrap();
+        assert_eq!(timeline.last().unwrap().timestamp, current.metadata.updated_at);
+        assert_eq!(timeline.last().unwrap().version, 5);
+    }
+
+    #[test]
+    fn test_write_template_qpx_replaces_corrupt_record() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let template = TemplateEntry::new("corrupt".to_string(), TemplateCategory::Technical, "c".to_string());
+        let qpx_path = voxel
+            .write_template_qpx(&template, sample_fbcu("corrupt"), vec![Pixel::new(1, 2, 3, 128); 4])
+            .unwrap();
+        voxel.insert_template(template.clone()).unwrap();
+
+        // Un `.qpxf` ilegible no impide volver a escribirlo: se reemplaza sin historia
+        let mut bytes = std::fs::read(&qpx_path).unwrap();
+        let len = bytes.len();
+        bytes[crate::qpx::QPXHeader::SIZE..len - 8].fill(0xAB);
+        std::fs::write(&qpx_path, &bytes).unwrap();
+        assert!(voxel.load_template_timeline(&template.id).is_err());
+
+        let rewritten = voxel
+            .write_template_qpx(&template, sample_fbcu("corrupt"), vec![Pixel::new(1, 2, 3, 128); 4])
+            .unwrap();
+        assert_eq!(rewritten, qpx_path);
+        let timeline = voxel.load_template_timeline(&template.id).unwrap();
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline[0].kind, TimelineEventKind::Created);
+
+        // Las reescrituras van por archivo temporal + rename, en la misma ruta
+        voxel.rename_template(&template.id, "corrupt_renamed").unwrap();
+        let files: Vec<_> = std::fs::read_dir(qpx_path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files, vec![qpx_path]);
+        assert_eq!(voxel.load_template_qpx(&template.id).unwrap().metadata.concept_name, "corrupt_renamed");
+    }
+
+    #[test]
+    fn test_gc_orphaned_qpx_in_pack() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        voxel.enable_packed_storage().unwrap();
+
+        let orphan = TemplateEntry::new("orphan".to_string(), TemplateCategory::Meta, "c".to_string());
+        voxel
+            .write_template_qpx(&orphan, sample_fbcu("orphan"), vec![Pixel::new(1, 2, 3, 128); 8])
+            .unwrap();
+        assert!(voxel.load_template_qpx(&orphan.id).is_ok());
+
+        voxel.gc_orphaned_qpx().unwrap();
+        assert!(voxel.load_template_qpx(&orphan.id).is_err());
+    }
+
+    #[test]
+    fn test_upgrade_qpx_storage() {
+        use tempfile::tempdir;
+
+        const V1_5_QUANTUM_CORE: &[u8] =
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/qpx/v1_5_quantum_core.qpx"));
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        // Un archivo v1.5 por template y otro registro v1.5 dentro del pack
+        let template = TemplateEntry::new("legacy".to_string(), TemplateCategory::Meta, "c".to_string());
+        let now = Utc::now();
+        let qpx_path = voxel.get_template_qpx_path(&template.id, now);
+        std::fs::create_dir_all(qpx_path.parent().unwrap()).unwrap();
+        std::fs::write(&qpx_path, V1_5_QUANTUM_CORE).unwrap();
+        voxel.enable_packed_storage().unwrap();
+        std::fs::create_dir_all(qpx_path.parent().unwrap()).unwrap();
+        std::fs::write(voxel.get_template_qpx_path("legacy_file", now), V1_5_QUANTUM_CORE)
+            .unwrap();
+
+        assert_eq!(voxel.upgrade_qpx_storage().unwrap(), 2);
+        assert_eq!(voxel.upgrade_qpx_storage().unwrap(), 0);
+
+        let summary = voxel.load_template_qpx_summary(&template.id).unwrap();
+        assert_eq!(summary.metadata.concept_name, "fixture_template");
+        let file_bytes = std::fs::read(voxel.get_template_qpx_path("legacy_file", now)).unwrap();
+        assert_eq!(crate::qpx::QPXHeader::from_bytes(&file_bytes).unwrap().version, crate::qpx::QPX_VERSION);
+    }
+
+    #[test]
+    fn test_query_nearest_and_box() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+
+        for (i, z) in [0.1, 0.3, 0.5, 0.7, 0.9].iter().enumerate() {
+            let mut template = TemplateEntry::new(
+                format!("t{}", i),
+                TemplateCategory::Technical,
+                format!("content {}", i),
+            );
+            template.coords.z = *z;
+            voxel.insert_template(template).unwrap();
+        }
+
+        let query = CubicCoords::new(0.0, 0.5, 0.62).unwrap();
+        let nearest = voxel.query_nearest(query, 2).unwrap();
+        assert_eq!(nearest.len(), 2);
+        assert_eq!(nearest[0].0.name, "t3");
+        assert_eq!(nearest[1].0.name, "t2");
+        assert!((nearest[0].1 - 0.08).abs() < 1e-9);
+
+        let in_box = voxel
+            .query_box(
+                CubicCoords::new(0.0, 0.4, 0.25).unwrap(),
+                CubicCoords::new(0.1, 0.6, 0.75).unwrap(),
+            )
+            .unwrap();
+        let names: Vec<_> = in_box.iter().map(|(t, _)| t.name.as_str()).collect();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[0], "t2");
+        assert!(names.contains(&"t1") && names.contains(&"t3"));
+    }
+
+    #[test]
+    fn test_index_snapshot_incremental_reload() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let mut ids = Vec::new();
+        {
+            let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+            for i in 0..4 {
+                ids.push(voxel.insert_template(TemplateEntry::new(
+                    format!("snap_{}", i),
+                    TemplateCategory::Analytical,
+                    format!("content {}", i),
+                )).unwrap());
+            }
+            voxel.save_index_snapshot().unwrap();
+        }
+        assert!(temp_dir.path().join(INDEX_SNAPSHOT_FILE_NAME).exists());
+
+        // Cambios fuera de VoxelDB: uno editado y uno borrado
+        let edited_path = temp_dir.path().join(format!("{}.json", ids[0]));
+        let mut edited: TemplateEntry =
+            serde_json::from_str(&std::fs::read_to_string(&edited_path).unwrap()).unwrap();
+        edited.name = "snap_edited".to_string();
+        std::fs::write(&edited_path, serde_json::to_string_pretty(&edited).unwrap()).unwrap();
+        std::fs::remove_file(temp_dir.path().join(format!("{}.json", ids[1]))).unwrap();
+
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(voxel.load_all_from_disk().unwrap(), 3);
+        assert_eq!(voxel.get_by_id(&ids[0]).unwrap().name, "snap_edited");
+        assert!(voxel.get_by_name("snap_0").is_err());
+        assert!(voxel.get_by_id(&ids[1]).is_err());
+        assert_eq!(voxel.query_by_category(TemplateCategory::Analytical).unwrap().len(), 3);
+        assert_eq!(voxel.query_nearest(edited.coords, 10).unwrap().len(), 3);
+
+        // Segunda carga: el snapshot reescrito ya refleja los cambios
+        let snapshot = IndexSnapshot::load(temp_dir.path()).unwrap();
+        assert_eq!(snapshot.templates.len(), 3);
+        assert_eq!(snapshot.manifest.len(), 3);
+        assert_eq!(snapshot.name_index.get("snap_edited"), Some(&ids[0]));
+
+        // Cargar dos veces no duplica entradas
+        assert_eq!(voxel.load_all_from_disk().unwrap(), 3);
+        assert_eq!(voxel.query_by_category(TemplateCategory::Analytical).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_index_snapshot_corrupted_falls_back_to_full_scan() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        {
+            let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+            voxel.insert_template(TemplateEntry::new(
+                "survivor".to_string(),
+                TemplateCategory::Emotional,
+                "content".to_string(),
+            )).unwrap();
+        }
+        std::fs::write(temp_dir.path().join(INDEX_SNAPSHOT_FILE_NAME), b"not cbor").unwrap();
+
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(voxel.load_all_from_disk().unwrap(), 1);
+        assert!(voxel.get_by_name("survivor").is_ok());
+        assert!(IndexSnapshot::load(temp_dir.path()).is_some());
+    }
+
+    #[test]
+    fn test_query_semantic_by_text_and_embedding() {
+        use crate::qpx::embedding_to_pixels;
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let docs = [
+            ("borrow_checker", "rust ownership borrow checker lifetimes"),
+            ("sourdough", "bread flour water starter fermentation"),
+            ("async_runtime", "rust async tokio runtime futures"),
+        ];
+        let mut ids = Vec::new();
+        {
+            let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+            for (name, content) in docs {
+                let template = TemplateEntry::new(
+                    name.to_string(),
+                    TemplateCategory::Technical,
+                    content.to_string(),
+                );
+                let pixels = embedding_to_pixels(&embed_text(content), 128);
+                voxel.write_template_qpx(&template, sample_fbcu(name), pixels).unwrap();
+                ids.push(voxel.insert_template(template).unwrap());
+            }
+
+            let results = voxel.query_semantic("lifetimes and the borrow checker", 2).unwrap();
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].0.name, "borrow_checker");
+            assert!(results[0].1 > results[1].1);
+        }
+
+        // El índice vectorial se reconstruye al cargar desde disco
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        voxel.load_all_from_disk().unwrap();
+        let results = voxel.query_semantic(embed_text("tokio futures runtime"), 1).unwrap();
+        assert_eq!(results[0].0.name, "async_runtime");
+
+        voxel.delete_template(&ids[2]).unwrap();
+        let results = voxel.query_semantic("tokio futures runtime", 3).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(t, _)| t.name != "async_runtime"));
+
+        assert!(matches!(
+            voxel.query_semantic("...", 3),
+            Err(VoxelDBError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            voxel.query_semantic(vec![1.0f32; 3], 3),
+            Err(VoxelDBError::ValidationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_semantic_index_is_restored_from_snapshot() {
+        use crate::qpx::embedding_to_pixels;
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let write = |voxel: &mut VoxelDB, name: &str, content: &str| {
+            let template = TemplateEntry::new(name.to_string(), TemplateCategory::Technical, content.to_string());
+            let pixels = embedding_to_pixels(&embed_text(content), 128);
+            voxel.write_template_qpx(&template, sample_fbcu(name), pixels).unwrap();
+            voxel.insert_template(template).unwrap()
+        };
+        {
+            let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+            write(&mut voxel, "borrow_checker", "rust ownership borrow checker lifetimes");
+            voxel.load_all_from_disk().unwrap();
+        }
+        assert_eq!(IndexSnapshot::load(temp_dir.path()).unwrap().semantic_index.len(), 1);
+
+        // Un QPX ilegible no importa: su embedding viene del snapshot
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        for (_, path) in voxel.list_template_qpx_files().unwrap() {
+            std::fs::write(path, b"not a qpx").unwrap();
+        }
+        voxel.load_all_from_disk().unwrap();
+        let results = voxel.query_semantic("borrow checker lifetimes", 1).unwrap();
+        assert_eq!(results[0].0.name, "borrow_checker");
+
+        // Templates nuevos sí se leen de su QPX
+        write(&mut voxel, "sourdough", "bread flour water starter fermentation");
+        let mut voxel = VoxelDB::new(temp_dir.path().to_path_buf()).unwrap();
+        voxel.load_all_from_disk().unwrap();
+        let results = voxel.query_semantic("flour and starter", 1).unwrap();
+        assert_eq!(results[0].0.name, "sourdough");
+        assert_eq!(IndexSnapshot::load(temp_dir.path()).unwrap().semantic_index.len(), 2);
+    }
 }
@@ -5,17 +5,20 @@
 //! ## Performance
 //!
 //! - Insert: O(log n)
-//! - Query esférica: O(log n + k) donde k = resultados
+//! - Query esférica / por caja: O(log n + k) donde k = resultados
+//! - kNN best-first: O(log n + k) nodos visitados en distribuciones típicas
 //! - Mejora: ~18-22x más rápido que búsqueda lineal
 
 use crate::voxeldb::CubicCoords;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
 
 /// Nodo del Octree
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OctreeNode<T> {
-    /// Templates almacenados en este nodo (solo en hojas)
-    pub items: Vec<T>,
+    /// Templates almacenados en este nodo con sus coordenadas (solo en hojas)
+    pub items: Vec<(CubicCoords, T)>,
     
     /// Hijos del nodo (8 octantes si está subdividido)
     pub children: Option<Box<[OctreeNode<T>; 8]>>,
@@ -28,7 +31,7 @@ pub struct OctreeNode<T> {
 }
 
 /// Bounding box (límites del espacio 3D)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub min_x: f64,
     pub min_y: f64,
@@ -123,6 +126,42 @@ impl BoundingBox {
         coords.y >= self.min_y && coords.y <= self.max_y &&
         coords.z >= self.min_z && coords.z <= self.max_z
     }
+    
+    /// Crear box a partir de dos esquinas opuestas (en cualquier orden)
+    pub fn from_corners(a: CubicCoords, b: CubicCoords) -> Self {
+        Self {
+            min_x: a.x.min(b.x),
+            min_y: a.y.min(b.y),
+            min_z: a.z.min(b.z),
+            max_x: a.x.max(b.x),
+            max_y: a.y.max(b.y),
+            max_z: a.z.max(b.z),
+        }
+    }
+    
+    /// Centro geométrico del box
+    pub fn center(&self) -> CubicCoords {
+        CubicCoords {
+            x: (self.min_x + self.max_x) / 2.0,
+            y: (self.min_y + self.max_y) / 2.0,
+            z: (self.min_z + self.max_z) / 2.0,
+        }
+    }
+    
+    /// Distancia mínima de un punto al box (0 si está dentro)
+    pub fn min_distance(&self, point: CubicCoords) -> f64 {
+        let dx = (self.min_x - point.x).max(0.0).max(point.x - self.max_x);
+        let dy = (self.min_y - point.y).max(0.0).max(point.y - self.max_y);
+        let dz = (self.min_z - point.z).max(0.0).max(point.z - self.max_z);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+    
+    /// Verificar si este box intersecta con otro
+    pub fn intersects_box(&self, other: &BoundingBox) -> bool {
+        self.min_x <= other.max_x && self.max_x >= other.min_x &&
+        self.min_y <= other.max_y && self.max_y >= other.min_y &&
+        self.min_z <= other.max_z && self.max_z >= other.min_z
+    }
 }
 
 impl<T: Clone> OctreeNode<T> {
@@ -148,7 +187,7 @@ impl<T: Clone> OctreeNode<T> {
         }
         
         let octants = self.bounds.subdivide();
-        let children = Box::new([
+        let mut children = Box::new([
             OctreeNode::new_leaf(octants[0], self.level + 1),
             OctreeNode::new_leaf(octants[1], self.level + 1),
             OctreeNode::new_leaf(octants[2], self.level + 1),
@@ -159,13 +198,13 @@ impl<T: Clone> OctreeNode<T> {
             OctreeNode::new_leaf(octants[7], self.level + 1),
         ]);
         
-        // Mover items existentes a hijos apropiados
+        // Mover items existentes a los hijos correctos: solo las hojas guardan items
         let items = std::mem::take(&mut self.items);
+        for (coords, item) in items {
+            let octant = self.get_octant(coords);
+            children[octant].items.push((coords, item));
+        }
         self.children = Some(children);
-        
-        // Re-insertar items en los hijos correctos
-        // (En esta implementación simplificada, los dejamos en el padre)
-        self.items = items;
     }
     
     /// Determinar en qué octante cae una posición
@@ -178,12 +217,22 @@ impl<T: Clone> OctreeNode<T> {
         let y_bit = if coords.y >= mid_y { 1 } else { 0 };
         let z_bit = if coords.z >= mid_z { 1 } else { 0 };
         
-        // Combinar bits: octant = x + 2*z + 4*y
-        x_bit | (z_bit << 1) | (y_bit << 2)
+        // Índice según el orden de `BoundingBox::subdivide`
+        match (x_bit, y_bit, z_bit) {
+            (0, 0, 0) => 0,
+            (1, 0, 0) => 1,
+            (1, 0, 1) => 2,
+            (0, 0, 1) => 3,
+            (0, 1, 0) => 4,
+            (0, 1, 1) => 5,
+            (1, 1, 0) => 6,
+            _ => 7,
+        }
     }
 }
 
 /// Octree principal para indexación espacial
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Octree<T> {
     /// Nodo raíz
     root: OctreeNode<T>,
@@ -225,7 +274,7 @@ impl<T: Clone> Octree<T> {
     fn insert_recursive(node: &mut OctreeNode<T>, coords: CubicCoords, item: T, node_capacity: usize, max_depth: usize) {
         // Si el nodo es hoja
         if node.is_leaf() {
-            node.items.push(item);
+            node.items.push((coords, item));
             
             // Subdividir si excede capacidad y no estamos en max_depth
             if node.items.len() > node_capacity && (node.level as usize) < max_depth {
@@ -265,10 +314,10 @@ impl<T: Clone> Octree<T> {
         
         // Si es hoja, añadir items que estén dentro del radio
         if node.is_leaf() {
-            for item in &node.items {
-                // Nota: En implementación real, necesitaríamos coordenadas del item
-                // Por simplicidad, añadimos todos los items del nodo que intersecta
-                results.push(item.clone());
+            for (coords, item) in &node.items {
+                if center.distance(coords) <= radius {
+                    results.push(item.clone());
+                }
             }
             return;
         }
@@ -281,6 +330,87 @@ impl<T: Clone> Octree<T> {
         }
     }
     
+    /// k vecinos más cercanos (best-first), ordenados por distancia ascendente
+    ///
+    /// Explora nodos en orden de distancia mínima a su bounding box, así que
+    /// se detiene en cuanto los `k` mejores están garantizados.
+    pub fn query_nearest(&self, center: CubicCoords, k: usize) -> Vec<(T, f64)> {
+        let mut results = Vec::with_capacity(k);
+        if k == 0 {
+            return results;
+        }
+        
+        let mut heap = BinaryHeap::new();
+        heap.push(Candidate {
+            distance: self.root.bounds.min_distance(center),
+            entry: CandidateEntry::Node(&self.root),
+        });
+        
+        while let Some(Candidate { distance, entry }) = heap.pop() {
+            match entry {
+                CandidateEntry::Item(item) => {
+                    results.push((item.clone(), distance));
+                    if results.len() == k {
+                        break;
+                    }
+                }
+                CandidateEntry::Node(node) => {
+                    for (coords, item) in &node.items {
+                        heap.push(Candidate {
+                            distance: center.distance(coords),
+                            entry: CandidateEntry::Item(item),
+                        });
+                    }
+                    if let Some(ref children) = node.children {
+                        for child in children.iter() {
+                            heap.push(Candidate {
+                                distance: child.bounds.min_distance(center),
+                                entry: CandidateEntry::Node(child),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        
+        results
+    }
+    
+    /// Items dentro de un box alineado a los ejes, con su distancia al centro del box
+    ///
+    /// Resultados ordenados por distancia ascendente.
+    pub fn query_box(&self, bounds: BoundingBox) -> Vec<(T, f64)> {
+        let center = bounds.center();
+        let mut results = Vec::new();
+        Self::box_recursive(&self.root, &bounds, center, &mut results);
+        results.sort_by(|a, b| a.1.total_cmp(&b.1));
+        results
+    }
+    
+    fn box_recursive(
+        node: &OctreeNode<T>,
+        bounds: &BoundingBox,
+        center: CubicCoords,
+        results: &mut Vec<(T, f64)>,
+    ) {
+        // PODA: nodo fuera del box
+        if !node.bounds.intersects_box(bounds) {
+            return;
+        }
+        
+        for (coords, item) in &node.items {
+            if bounds.contains(*coords) {
+                results.push((item.clone(), center.distance(coords)));
+            }
+        }
+        
+        if let Some(ref children) = node.children {
+            for child in children.iter() {
+                Self::box_recursive(child, bounds, center, results);
+            }
+        }
+    }
+    
     /// Remover item en coordenadas específicas
     pub fn remove(&mut self, coords: CubicCoords, item_to_remove: &T) 
     where
@@ -304,7 +434,7 @@ impl<T: Clone> Octree<T> {
         T: PartialEq,
     {
         if node.is_leaf() {
-            node.items.retain(|i| i != item);
+            node.items.retain(|(_, i)| i != item);
             return;
         }
         
@@ -349,6 +479,52 @@ impl<T: Clone> Octree<T> {
     }
 }
 
+/// Entrada de la cola de prioridad de `query_nearest`
+struct Candidate<'a, T> {
+    /// Distancia exacta (items) o cota inferior (nodos)
+    distance: f64,
+    entry: CandidateEntry<'a, T>,
+}
+
+enum CandidateEntry<'a, T> {
+    Node(&'a OctreeNode<T>),
+    Item(&'a T),
+}
+
+impl<T> CandidateEntry<'_, T> {
+    /// A igual distancia, los items salen antes que los nodos
+    fn rank(&self) -> u8 {
+        match self {
+            CandidateEntry::Item(_) => 0,
+            CandidateEntry::Node(_) => 1,
+        }
+    }
+}
+
+impl<T> PartialEq for Candidate<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Candidate<'_, T> {}
+
+impl<T> PartialOrd for Candidate<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<'_, T> {
+    // Invertido: BinaryHeap es max-heap y queremos la menor distancia primero
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.entry.rank().cmp(&self.entry.rank()))
+    }
+}
+
 /// Estadísticas del Octree
 #[derive(Debug, Default, Clone)]
 pub struct OctreeStats {
@@ -436,4 +612,73 @@ mod tests {
         );
         assert!(stats.total_nodes > 0);
     }
+
+    fn grid_octree() -> (Octree<usize>, Vec<CubicCoords>) {
+        let mut octree = Octree::new(100);
+        let mut points = Vec::new();
+        for i in 0..200 {
+            let coords = CubicCoords {
+                x: ((i * 37) % 200) as f64 / 200.0,
+                y: ((i * 73) % 200) as f64 / 200.0,
+                z: ((i * 101) % 200) as f64 / 200.0,
+            };
+            octree.insert(coords, i);
+            points.push(coords);
+        }
+        (octree, points)
+    }
+    
+    #[test]
+    fn test_octree_query_nearest_matches_linear_scan() {
+        let (octree, points) = grid_octree();
+        let center = CubicCoords { x: 0.3, y: 0.6, z: 0.45 };
+        
+        let results = octree.query_nearest(center, 5);
+        
+        let mut expected: Vec<(usize, f64)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, center.distance(p)))
+            .collect();
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+        
+        assert_eq!(results.len(), 5);
+        for (got, want) in results.iter().zip(expected.iter()) {
+            assert!((got.1 - want.1).abs() < 1e-12);
+        }
+        assert!(results.windows(2).all(|w| w[0].1 <= w[1].1));
+        
+        assert_eq!(octree.query_nearest(center, 500).len(), 200);
+        assert!(octree.query_nearest(center, 0).is_empty());
+    }
+    
+    #[test]
+    fn test_octree_query_box() {
+        let (octree, points) = grid_octree();
+        let bounds = BoundingBox::from_corners(
+            CubicCoords { x: 0.6, y: 0.6, z: 0.6 },
+            CubicCoords { x: 0.2, y: 0.2, z: 0.2 },
+        );
+        
+        let results = octree.query_box(bounds);
+        let expected = points.iter().filter(|p| bounds.contains(**p)).count();
+        
+        assert_eq!(results.len(), expected);
+        assert!(expected > 0);
+        for (i, distance) in &results {
+            assert!(bounds.contains(points[*i]));
+            assert!((bounds.center().distance(&points[*i]) - distance).abs() < 1e-12);
+        }
+        assert!(results.windows(2).all(|w| w[0].1 <= w[1].1));
+    }
+    
+    #[test]
+    fn test_octree_subdivide_keeps_items_queryable() {
+        let (octree, points) = grid_octree();
+        
+        // Todos los items siguen siendo alcanzables tras subdividir
+        assert_eq!(octree.stats().total_items, points.len());
+        let found = octree.query_sphere(points[0], 0.0);
+        assert!(found.contains(&0));
+    }
 }
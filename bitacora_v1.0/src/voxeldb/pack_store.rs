@@ -0,0 +1,635 @@
+//! # Pack Store - Almacenamiento QPX Empaquetado para VoxelDB
+//!
+//! Alternativa al layout de un `.qpxf` por template (`templates/YYYY/MM/`):
+//! todos los templates viven en un único archivo append-only de registros QPX
+//! más un archivo de índice ID → offset.
+//!
+//! ## Layout
+//!
+//! ```text
+//! templates.qpxpack   [registro][registro]...   (append-only)
+//! templates.qpxidx    snapshot del índice + longitud del pack que cubre
+//! ```
+//!
+//! Cada registro: `"QPKR"` + flags (u8) + id_len (u16) + payload_len (u32) +
+//! header_crc (u32) + id + QPX. El CRC cubre los campos anteriores de la
+//! cabecera: un registro corto al final con cabecera válida es una escritura
+//! interrumpida y se trunca; una cabecera que no cuadra es corrupción.
+//! Los borrados se escriben como tombstones; `compact` reescribe solo los
+//! registros vivos. Al abrir, el índice persistido se carga y solo se escanea
+//! la cola del pack escrita después de él (o todo el pack si falta, es inválido
+//! o alguna entrada no apunta a su registro, p. ej. un índice viejo que quedó
+//! tras un crash entre el rename de `compact` y `flush_index`).
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+
+use crate::voxeldb::{Result, VoxelDBError};
+
+/// Nombre del archivo de registros dentro del directorio de VoxelDB
+pub const QPX_PACK_FILE_NAME: &str = "templates.qpxpack";
+
+/// Nombre del archivo de índice ID → offset
+pub const QPX_PACK_INDEX_FILE_NAME: &str = "templates.qpxidx";
+
+const RECORD_MAGIC: &[u8; 4] = b"QPKR";
+const RECORD_HEADER_SIZE: u64 = 4 + 1 + 2 + 4 + 4;
+const RECORD_FLAG_TOMBSTONE: u8 = 0b1;
+
+const INDEX_MAGIC: &[u8; 4] = b"QPKI";
+const INDEX_VERSION: u32 = 1;
+
+/// Ubicación de un payload QPX dentro del pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackEntry {
+    /// Offset del registro (inicio de la cabecera)
+    pub offset: u64,
+
+    /// Longitud total del registro (cabecera + id + payload)
+    pub record_len: u64,
+
+    /// Longitud del payload QPX
+    pub payload_len: u32,
+}
+
+impl PackEntry {
+    /// Offset del payload QPX dentro del pack
+    fn payload_offset(&self) -> u64 {
+        self.offset + self.record_len - self.payload_len as u64
+    }
+}
+
+/// Store append-only de registros QPX con índice ID → offset
+pub struct QPXPackStore {
+    /// Ruta de `templates.qpxpack`
+    path: PathBuf,
+
+    /// Ruta de `templates.qpxidx`
+    index_path: PathBuf,
+
+    /// Handle en modo append (lecturas posicionales con `read_exact_at`)
+    file: File,
+
+    /// Registros vivos por ID
+    index: HashMap<String, PackEntry>,
+
+    /// Bytes ocupados por registros sobrescritos o borrados
+    dead_bytes: u64,
+
+    /// Fsync después de cada escritura
+    sync_on_write: bool,
+}
+
+impl QPXPackStore {
+    /// Abre (o crea) el pack en `dir/templates.qpxpack`
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(QPX_PACK_FILE_NAME);
+        let index_path = dir.join(QPX_PACK_INDEX_FILE_NAME);
+
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        let mut store = Self {
+            path,
+            index_path,
+            file,
+            index: HashMap::new(),
+            dead_bytes: 0,
+            sync_on_write: true,
+        };
+        store.load_index()?;
+
+        Ok(store)
+    }
+
+    /// Verifica si existe un pack en `dir`
+    pub fn exists_in(dir: &Path) -> bool {
+        dir.join(QPX_PACK_FILE_NAME).exists()
+    }
+
+    /// Desactiva/activa el fsync por escritura (cargas masivas, migración)
+    pub fn set_sync_on_write(&mut self, sync_on_write: bool) {
+        self.sync_on_write = sync_on_write;
+    }
+
+    /// Agrega (o reemplaza) el payload QPX de `id`
+    pub fn append(&mut self, id: &str, qpx_bytes: &[u8]) -> Result<()> {
+        let entry = self.write_record(id, 0, qpx_bytes)?;
+        if let Some(old) = self.index.insert(id.to_string(), entry) {
+            self.dead_bytes += old.record_len;
+        }
+        Ok(())
+    }
+
+    /// Lee el payload QPX de `id`
+    pub fn read(&self, id: &str) -> Result<Option<Vec<u8>>> {
+        match self.index.get(id) {
+            Some(entry) => self.read_payload(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Borra `id` escribiendo un tombstone; retorna `false` si no existía
+    pub fn remove(&mut self, id: &str) -> Result<bool> {
+        if !self.index.contains_key(id) {
+            return Ok(false);
+        }
+
+        let tombstone = self.write_record(id, RECORD_FLAG_TOMBSTONE, &[])?;
+        if let Some(old) = self.index.remove(id) {
+            self.dead_bytes += old.record_len + tombstone.record_len;
+        }
+        Ok(true)
+    }
+
+    /// Verifica si `id` tiene un registro vivo
+    pub fn contains(&self, id: &str) -> bool {
+        self.index.contains_key(id)
+    }
+
+    /// IDs con registro vivo
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    /// Número de registros vivos
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Verifica si el pack no tiene registros vivos
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Bytes recuperables con `compact`
+    pub fn dead_bytes(&self) -> u64 {
+        self.dead_bytes
+    }
+
+    /// Reescribe el pack con solo los registros vivos
+    ///
+    /// El nuevo pack se escribe en un archivo temporal y se renombra
+    /// atómicamente. Retorna los bytes recuperados.
+    pub fn compact(&mut self) -> Result<u64> {
+        let before = self.file.metadata()?.len();
+        let tmp_path = self.path.with_extension("qpxpack.tmp");
+
+        // Orden por offset: preserva la localidad del pack original
+        let mut live: Vec<(String, PackEntry)> = self
+            .index
+            .iter()
+            .map(|(id, entry)| (id.clone(), *entry))
+            .collect();
+        live.sort_by_key(|(_, entry)| entry.offset);
+
+        let mut new_index = HashMap::with_capacity(live.len());
+        {
+            let mut writer = std::io::BufWriter::new(File::create(&tmp_path)?);
+            let mut offset = 0u64;
+            for (id, entry) in live {
+                let payload = self.read_payload(&entry)?;
+                let record = encode_record(&id, 0, &payload)?;
+                writer.write_all(&record)?;
+
+                new_index.insert(
+                    id,
+                    PackEntry {
+                        offset,
+                        record_len: record.len() as u64,
+                        payload_len: entry.payload_len,
+                    },
+                );
+                offset += record.len() as u64;
+            }
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+
+        std::fs::rename(&tmp_path, &self.path)?;
+        self.file = OpenOptions::new().read(true).append(true).open(&self.path)?;
+        self.index = new_index;
+        self.dead_bytes = 0;
+        self.flush_index()?;
+
+        Ok(before.saturating_sub(self.file.metadata()?.len()))
+    }
+
+    /// Persiste el índice ID → offset (cubre el pack hasta su longitud actual)
+    pub fn flush_index(&self) -> Result<()> {
+        let pack_len = self.file.metadata()?.len();
+
+        let mut buf = Vec::with_capacity(32 + self.index.len() * 48);
+        buf.extend_from_slice(INDEX_MAGIC);
+        buf.write_u32::<LittleEndian>(INDEX_VERSION)?;
+        buf.write_u64::<LittleEndian>(pack_len)?;
+        buf.write_u64::<LittleEndian>(self.dead_bytes)?;
+        buf.write_u32::<LittleEndian>(self.index.len() as u32)?;
+        for (id, entry) in &self.index {
+            buf.write_u16::<LittleEndian>(id.len() as u16)?;
+            buf.extend_from_slice(id.as_bytes());
+            buf.write_u64::<LittleEndian>(entry.offset)?;
+            buf.write_u64::<LittleEndian>(entry.record_len)?;
+            buf.write_u32::<LittleEndian>(entry.payload_len)?;
+        }
+
+        let tmp_path = self.index_path.with_extension("qpxidx.tmp");
+        std::fs::write(&tmp_path, &buf)?;
+        std::fs::rename(&tmp_path, &self.index_path)?;
+        Ok(())
+    }
+
+    /// Fuerza fsync del pack
+    pub fn sync(&self) -> Result<()> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Ruta del archivo de registros
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // === Métodos privados ===
+
+    fn write_record(&mut self, id: &str, flags: u8, payload: &[u8]) -> Result<PackEntry> {
+        let record = encode_record(id, flags, payload)?;
+        let offset = self.file.metadata()?.len();
+
+        self.file.write_all(&record)?;
+        if self.sync_on_write {
+            self.file.sync_data()?;
+        }
+
+        Ok(PackEntry {
+            offset,
+            record_len: record.len() as u64,
+            payload_len: payload.len() as u32,
+        })
+    }
+
+    fn read_payload(&self, entry: &PackEntry) -> Result<Vec<u8>> {
+        let mut payload = vec![0u8; entry.payload_len as usize];
+        self.file.read_exact_at(&mut payload, entry.payload_offset())?;
+        Ok(payload)
+    }
+
+    /// Comprueba que en `entry.offset` haya un registro vivo de `id` con las
+    /// longitudes del índice
+    fn record_matches(&self, id: &str, entry: &PackEntry) -> bool {
+        let mut header = vec![0u8; RECORD_HEADER_SIZE as usize + id.len()];
+        if self.file.read_exact_at(&mut header, entry.offset).is_err() {
+            return false;
+        }
+        let mut cursor = Cursor::new(&header[4..RECORD_HEADER_SIZE as usize]);
+        let flags = cursor.read_u8().unwrap_or(RECORD_FLAG_TOMBSTONE);
+        let id_len = cursor.read_u16::<LittleEndian>().unwrap_or(0);
+        let payload_len = cursor.read_u32::<LittleEndian>().unwrap_or(0);
+        let header_crc = cursor.read_u32::<LittleEndian>().unwrap_or(0);
+
+        &header[..4] == RECORD_MAGIC
+            && header_crc == record_header_crc(flags, id_len, payload_len)
+            && flags & RECORD_FLAG_TOMBSTONE == 0
+            && id_len as usize == id.len()
+            && payload_len == entry.payload_len
+            && RECORD_HEADER_SIZE + id.len() as u64 + payload_len as u64 == entry.record_len
+            && &header[RECORD_HEADER_SIZE as usize..] == id.as_bytes()
+    }
+
+    /// Carga el índice persistido y escanea la cola del pack no cubierta
+    fn load_index(&mut self) -> Result<()> {
+        let file_len = self.file.metadata()?.len();
+
+        let covered = match self.read_index_file(file_len) {
+            Some((index, dead_bytes, covered)) => {
+                self.index = index;
+                self.dead_bytes = dead_bytes;
+                covered
+            }
+            None => {
+                self.index.clear();
+                self.dead_bytes = 0;
+                0
+            }
+        };
+
+        if covered < file_len {
+            self.scan_from(covered, file_len)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lee `templates.qpxidx`; `None` si falta, es inválido o no encaja con el pack
+    fn read_index_file(&self, file_len: u64) -> Option<(HashMap<String, PackEntry>, u64, u64)> {
+        let bytes = std::fs::read(&self.index_path).ok()?;
+        let mut cursor = Cursor::new(bytes.as_slice());
+
+        let mut magic = [0u8; 4];
+        cursor.read_exact(&mut magic).ok()?;
+        if &magic != INDEX_MAGIC || cursor.read_u32::<LittleEndian>().ok()? != INDEX_VERSION {
+            return None;
+        }
+
+        let covered = cursor.read_u64::<LittleEndian>().ok()?;
+        let dead_bytes = cursor.read_u64::<LittleEndian>().ok()?;
+        let count = cursor.read_u32::<LittleEndian>().ok()?;
+        if covered > file_len {
+            // Pack truncado o reemplazado después de escribir el índice
+            return None;
+        }
+
+        let mut index = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let id_len = cursor.read_u16::<LittleEndian>().ok()?;
+            let mut id = vec![0u8; id_len as usize];
+            cursor.read_exact(&mut id).ok()?;
+            let entry = PackEntry {
+                offset: cursor.read_u64::<LittleEndian>().ok()?,
+                record_len: cursor.read_u64::<LittleEndian>().ok()?,
+                payload_len: cursor.read_u32::<LittleEndian>().ok()?,
+            };
+            if entry.offset + entry.record_len > covered {
+                return None;
+            }
+            let id = String::from_utf8(id).ok()?;
+            if !self.record_matches(&id, &entry) {
+                // Índice de otro pack (p. ej. anterior a un `compact`)
+                return None;
+            }
+            index.insert(id, entry);
+        }
+
+        Some((index, dead_bytes, covered))
+    }
+
+    /// Recorre registros en `[start, file_len)` aplicándolos al índice
+    fn scan_from(&mut self, start: u64, file_len: u64) -> Result<()> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(start))?;
+
+        let mut offset = start;
+        while offset < file_len {
+            let remaining = file_len - offset;
+            if remaining < RECORD_HEADER_SIZE {
+                break;
+            }
+
+            let mut magic = [0u8; 4];
+            reader.read_exact(&mut magic)?;
+            let flags = reader.read_u8()?;
+            let id_len = reader.read_u16::<LittleEndian>()?;
+            let payload_len = reader.read_u32::<LittleEndian>()?;
+            let header_crc = reader.read_u32::<LittleEndian>()?;
+            let record_len = RECORD_HEADER_SIZE + id_len as u64 + payload_len as u64;
+
+            let header_ok = header_crc == record_header_crc(flags, id_len, payload_len);
+            if &magic != RECORD_MAGIC || !header_ok {
+                // Con una cabecera completa delante, no es una escritura
+                // interrumpida: no se trunca
+                return Err(VoxelDBError::SerializationError(format!(
+                    "corrupted pack record at byte {} in {:?}",
+                    offset, self.path
+                )));
+            }
+            if record_len > remaining {
+                // Cabecera íntegra pero registro incompleto al final:
+                // escritura interrumpida
+                break;
+            }
+
+            let mut id = vec![0u8; id_len as usize];
+            reader.read_exact(&mut id)?;
+            let id = String::from_utf8(id)
+                .map_err(|e| VoxelDBError::SerializationError(e.to_string()))?;
+            reader.seek_relative(payload_len as i64)?;
+
+            if flags & RECORD_FLAG_TOMBSTONE != 0 {
+                if let Some(old) = self.index.remove(&id) {
+                    self.dead_bytes += old.record_len;
+                }
+                self.dead_bytes += record_len;
+            } else {
+                let entry = PackEntry { offset, record_len, payload_len };
+                if let Some(old) = self.index.insert(id, entry) {
+                    self.dead_bytes += old.record_len;
+                }
+            }
+
+            offset += record_len;
+        }
+
+        if offset < file_len {
+            tracing::warn!(
+                "QPX pack {:?} has a torn tail, truncating to {} bytes",
+                self.path,
+                offset
+            );
+            self.file.set_len(offset)?;
+            self.file.sync_all()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for QPXPackStore {
+    fn drop(&mut self) {
+        // Best-effort: un índice desactualizado solo implica escanear la cola
+        if let Err(e) = self.flush_index() {
+            tracing::warn!("Failed to flush QPX pack index {:?}: {}", self.index_path, e);
+        }
+    }
+}
+
+fn encode_record(id: &str, flags: u8, payload: &[u8]) -> Result<Vec<u8>> {
+    if id.len() > u16::MAX as usize {
+        return Err(VoxelDBError::ValidationFailed(format!("template id too long: {} bytes", id.len())));
+    }
+    if payload.len() > u32::MAX as usize {
+        return Err(VoxelDBError::ValidationFailed(format!("QPX payload too large: {} bytes", payload.len())));
+    }
+
+    let mut record = Vec::with_capacity(RECORD_HEADER_SIZE as usize + id.len() + payload.len());
+    record.extend_from_slice(RECORD_MAGIC);
+    record.write_u8(flags)?;
+    record.write_u16::<LittleEndian>(id.len() as u16)?;
+    record.write_u32::<LittleEndian>(payload.len() as u32)?;
+    let header_crc = record_header_crc(flags, id.len() as u16, payload.len() as u32);
+    record.write_u32::<LittleEndian>(header_crc)?;
+    record.extend_from_slice(id.as_bytes());
+    record.extend_from_slice(payload);
+    Ok(record)
+}
+
+/// CRC32 de los campos de la cabecera de un registro
+fn record_header_crc(flags: u8, id_len: u16, payload_len: u32) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(RECORD_MAGIC);
+    hasher.update(&[flags]);
+    hasher.update(&id_len.to_le_bytes());
+    hasher.update(&payload_len.to_le_bytes());
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pack_append_read_reopen() {
+        let temp_dir = tempdir().unwrap();
+
+        {
+            let mut store = QPXPackStore::open(temp_dir.path()).unwrap();
+            store.append("a", b"alpha").unwrap();
+            store.append("b", b"beta").unwrap();
+            store.append("a", b"alpha-v2").unwrap();
+            assert!(store.remove("b").unwrap());
+            assert!(!store.remove("b").unwrap());
+        }
+
+        let store = QPXPackStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.read("a").unwrap().unwrap(), b"alpha-v2");
+        assert!(store.read("b").unwrap().is_none());
+        assert!(store.dead_bytes() > 0);
+    }
+
+    #[test]
+    fn test_pack_scans_tail_beyond_index() {
+        let temp_dir = tempdir().unwrap();
+
+        {
+            let mut store = QPXPackStore::open(temp_dir.path()).unwrap();
+            store.append("a", b"alpha").unwrap();
+            store.flush_index().unwrap();
+        }
+
+        // Registro escrito después del índice (simula crash antes de flush)
+        let record = encode_record("b", 0, b"beta").unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(temp_dir.path().join(QPX_PACK_FILE_NAME))
+            .unwrap();
+        file.write_all(&record).unwrap();
+        // Cola incompleta
+        file.write_all(&record[..6]).unwrap();
+        drop(file);
+
+        let store = QPXPackStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.read("b").unwrap().unwrap(), b"beta");
+    }
+
+    #[test]
+    fn test_pack_compaction() {
+        let temp_dir = tempdir().unwrap();
+        let mut store = QPXPackStore::open(temp_dir.path()).unwrap();
+
+        for i in 0..10 {
+            store.append(&format!("id{}", i), &[i as u8; 64]).unwrap();
+        }
+        for i in 0..5 {
+            store.remove(&format!("id{}", i)).unwrap();
+        }
+
+        let reclaimed = store.compact().unwrap();
+        assert!(reclaimed > 0);
+        assert_eq!(store.dead_bytes(), 0);
+        assert_eq!(store.len(), 5);
+        assert_eq!(store.read("id7").unwrap().unwrap(), vec![7u8; 64]);
+        drop(store);
+
+        let store = QPXPackStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.len(), 5);
+        assert!(store.read("id2").unwrap().is_none());
+        assert_eq!(store.read("id9").unwrap().unwrap(), vec![9u8; 64]);
+    }
+
+    #[test]
+    fn test_pack_corrupted_record_is_not_truncated() {
+        let temp_dir = tempdir().unwrap();
+        let pack_path = temp_dir.path().join(QPX_PACK_FILE_NAME);
+
+        {
+            let mut store = QPXPackStore::open(temp_dir.path()).unwrap();
+            store.append("a", b"alpha").unwrap();
+            store.append("b", b"beta").unwrap();
+        }
+        std::fs::remove_file(temp_dir.path().join(QPX_PACK_INDEX_FILE_NAME)).unwrap();
+
+        // Magic dañado en el primer registro, con más registros detrás
+        let mut bytes = std::fs::read(&pack_path).unwrap();
+        let len = bytes.len();
+        bytes[0] = b'X';
+        std::fs::write(&pack_path, &bytes).unwrap();
+
+        assert!(QPXPackStore::open(temp_dir.path()).is_err());
+        assert_eq!(std::fs::metadata(&pack_path).unwrap().len(), len as u64);
+    }
+
+    #[test]
+    fn test_pack_corrupted_length_is_not_truncated() {
+        let temp_dir = tempdir().unwrap();
+        let pack_path = temp_dir.path().join(QPX_PACK_FILE_NAME);
+
+        {
+            let mut store = QPXPackStore::open(temp_dir.path()).unwrap();
+            store.append("a", &[1u8; 64]).unwrap();
+            store.append("b", &[2u8; 64]).unwrap();
+            store.append("c", &[3u8; 64]).unwrap();
+        }
+        std::fs::remove_file(temp_dir.path().join(QPX_PACK_INDEX_FILE_NAME)).unwrap();
+
+        // payload_len del primer registro apuntando más allá del pack
+        let mut bytes = std::fs::read(&pack_path).unwrap();
+        let len = bytes.len();
+        bytes[10] ^= 0x80;
+        std::fs::write(&pack_path, &bytes).unwrap();
+
+        assert!(matches!(
+            QPXPackStore::open(temp_dir.path()),
+            Err(VoxelDBError::SerializationError(_))
+        ));
+        assert_eq!(std::fs::metadata(&pack_path).unwrap().len(), len as u64);
+    }
+
+    #[test]
+    fn test_pack_ignores_index_older_than_compaction() {
+        let temp_dir = tempdir().unwrap();
+        let index_path = temp_dir.path().join(QPX_PACK_INDEX_FILE_NAME);
+
+        let stale_index = {
+            let mut store = QPXPackStore::open(temp_dir.path()).unwrap();
+            store.append("a", &[1u8; 64]).unwrap();
+            store.append("b", &[2u8; 64]).unwrap();
+            store.flush_index().unwrap();
+            let stale_index = std::fs::read(&index_path).unwrap();
+
+            store.remove("a").unwrap();
+            store.compact().unwrap();
+            store.append("c", &[3u8; 64]).unwrap();
+            store.append("d", &[4u8; 64]).unwrap();
+            stale_index
+        };
+
+        // Crash entre el rename de `compact` y `flush_index`
+        std::fs::write(&index_path, stale_index).unwrap();
+
+        let store = QPXPackStore::open(temp_dir.path()).unwrap();
+        assert_eq!(store.len(), 3);
+        assert!(store.read("a").unwrap().is_none());
+        assert_eq!(store.read("b").unwrap().unwrap(), vec![2u8; 64]);
+        assert_eq!(store.read("c").unwrap().unwrap(), vec![3u8; 64]);
+    }
+}
@@ -0,0 +1,230 @@
+//! # Semantic Index - Búsqueda por Embeddings de Templates
+//!
+//! Los `.qpxf` guardan un embedding de 384D empaquetado en 128 píxeles RGB
+//! (`QPXQuantumCore.pixels`). Este módulo lo decodifica de vuelta a vector y
+//! mantiene un índice en memoria para `VoxelDB::query_semantic`.
+//!
+//! ## Características
+//!
+//! - Búsqueda exacta: producto punto sobre vectores normalizados (coseno)
+//! - Consultas por texto (`embed_text`) o por embedding explícito
+//! - Se persiste en el snapshot del índice; `load_all_from_disk` solo decodifica
+//!   los QPX de templates nuevos o modificados
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Dimensión de los embeddings de templates (128 píxeles × 3 canales RGB)
+pub const TEMPLATE_EMBEDDING_DIM: usize = 384;
+
+/// Consulta semántica: texto libre o embedding ya calculado
+#[derive(Debug, Clone, PartialEq)]
+pub enum SemanticQuery {
+    /// Texto; se embebe con `embed_text`
+    Text(String),
+
+    /// Embedding explícito (misma dimensión que los templates indexados)
+    Embedding(Vec<f32>),
+}
+
+impl SemanticQuery {
+    /// Vector de la consulta
+    pub fn to_embedding(&self) -> Vec<f32> {
+        match self {
+            Self::Text(text) => embed_text(text),
+            Self::Embedding(embedding) => embedding.clone(),
+        }
+    }
+}
+
+impl From<&str> for SemanticQuery {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for SemanticQuery {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Vec<f32>> for SemanticQuery {
+    fn from(embedding: Vec<f32>) -> Self {
+        Self::Embedding(embedding)
+    }
+}
+
+impl From<&[f32]> for SemanticQuery {
+    fn from(embedding: &[f32]) -> Self {
+        Self::Embedding(embedding.to_vec())
+    }
+}
+
+/// Embedding determinista de texto por feature hashing (384D, norma 1)
+///
+/// Unigramas y bigramas de palabras en minúsculas se proyectan a un bucket con
+/// signo (FNV-1a), así que textos con vocabulario compartido quedan cerca en
+/// coseno. Texto sin palabras → vector cero.
+pub fn embed_text(text: &str) -> Vec<f32> {
+    let mut embedding = vec![0.0f32; TEMPLATE_EMBEDDING_DIM];
+
+    let lower = text.to_lowercase();
+    let tokens: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let mut add_feature = |feature: &[&str], weight: f32| {
+        let hash = fnv1a(feature);
+        let bucket = (hash % TEMPLATE_EMBEDDING_DIM as u64) as usize;
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        embedding[bucket] += sign * weight;
+    };
+
+    for token in &tokens {
+        add_feature(&[token], 1.0);
+    }
+    for pair in tokens.windows(2) {
+        add_feature(pair, 0.5);
+    }
+
+    normalize(&mut embedding);
+    embedding
+}
+
+/// Índice vectorial en memoria (ID de template → embedding normalizado)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SemanticIndex {
+    vectors: HashMap<String, Vec<f32>>,
+}
+
+impl SemanticIndex {
+    /// Crear índice vacío
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexar (o reemplazar) el embedding de `id`; `false` si el vector es nulo
+    pub fn insert(&mut self, id: &str, embedding: &[f32]) -> bool {
+        let mut vector = embedding.to_vec();
+        if !normalize(&mut vector) {
+            self.vectors.remove(id);
+            return false;
+        }
+        self.vectors.insert(id.to_string(), vector);
+        true
+    }
+
+    /// Eliminar `id` del índice
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.vectors.remove(id).is_some()
+    }
+
+    /// Verificar si `id` está indexado
+    pub fn contains(&self, id: &str) -> bool {
+        self.vectors.contains_key(id)
+    }
+
+    /// Conservar solo los IDs para los que `keep` retorna `true`
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.vectors.retain(|id, _| keep(id));
+    }
+
+    /// Vaciar el índice
+    pub fn clear(&mut self) {
+        self.vectors.clear();
+    }
+
+    /// Número de vectores indexados
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    /// Verificar si el índice está vacío
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Todos los `(id, similitud coseno)` ordenados de mayor a menor similitud
+    ///
+    /// Vectores de otra dimensión que `query` se ignoran.
+    pub fn ranked(&self, query: &[f32]) -> Vec<(String, f32)> {
+        let mut query = query.to_vec();
+        if !normalize(&mut query) {
+            return Vec::new();
+        }
+
+        let mut results: Vec<(String, f32)> = self
+            .vectors
+            .iter()
+            .filter(|(_, v)| v.len() == query.len())
+            .map(|(id, v)| (id.clone(), dot(v, &query)))
+            .collect();
+
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        results
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Normalizar in-place a norma 1; `false` si el vector es nulo
+fn normalize(vector: &mut [f32]) -> bool {
+    let norm = dot(vector, vector).sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return false;
+    }
+    vector.iter_mut().for_each(|x| *x /= norm);
+    true
+}
+
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            hash ^= 0x1f;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        for byte in part.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_text_is_normalized_and_deterministic() {
+        let a = embed_text("Debugging memory leaks in Rust");
+        let b = embed_text("debugging memory leaks in rust");
+
+        assert_eq!(a.len(), TEMPLATE_EMBEDDING_DIM);
+        assert_eq!(a, b);
+        assert!((dot(&a, &a) - 1.0).abs() < 1e-5);
+        assert!(embed_text("  ...  ").iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn test_semantic_index_ranks_shared_vocabulary_first() {
+        let mut index = SemanticIndex::new();
+        index.insert("rust", &embed_text("rust borrow checker lifetimes ownership"));
+        index.insert("cooking", &embed_text("pasta recipe tomato basil garlic"));
+        assert!(!index.insert("empty", &[0.0; TEMPLATE_EMBEDDING_DIM]));
+
+        let ranked = index.ranked(&embed_text("ownership and lifetimes in rust"));
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, "rust");
+        assert!(ranked[0].1 > ranked[1].1);
+
+        assert!(index.ranked(&[1.0, 0.0]).is_empty());
+        assert!(index.remove("rust"));
+        assert_eq!(index.len(), 1);
+    }
+}